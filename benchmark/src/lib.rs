@@ -7,13 +7,13 @@ use hwloc::{CpuSet, ObjectType, Topology, CPUBIND_THREAD};
 use regex::Regex;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
+use std::ops::Range;
 use std::os::unix::prelude::FileExt;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::mpsc::Receiver;
 use std::sync::{Arc, Barrier, Mutex};
 use std::time::{Duration, Instant};
 
-const K_CHECKPOINT_SECONDS: u64 = 30;
+pub const K_CHECKPOINT_SECONDS: u64 = 30;
 const K_COMPLETE_PENDING_INTERVAL: usize = 1600;
 const K_REFRESH_INTERVAL: usize = 64;
 const K_RUN_TIME: u64 = 360;
@@ -26,6 +26,7 @@ const K_NANOS_PER_SECOND: usize = 1000000000;
 
 const K_THREAD_STACK_SIZE: usize = 4 * 1024 * 1024;
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum Operation {
     Read,
     Upsert,
@@ -164,21 +165,21 @@ pub fn populate_store(store: &Arc<FasterKv>, keys: &Arc<Vec<u64>>, num_threads:
                     .unwrap();
             }
 
-            let _session = store.start_session();
+            let _session = store.start_session().unwrap();
             let mut chunk_idx = idx.fetch_add(K_CHUNK_SIZE, Ordering::SeqCst);
             while chunk_idx < K_INIT_COUNT {
                 for i in chunk_idx..(chunk_idx + K_CHUNK_SIZE) {
                     if i % K_REFRESH_INTERVAL == 0 {
                         store.refresh();
                         if i % K_COMPLETE_PENDING_INTERVAL == 0 {
-                            store.complete_pending(false);
+                            store.complete_pending(false).unwrap();
                         }
                     }
-                    store.upsert(&*keys.get(i as usize).unwrap(), &42, i as u64);
+                    store.upsert(&*keys.get(i as usize).unwrap(), &42, i as u64).unwrap();
                 }
                 chunk_idx = idx.fetch_add(K_CHUNK_SIZE, Ordering::SeqCst);
             }
-            store.complete_pending(true);
+            store.complete_pending(true).unwrap();
             store.stop_session();
         }));
     }
@@ -187,10 +188,53 @@ pub fn populate_store(store: &Arc<FasterKv>, keys: &Arc<Vec<u64>>, num_threads:
     }
 }
 
+// Issues the operations for `range` in order, as one batch per run of consecutive operations of
+// the same type. Each operation takes the next serial number from `serial`.
+fn run_batch<F: Fn(usize) -> Operation>(
+    store: &FasterKv,
+    keys: &[u64],
+    range: Range<usize>,
+    serial: &mut u64,
+    op_allocator: F,
+) -> (usize, usize, usize) {
+    let mut counts = (0, 0, 0);
+    let mut run_start = range.start;
+    while run_start < range.end {
+        let operation = op_allocator(run_start);
+        let mut run_end = run_start + 1;
+        while run_end < range.end && op_allocator(run_end) == operation {
+            run_end += 1;
+        }
+        let run = &keys[run_start..run_end];
+        match operation {
+            Operation::Read => {
+                store.read_batch::<u64, i32, _>(run, *serial).unwrap();
+                counts.0 += run.len();
+            }
+            Operation::Upsert => {
+                store
+                    .upsert_batch(run.iter().map(|key| (key, &42)), *serial)
+                    .unwrap();
+                counts.1 += run.len();
+            }
+            Operation::Rmw => {
+                store
+                    .rmw_batch(run.iter().map(|key| (key, &5)), *serial)
+                    .unwrap();
+                counts.2 += run.len();
+            }
+        }
+        *serial += run.len() as u64;
+        run_start = run_end;
+    }
+    counts
+}
+
 pub fn run_benchmark<F: Fn(usize) -> Operation + Send + Copy + 'static>(
     store: &Arc<FasterKv>,
     keys: &Arc<Vec<u64>>,
     num_threads: u8,
+    batch_size: usize,
     op_allocator: F,
 ) {
     let topo = Arc::new(Mutex::new(Topology::new()));
@@ -224,8 +268,9 @@ pub fn run_benchmark<F: Fn(usize) -> Operation + Send + Copy + 'static>(
                     let mut reads = 0;
                     let mut upserts = 0;
                     let mut rmws = 0;
+                    let mut serial = 1;
 
-                    let _session = store.start_session();
+                    let _session = store.start_session().unwrap();
 
                     barrier.wait();
                     let start = Instant::now();
@@ -237,32 +282,59 @@ pub fn run_benchmark<F: Fn(usize) -> Operation + Send + Copy + 'static>(
                             }
                             chunk_idx = idx.fetch_add(K_CHUNK_SIZE, Ordering::SeqCst);
                         }
+                        if batch_size > 1 {
+                            let chunk_end = chunk_idx + K_CHUNK_SIZE;
+                            for batch_idx in (chunk_idx..chunk_end).step_by(batch_size) {
+                                let batch_end = std::cmp::min(batch_idx + batch_size, chunk_end);
+                                // Batches wait for their own pending operations, the others are
+                                // completed as often as when issuing operations one by one
+                                if batch_idx / K_REFRESH_INTERVAL != batch_end / K_REFRESH_INTERVAL
+                                {
+                                    store.refresh();
+                                    if batch_idx / K_COMPLETE_PENDING_INTERVAL
+                                        != batch_end / K_COMPLETE_PENDING_INTERVAL
+                                    {
+                                        store.complete_pending(false).unwrap();
+                                    }
+                                }
+                                let counts = run_batch(
+                                    &store,
+                                    &keys,
+                                    batch_idx..batch_end,
+                                    &mut serial,
+                                    op_allocator,
+                                );
+                                reads += counts.0;
+                                upserts += counts.1;
+                                rmws += counts.2;
+                            }
+                            continue;
+                        }
                         for i in chunk_idx..(chunk_idx + K_CHUNK_SIZE) {
                             if i % K_REFRESH_INTERVAL == 0 {
                                 store.refresh();
                                 if i % K_COMPLETE_PENDING_INTERVAL == 0 {
-                                    store.complete_pending(false);
+                                    store.complete_pending(false).unwrap();
                                 }
                             }
                             match op_allocator(i) {
                                 Operation::Read => {
-                                    let (_, _): (u8, Receiver<i32>) =
-                                        store.read(&*keys.get(i).unwrap(), 1);
+                                    store.read::<u64, i32>(&*keys.get(i).unwrap(), 1).unwrap();
                                     reads += 1;
                                 }
                                 Operation::Upsert => {
-                                    store.upsert(&*keys.get(i).unwrap(), &42, 1);
+                                    store.upsert(&*keys.get(i).unwrap(), &42, 1).unwrap();
                                     upserts += 1;
                                 }
                                 Operation::Rmw => {
-                                    store.rmw(&*keys.get(i).unwrap(), &5, 1);
+                                    store.rmw(&*keys.get(i).unwrap(), &5, 1).unwrap();
                                     rmws += 1;
                                 }
                             }
                         }
                     }
 
-                    store.complete_pending(true);
+                    store.complete_pending(true).unwrap();
                     store.stop_session();
                     let duration = Instant::now().duration_since(start);
 
@@ -283,17 +355,11 @@ pub fn run_benchmark<F: Fn(usize) -> Operation + Send + Copy + 'static>(
 
     barrier.wait();
     let start = Instant::now();
-    let mut last_checkpoint = Instant::now();
-    let mut num_checkpoints = 0;
+    // The store's checkpoint policy takes a checkpoint every K_CHECKPOINT_SECONDS
+    let checkpoints_before = store.checkpoints().len();
 
     while Instant::now().duration_since(start).as_secs() < K_RUN_TIME {
         std::thread::sleep(Duration::from_secs(1));
-        if Instant::now().duration_since(last_checkpoint).as_secs() > K_CHECKPOINT_SECONDS {
-            println!("Checkpointing...");
-            store.checkpoint();
-            num_checkpoints += 1;
-            last_checkpoint = Instant::now();
-        }
     }
 
     done.store(true, Ordering::SeqCst);
@@ -307,6 +373,7 @@ pub fn run_benchmark<F: Fn(usize) -> Operation + Send + Copy + 'static>(
         total_counts.3 += duration;
     }
 
+    let num_checkpoints = store.checkpoints().len() - checkpoints_before;
     println!(
         "Finished benchmark: {} checkpoints, {} reads, {} writes, {} rmws. {} ops/second/thread",
         num_checkpoints,
@@ -2,8 +2,9 @@ extern crate clap;
 
 use benchmark::*;
 use clap::{App, Arg, SubCommand};
-use faster_rs::FasterKvBuilder;
+use faster_rs::{CheckpointPolicy, FasterKvBuilder};
 use std::sync::Arc;
+use std::time::Duration;
 
 fn main() {
     let matches = App::new("faster-rs Benchmark")
@@ -32,6 +33,15 @@ fn main() {
                         .display_order(1)
                         .help("Number of threads to use"),
                 )
+                .arg(
+                    Arg::with_name("batch-size")
+                        .short("b")
+                        .long("batch-size")
+                        .takes_value(true)
+                        .default_value("1")
+                        .display_order(2)
+                        .help("Number of operations to issue per batch"),
+                )
                 .arg(
                     Arg::with_name("load")
                         .required(true)
@@ -80,6 +90,11 @@ fn main() {
         let num_threads: u8 = num_threads
             .parse()
             .expect("num-threads argument must be integer");
+        let batch_size: usize = matches
+            .value_of("batch-size")
+            .unwrap()
+            .parse()
+            .expect("batch-size argument must be integer");
         let load_keys_file = matches
             .value_of("load")
             .expect("File containing load transactions not specified");
@@ -99,14 +114,22 @@ fn main() {
         let table_size: u64 = 134217728;
         let log_size: u64 = 17179869184;
         let dir_path = String::from("benchmark_store");
-        let store = Arc::new(FasterKvBuilder::new(table_size, log_size).with_disk(&dir_path).build().unwrap());
+        let store = Arc::new(
+            FasterKvBuilder::new(table_size, log_size)
+                .with_disk(&dir_path)
+                .with_checkpoint_policy(CheckpointPolicy::Interval(Duration::from_secs(
+                    K_CHECKPOINT_SECONDS,
+                )))
+                .build()
+                .unwrap(),
+        );
         let (load_keys, txn_keys) = load_files(load_keys_file, run_keys_file);
         let load_keys = Arc::new(load_keys);
         let txn_keys = Arc::new(txn_keys);
         println!("Populating datastore");
         populate_store(&store, &load_keys, num_threads);
         println!("Beginning benchmark");
-        run_benchmark(&store, &txn_keys, num_threads, op_allocator);
+        run_benchmark(&store, &txn_keys, num_threads, batch_size, op_allocator);
         match store.clean_storage() {
             Ok(_) => { /*no-op*/ }
             Err(_) => eprintln!("Unable to clear storage"),
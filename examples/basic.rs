@@ -1,7 +1,6 @@
 extern crate faster_rs;
 
-use faster_rs::{status, FasterKv};
-use std::sync::mpsc::Receiver;
+use faster_rs::{FasterKv, Status};
 
 fn main() {
     // Create a Key-Value Store
@@ -12,24 +11,24 @@ fn main() {
 
     // Upsert
     for i in 0..1000 {
-        let upsert = store.upsert(&(key0 + i), &(value0 + i), i);
-        assert!(upsert == status::OK || upsert == status::PENDING);
+        let upsert = store.upsert(&(key0 + i), &(value0 + i), i).unwrap();
+        assert!(upsert == Status::Ok || upsert == Status::Pending);
     }
 
     // Read-Modify-Write
     for i in 0..1000 {
-        let rmw = store.rmw(&(key0 + i), &(5 as u64), i + 1000);
-        assert!(rmw == status::OK || rmw == status::PENDING);
+        let rmw = store.rmw(&(key0 + i), &(5 as u64), i + 1000).unwrap();
+        assert!(rmw == Status::Ok || rmw == Status::Pending);
     }
 
     assert!(store.size() > 0);
 
     // Read
     for i in 0..1000 {
-        // Note: need to provide type annotation for the Receiver
-        let (read, recv): (u8, Receiver<u64>) = store.read(&(key0 + i), i);
-        assert!(read == status::OK || read == status::PENDING);
-        let val = recv.recv().unwrap();
+        // Note: need to provide the key and value types to read
+        let (read, recv) = store.read::<u64, u64>(&(key0 + i), i).unwrap();
+        assert!(read == Status::Ok || read == Status::Pending);
+        let val = recv.recv().unwrap().unwrap();
         assert_eq!(val, value0 + i + modification);
         println!("Key: {}, Value: {}", key0 + i, val);
     }
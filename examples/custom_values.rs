@@ -1,9 +1,8 @@
 extern crate faster_rs;
 extern crate serde_derive;
 
-use faster_rs::{status, FasterKv};
+use faster_rs::{FasterKv, Status};
 use serde_derive::{Deserialize, Serialize};
-use std::sync::mpsc::Receiver;
 
 // Note: Debug annotation is just for printing later
 #[derive(Serialize, Deserialize, Debug)]
@@ -22,15 +21,15 @@ fn main() {
     };
 
     // Upsert
-    let upsert = store.upsert(&key, &value, 1);
-    assert!(upsert == status::OK || upsert == status::PENDING);
+    let upsert = store.upsert(&key, &value, 1).unwrap();
+    assert!(upsert == Status::Ok || upsert == Status::Pending);
 
     assert!(store.size() > 0);
 
-    // Note: need to provide type annotation for the Receiver
-    let (read, recv): (u8, Receiver<MyValue>) = store.read(&key, 1);
-    assert!(read == status::OK || read == status::PENDING);
-    let val = recv.recv().unwrap();
+    // Note: need to provide the key and value types to read
+    let (read, recv) = store.read::<u64, MyValue>(&key, 1).unwrap();
+    assert!(read == Status::Ok || read == Status::Pending);
+    let val = recv.recv().unwrap().unwrap();
     println!("Key: {}, Value: {:?}", key, val);
 
     // Clear used storage
@@ -2,8 +2,6 @@ extern crate faster_rs;
 
 use faster_rs::*;
 use std::env;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::mpsc::Receiver;
 use std::sync::Arc;
 
 const TABLE_SIZE: u64 = 1 << 15;
@@ -50,38 +48,36 @@ fn main() {
 }
 
 fn populate(num_threads: usize) -> () {
-    if let Ok(store) = FasterKvBuilder::new(TABLE_SIZE, LOG_SIZE).with_disk(STORAGE_DIR).build() {
+    // A checkpoint is taken in the background every CHECKPOINT_INTERVAL operations per thread
+    let policy = CheckpointPolicy::Operations(CHECKPOINT_INTERVAL * num_threads as u64);
+    if let Ok(store) = FasterKvBuilder::new(TABLE_SIZE, LOG_SIZE)
+        .with_disk(STORAGE_DIR)
+        .with_checkpoint_policy(policy)
+        .build()
+    {
         let store = Arc::new(store);
         let mut threads = vec![];
-        let num_active_threads = Arc::new(AtomicUsize::new(0));
         for thread_id in 0..num_threads {
             let store = Arc::clone(&store);
-            let num_active_threads = Arc::clone(&num_active_threads);
             threads.push(std::thread::spawn(move || {
                 // Populate Store
-                let _session = store.start_session();
-                num_active_threads.fetch_add(1, Ordering::SeqCst);
+                let session = store
+                    .session_with_refresh_interval(REFRESH_INTERVAL)
+                    .unwrap();
 
                 for i in 0..NUM_OPS {
                     let idx = i as u64;
-                    store.rmw(&(idx % NUM_UNIQUE_KEYS), &(1 as u64), idx);
-
-                    if (idx % CHECKPOINT_INTERVAL == 0)
-                        && num_active_threads.load(Ordering::SeqCst) == num_threads
-                    {
-                        let check = store.checkpoint().unwrap();
-                        println!("Calling checkpoint with token {}", check.token);
-                    }
+                    session
+                        .rmw(&(idx % NUM_UNIQUE_KEYS), &(1 as u64), idx)
+                        .unwrap();
 
                     if (idx % COMPLETE_PENDING_INTERVAL) == 0 {
-                        store.complete_pending(false);
-                    } else if (idx % REFRESH_INTERVAL) == 0 {
-                        store.refresh();
+                        session.complete_pending(false).unwrap();
                     }
                 }
 
-                store.complete_pending(true);
-                store.stop_session();
+                // Completes pending operations and stops the session
+                drop(session);
                 println!("Thread {} finished populating", thread_id);
             }));
         }
@@ -89,22 +85,25 @@ fn populate(num_threads: usize) -> () {
             t.join().expect("Something went wrong in a thread");
         }
         println!("Threads finished populating");
+        for checkpoint in store.checkpoints() {
+            println!("Took checkpoint with token {}", checkpoint.token);
+        }
         println!("Store size: {}", store.size());
         println!("Verifying values");
 
-        store.start_session();
+        store.start_session().unwrap();
         let mut read_results = Vec::with_capacity(NUM_UNIQUE_KEYS as usize);
         for idx in 0..NUM_UNIQUE_KEYS {
-            let (_, receiver): (u8, Receiver<u64>) = store.read(&idx, idx);
+            let (_, receiver) = store.read::<u64, u64>(&idx, idx).unwrap();
             read_results.insert(idx as usize, receiver);
         }
-        store.complete_pending(true);
+        store.complete_pending(true).unwrap();
         store.stop_session();
 
         let expected_value: u64 = (num_threads as u64) * NUM_OPS / NUM_UNIQUE_KEYS;
         for idx in 0..NUM_UNIQUE_KEYS {
             match read_results[idx as usize].recv() {
-                Ok(val) => {
+                Ok(Ok(val)) => {
                     if val != expected_value {
                         println!(
                             "Error for {}, expected {}, actual {}",
@@ -112,7 +111,7 @@ fn populate(num_threads: usize) -> () {
                         );
                     }
                 }
-                Err(_) => {
+                _ => {
                     println!("Error reading {}", idx);
                 }
             }
@@ -124,29 +123,31 @@ fn populate(num_threads: usize) -> () {
 
 fn recover(token: String, num_threads: usize) -> () {
     println!("Attempting to recover");
-    if let Ok(store) = FasterKvBuilder::new(TABLE_SIZE, LOG_SIZE).with_disk(STORAGE_DIR).build() {
+    if let Ok(store) = FasterKvBuilder::new(TABLE_SIZE, LOG_SIZE)
+        .with_disk(STORAGE_DIR)
+        .build()
+    {
         match store.recover(token.clone(), token.clone()) {
             Ok(rec) => {
                 println!("Recover version: {}", rec.version);
-                println!("Recover status: {}", rec.status);
+                println!("Recover status: {:?}", rec.status);
                 println!("Recovered sessions: {:?}", rec.session_ids);
-                
+
                 let mut serial_nums = vec![];
                 for id in rec.session_ids {
-                    serial_nums.push(store.continue_session(id));
+                    serial_nums.push(store.continue_session(id.to_string()).unwrap());
                     store.stop_session();
                 }
 
-                store.start_session();
+                store.start_session().unwrap();
                 let mut read_results = Vec::with_capacity(NUM_UNIQUE_KEYS as usize);
                 for idx in 0..NUM_UNIQUE_KEYS {
-                    let (_, receiver): (u8, Receiver<u64>) = store.read(&idx, idx);
+                    let (_, receiver) = store.read::<u64, u64>(&idx, idx).unwrap();
                     read_results.insert(idx as usize, receiver);
                 }
-                store.complete_pending(true);
+                store.complete_pending(true).unwrap();
                 store.stop_session();
 
-
                 println!("Generating expected values");
                 let mut expected_results = Vec::with_capacity(NUM_UNIQUE_KEYS as usize);
                 expected_results.resize(NUM_UNIQUE_KEYS as usize, 0);
@@ -176,9 +177,10 @@ fn recover(token: String, num_threads: usize) -> () {
                 let mut incorrect = 0;
                 for i in 0..NUM_OPS {
                     let idx = i as u64;
-                    let (status, recv): (u8, Receiver<u64>) =
-                        store.read(&(idx % NUM_UNIQUE_KEYS), idx);
-                    if let Ok(val) = recv.recv() {
+                    let (status, recv) = store
+                        .read::<u64, u64>(&(idx % NUM_UNIQUE_KEYS), idx)
+                        .unwrap();
+                    if let Ok(Ok(val)) = recv.recv() {
                         let expected = *expected_results
                             .get((idx % NUM_UNIQUE_KEYS) as usize)
                             .unwrap();
@@ -190,7 +192,10 @@ fn recover(token: String, num_threads: usize) -> () {
                             incorrect += 1;
                         }
                     } else {
-                        println!("Failure to read with status: {}, and key: {}", status, idx);
+                        println!(
+                            "Failure to read with status: {:?}, and key: {}",
+                            status, idx
+                        );
                     }
                 }
                 println!("{} incorrect recoveries", incorrect);
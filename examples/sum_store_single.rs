@@ -2,7 +2,6 @@ extern crate faster_rs;
 
 use faster_rs::*;
 use std::env;
-use std::sync::mpsc::Receiver;
 
 const TABLE_SIZE: u64 = 1 << 15;
 const LOG_SIZE: u64 = 1024 * 1024 * 1024;
@@ -48,12 +47,12 @@ fn populate() -> () {
         .build()
     {
         // Populate Store
-        let session = store.start_session();
+        let session = store.start_session().unwrap();
         println!("Starting Session {}", session);
 
         for i in 0..NUM_OPS {
             let idx = i as u64;
-            store.rmw(&(idx % NUM_UNIQUE_KEYS), &(1 as u64), idx);
+            store.rmw(&(idx % NUM_UNIQUE_KEYS), &(1 as u64), idx).unwrap();
 
             if (idx % CHECKPOINT_INTERVAL) == 0 {
                 let check = store.checkpoint().unwrap();
@@ -61,7 +60,7 @@ fn populate() -> () {
             }
 
             if (idx % COMPLETE_PENDING_INTERVAL) == 0 {
-                store.complete_pending(false);
+                store.complete_pending(false).unwrap();
             } else if (idx % REFRESH_INTERVAL) == 0 {
                 store.refresh();
             }
@@ -70,7 +69,7 @@ fn populate() -> () {
         println!("Dumping distribution");
         store.dump_distribution();
         println!("Stopping Session {}", session);
-        store.complete_pending(true);
+        store.complete_pending(true).unwrap();
         store.stop_session();
         println!("Store size: {}", store.size());
     } else {
@@ -88,10 +87,10 @@ fn recover(token: String) -> () {
         match recover_store.recover(token.clone(), token.clone()) {
             Ok(rec) => {
                 println!("Recover version: {}", rec.version);
-                println!("Recover status: {}", rec.status);
+                println!("Recover status: {:?}", rec.status);
                 println!("Recovered sessions: {:?}", rec.session_ids);
                 let persisted_count =
-                    recover_store.continue_session(rec.session_ids.first().cloned().unwrap());
+                    recover_store.continue_session(rec.session_ids.first().unwrap().to_string()).unwrap();
                 println!("Session persisted until: {}", persisted_count);
 
                 let mut expected_results = Vec::with_capacity(NUM_UNIQUE_KEYS as usize);
@@ -107,9 +106,10 @@ fn recover(token: String) -> () {
                 let mut incorrect = 0;
                 for i in 0..NUM_OPS {
                     let idx = i as u64;
-                    let (status, recv): (u8, Receiver<u64>) =
-                        recover_store.read(&(idx % NUM_UNIQUE_KEYS), idx);
-                    if let Ok(val) = recv.recv() {
+                    let (status, recv) = recover_store
+                        .read::<u64, u64>(&(idx % NUM_UNIQUE_KEYS), idx)
+                        .unwrap();
+                    if let Ok(Ok(val)) = recv.recv() {
                         let expected = *expected_results
                             .get((idx % NUM_UNIQUE_KEYS) as usize)
                             .unwrap();
@@ -121,7 +121,7 @@ fn recover(token: String) -> () {
                             incorrect += 1;
                         }
                     } else {
-                        println!("Failure to read with status: {}, and key: {}", status, idx);
+                        println!("Failure to read with status: {:?}, and key: {}", status, idx);
                     }
                 }
                 println!("{} incorrect recoveries", incorrect);
@@ -2,7 +2,8 @@ extern crate bindgen;
 
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use cmake::Config;
 
 // Credit to: https://github.com/rust-rocksdb/rust-rocksdb/blob/master/librocksdb-sys/build.rs
@@ -19,7 +20,7 @@ fn fail_on_empty_directory(name: &str) {
 
 fn faster_bindgen() {
     let bindings = bindgen::Builder::default()
-        .header("FASTER/cc/src/core/faster-c.h")
+        .header("faster-c/faster-c.h")
         .blacklist_type("max_align_t") // https://github.com/rust-lang-nursery/rust-bindgen/issues/550
         .ctypes_prefix("libc")
         .generate()
@@ -32,18 +33,64 @@ fn faster_bindgen() {
 }
 
 
+// The C interface is kept in this crate, next to the bindings built from it
+fn faster_c() {
+    cc::Build::new()
+        .cpp(true)
+        .file("faster-c/faster-c.cpp")
+        .include("faster-c")
+        .include("FASTER/cc/src")
+        .flag("--std=c++14")
+        .warnings(false)
+        .compile("faster-c");
+}
+
+// The FASTER library bundles the fork's own faster-c, which would define every faster_* symbol a
+// second time, so it is removed from the archive
+fn strip_bundled_interface(lib_dir: &Path) {
+    let archive = lib_dir.join("libfaster.a");
+    let ar = env::var("AR").unwrap_or_else(|_| "ar".to_owned());
+    let members = Command::new(&ar)
+        .arg("t")
+        .arg(&archive)
+        .output()
+        .expect("unable to list the members of the FASTER library");
+    let bundled: Vec<String> = String::from_utf8_lossy(&members.stdout)
+        .lines()
+        .filter(|member| member.starts_with("faster-c"))
+        .map(str::to_owned)
+        .collect();
+    if !bundled.is_empty() {
+        let status = Command::new(&ar)
+            .arg("d")
+            .arg(&archive)
+            .args(&bundled)
+            .status()
+            .expect("unable to remove faster-c from the FASTER library");
+        assert!(
+            status.success(),
+            "unable to remove faster-c from the FASTER library"
+        );
+    }
+}
+
 fn main() {
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=faster-c/");
     println!("cargo:rerun-if-changed=FASTER/");
 
     fail_on_empty_directory("FASTER");
 
     faster_bindgen();
+    // Linked ahead of the FASTER library, which it depends on
+    faster_c();
 
     let dst = Config::new("FASTER/cc")
         .cflag("--std=c++11 ")
         .build();
 
+    strip_bundled_interface(&dst.join("build"));
+
     println!("cargo:rustc-link-search=native={}/{}", dst.display(), "build");
     // Fix this...
     println!("cargo:rustc-link-lib=static=faster");
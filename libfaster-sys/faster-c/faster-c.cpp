@@ -0,0 +1,1429 @@
+// C interface to FASTER used by faster-rs, see faster-c.h.
+
+#include <algorithm>
+#include <atomic>
+#include <chrono>
+#include <condition_variable>
+#include <cstdlib>
+#include <cstring>
+#include <memory>
+#include <mutex>
+#include <string>
+#include <thread>
+#include <unordered_map>
+#include <utility>
+#include <vector>
+
+#include "core/faster.h"
+#include "core/log_scan.h"
+#include "device/file_system_disk.h"
+#include "device/null_disk.h"
+
+#include "faster-c.h"
+
+using namespace FASTER::core;
+
+namespace {
+
+// FNV-1a over the encoded key
+inline uint64_t hash_bytes(const uint8_t* data, uint64_t length) {
+  uint64_t hash = 14695981039346656037ull;
+  for(uint64_t i = 0; i < length; ++i) {
+    hash ^= data[i];
+    hash *= 1099511628211ull;
+  }
+  return hash;
+}
+
+// Bytes handed over by the caller, which are released once the call returns. Copies own their
+// bytes, which is how contexts keep them alive once FASTER deep-copies an operation that went
+// pending.
+class Bytes {
+ public:
+  Bytes(const uint8_t* data, uint64_t length)
+    : data_{ data }
+    , length_{ length } {
+  }
+  Bytes(const Bytes& other)
+    : owned_{ other.data_, other.data_ + other.length_ }
+    , data_{ owned_.data() }
+    , length_{ other.length_ } {
+  }
+  Bytes& operator=(const Bytes&) = delete;
+
+  inline const uint8_t* data() const {
+    return data_;
+  }
+  inline uint64_t length() const {
+    return length_;
+  }
+
+ private:
+  std::vector<uint8_t> owned_;
+  const uint8_t* data_;
+  uint64_t length_;
+};
+
+// The length of the bytes of a key, without FASTER_INTERNAL_KEY
+inline uint64_t untagged(uint64_t key_length) {
+  return key_length & ~FASTER_INTERNAL_KEY;
+}
+
+// An encoded key. Keys built from a buffer point at it, while the copy FASTER makes into a
+// record stores the bytes inline, right after the header. The length keeps the
+// FASTER_INTERNAL_KEY flag it was passed with, so internal keys never equal those of callers.
+class Key {
+ public:
+  Key(const uint8_t* data, uint64_t tagged_length)
+    : length_{ tagged_length }
+    , external_{ data } {
+  }
+  Key(const Key& other)
+    : length_{ other.length_ }
+    , external_{ nullptr } {
+    std::memcpy(reinterpret_cast<uint8_t*>(this + 1), other.data(), other.length());
+  }
+  Key& operator=(const Key&) = delete;
+
+  inline uint32_t size() const {
+    return static_cast<uint32_t>(sizeof(Key) + length());
+  }
+  inline KeyHash GetHash() const {
+    uint64_t hash = hash_bytes(data(), length());
+    return KeyHash{ internal() ? ~hash : hash };
+  }
+  inline bool operator==(const Key& other) const {
+    return length_ == other.length_ && std::memcmp(data(), other.data(), length()) == 0;
+  }
+  inline bool operator!=(const Key& other) const {
+    return !(*this == other);
+  }
+
+  inline const uint8_t* data() const {
+    return external_ != nullptr ? external_ : reinterpret_cast<const uint8_t*>(this + 1);
+  }
+  inline uint64_t length() const {
+    return untagged(length_);
+  }
+  // The length with FASTER_INTERNAL_KEY set for internal keys
+  inline uint64_t tagged_length() const {
+    return length_;
+  }
+  inline bool internal() const {
+    return (length_ & FASTER_INTERNAL_KEY) != 0;
+  }
+
+ private:
+  uint64_t length_;
+  const uint8_t* external_;
+};
+
+// An encoded value, stored inline right after its length.
+//
+// Values in the mutable region are updated in place when the new value has the same length,
+// under a generation lock like that of FASTER's variable-length samples: writers lock the
+// value while they copy the new bytes in, and readers copy it out, retrying if a writer got in
+// between. A writer whose value does not fit marks the value replaced, so that every later
+// writer appends a record as well rather than wait for a lock that is never released.
+//
+// A value only holds data once it is set. Log pages are zeroed when allocated, so the value of
+// a tombstone FASTER appends holds none, and neither does a cleared value, which conditional
+// Read-Modify-Writes write for a key they delete. Both read as a missing key.
+class Value {
+ public:
+  Value()
+    : control_{ 0 }
+    , length_{ 0 }
+    , live_{ false } {
+  }
+
+  static inline uint32_t size(uint64_t length) {
+    return static_cast<uint32_t>(sizeof(Value) + length);
+  }
+  inline uint32_t size() const {
+    return size(length_);
+  }
+
+  inline const uint8_t* data() const {
+    return reinterpret_cast<const uint8_t*>(this + 1);
+  }
+  inline uint64_t length() const {
+    return length_;
+  }
+  inline bool live() const {
+    return live_;
+  }
+
+  // Only for records no other thread can see yet
+  inline void Set(const uint8_t* data, uint64_t length) {
+    length_ = static_cast<uint32_t>(length);
+    live_ = true;
+    std::memcpy(reinterpret_cast<uint8_t*>(this + 1), data, length);
+  }
+  inline void Clear() {
+    length_ = 0;
+    live_ = false;
+  }
+
+  // Copies a consistent snapshot of the bytes of a value that may be updated in place
+  inline void CopyTo(std::vector<uint8_t>& out) const {
+    for(;;) {
+      uint64_t before = control_.load();
+      if((before & kLocked) != 0) {
+        std::this_thread::yield();
+        continue;
+      }
+      out.assign(data(), data() + length_);
+      if(control_.load() == before) {
+        return;
+      }
+    }
+  }
+
+  // Locks the value for an update in place, unless it was replaced
+  inline bool TryLock() {
+    for(;;) {
+      uint64_t expected = control_.load();
+      if((expected & kReplaced) != 0) {
+        return false;
+      }
+      if((expected & kLocked) == 0 &&
+         control_.compare_exchange_weak(expected, expected | kLocked)) {
+        return true;
+      }
+      std::this_thread::yield();
+    }
+  }
+  // Unlocks the value, marking it replaced when the update did not fit
+  inline void Unlock(bool replaced) {
+    uint64_t generation = ((control_.load() & kGeneration) + 1) & kGeneration;
+    control_.store(replaced ? generation | kReplaced : generation);
+  }
+  // Overwrites the bytes of a live value of the same length while it is locked
+  inline void Overwrite(const uint8_t* data, uint64_t length) {
+    std::memcpy(reinterpret_cast<uint8_t*>(this + 1), data, length);
+  }
+
+  // Updates the value in place if the new bytes have its length
+  inline bool TryUpdate(const uint8_t* data, uint64_t length) {
+    if(!TryLock()) {
+      return false;
+    }
+    if(!live_ || length != length_) {
+      Unlock(true);
+      return false;
+    }
+    Overwrite(data, length);
+    Unlock(false);
+    return true;
+  }
+
+ private:
+  static constexpr uint64_t kLocked = 1ull << 63;
+  static constexpr uint64_t kReplaced = 1ull << 62;
+  static constexpr uint64_t kGeneration = kReplaced - 1;
+
+  std::atomic<uint64_t> control_;
+  uint32_t length_;
+  bool live_;
+};
+
+class UpsertContext : public IAsyncContext {
+ public:
+  typedef Key key_t;
+  typedef Value value_t;
+
+  UpsertContext(const uint8_t* key, uint64_t key_length, const uint8_t* value,
+                uint64_t value_length)
+    : key_bytes_{ key, untagged(key_length) }
+    , key_{ key, key_length }
+    , value_{ value, value_length } {
+  }
+  UpsertContext(const UpsertContext& other)
+    : key_bytes_{ other.key_bytes_ }
+    , key_{ key_bytes_.data(), other.key_.tagged_length() }
+    , value_{ other.value_ } {
+  }
+
+  inline const Key& key() const {
+    return key_;
+  }
+  inline uint32_t value_size() const {
+    return Value::size(value_.length());
+  }
+  inline void Put(Value& value) {
+    value.Set(value_.data(), value_.length());
+  }
+  inline bool PutAtomic(Value& value) {
+    // Values of another length do not fit the record, so FASTER appends a new one for them
+    return value.TryUpdate(value_.data(), value_.length());
+  }
+
+ protected:
+  Status DeepCopy_Internal(IAsyncContext*& context_copy) {
+    return IAsyncContext::DeepCopy_Internal(*this, context_copy);
+  }
+
+ private:
+  Bytes key_bytes_;
+  Key key_;
+  Bytes value_;
+};
+
+class ReadContext : public IAsyncContext {
+ public:
+  typedef Key key_t;
+  typedef Value value_t;
+
+  ReadContext(const uint8_t* key, uint64_t key_length, read_callback cb, void* target)
+    : key_bytes_{ key, untagged(key_length) }
+    , key_{ key, key_length }
+    , cb_{ cb }
+    , target_{ target }
+    , delivered_{ false } {
+  }
+  ReadContext(const ReadContext& other)
+    : key_bytes_{ other.key_bytes_ }
+    , key_{ key_bytes_.data(), other.key_.tagged_length() }
+    , cb_{ other.cb_ }
+    , target_{ other.target_ }
+    , delivered_{ other.delivered_ } {
+  }
+
+  inline const Key& key() const {
+    return key_;
+  }
+  // Values below the mutable region are never updated in place
+  inline void Get(const Value& value) {
+    Deliver(value, value.data(), value.length());
+  }
+  inline void GetAtomic(const Value& value) {
+    std::vector<uint8_t> snapshot;
+    value.CopyTo(snapshot);
+    Deliver(value, snapshot.data(), snapshot.size());
+  }
+  // Invokes the callback for reads that did not find a value
+  inline void Complete(Status result) {
+    if(!delivered_) {
+      delivered_ = true;
+      cb_(target_, nullptr, 0, static_cast<uint32_t>(result));
+    }
+  }
+
+ protected:
+  Status DeepCopy_Internal(IAsyncContext*& context_copy) {
+    return IAsyncContext::DeepCopy_Internal(*this, context_copy);
+  }
+
+ private:
+  inline void Deliver(const Value& value, const uint8_t* data, uint64_t length) {
+    delivered_ = true;
+    if(value.live()) {
+      cb_(target_, data, length, static_cast<uint32_t>(Status::Ok));
+    } else {
+      cb_(target_, nullptr, 0, static_cast<uint32_t>(Status::NotFound));
+    }
+  }
+
+  Bytes key_bytes_;
+  Key key_;
+  read_callback cb_;
+  void* target_;
+  bool delivered_;
+};
+
+class RmwContext : public IAsyncContext {
+ public:
+  typedef Key key_t;
+  typedef Value value_t;
+
+  RmwContext(const uint8_t* key, uint64_t key_length, const uint8_t* modification,
+             uint64_t length, rmw_callback cb)
+    : key_bytes_{ key, untagged(key_length) }
+    , key_{ key, key_length }
+    , modification_{ modification, length }
+    , cb_{ cb }
+    , staged_for_{ nullptr } {
+  }
+  RmwContext(const RmwContext& other)
+    : key_bytes_{ other.key_bytes_ }
+    , key_{ key_bytes_.data(), other.key_.tagged_length() }
+    , modification_{ other.modification_ }
+    , cb_{ other.cb_ }
+    , staged_for_{ nullptr } {
+  }
+
+  inline const Key& key() const {
+    return key_;
+  }
+  inline uint32_t value_size() const {
+    return Value::size(modification_.length());
+  }
+  // The merged value is computed here, so the record FASTER allocates for it is large enough
+  // even if merging the same values twice would not give the same size
+  inline uint32_t value_size(const Value& old_value) const {
+    Stage(old_value);
+    return Value::size(staged_.size());
+  }
+  inline void RmwInitial(Value& value) {
+    value.Set(modification_.data(), modification_.length());
+  }
+  inline void RmwCopy(const Value& old_value, Value& value) {
+    if(staged_for_ != &old_value) {
+      Stage(old_value);
+    }
+    value.Set(staged_.data(), staged_.size());
+  }
+  // Merges while holding the lock of the value, so the merged value is written in place if it
+  // has the same length, and otherwise left staged for the copy FASTER makes next
+  inline bool RmwAtomic(Value& value) {
+    if(!value.TryLock()) {
+      return false;
+    }
+    if(!value.live()) {
+      value.Unlock(true);
+      return false;
+    }
+    Merge(value, value.data(), value.length());
+    if(staged_.size() != value.length()) {
+      value.Unlock(true);
+      return false;
+    }
+    value.Overwrite(staged_.data(), staged_.size());
+    value.Unlock(false);
+    return true;
+  }
+
+ protected:
+  Status DeepCopy_Internal(IAsyncContext*& context_copy) {
+    return IAsyncContext::DeepCopy_Internal(*this, context_copy);
+  }
+
+ private:
+  // A deleted value merges like a missing key
+  inline void Stage(const Value& old_value) const {
+    if(!old_value.live()) {
+      staged_.assign(modification_.data(), modification_.data() + modification_.length());
+      staged_for_ = &old_value;
+      return;
+    }
+    std::vector<uint8_t> current;
+    old_value.CopyTo(current);
+    Merge(old_value, current.data(), current.size());
+  }
+  inline void Merge(const Value& old_value, const uint8_t* current, uint64_t length) const {
+    uint64_t size = cb_(current, length, modification_.data(), modification_.length(), nullptr);
+    staged_.resize(size);
+    cb_(current, length, modification_.data(), modification_.length(), staged_.data());
+    staged_for_ = &old_value;
+  }
+
+  Bytes key_bytes_;
+  Key key_;
+  Bytes modification_;
+  rmw_callback cb_;
+  mutable std::vector<uint8_t> staged_;
+  mutable const Value* staged_for_;
+};
+
+// Actions reported by the callbacks of conditional Read-Modify-Writes
+constexpr uint8_t kRmwUpdate = 0;
+constexpr uint8_t kRmwKeep = 1;
+constexpr uint8_t kRmwDelete = 2;
+
+// What an empty value that is present points at, since missing values are null
+constexpr uint8_t kNoBytes = 0;
+
+// Decides through a callback that carries the caller's context, which learns the status of the
+// operation once it completes
+class ContextDecision {
+ public:
+  ContextDecision(rmw_context_callback cb, rmw_complete_callback complete, void* context,
+                  bool existing_only)
+    : cb_{ cb }
+    , complete_{ complete }
+    , context_{ context }
+    , existing_only_{ existing_only } {
+  }
+
+  inline uint64_t operator()(const uint8_t* current, uint64_t length, uint8_t* dst,
+                             uint8_t& action) const {
+    if(current == nullptr && existing_only_) {
+      action = kRmwKeep;
+      return 0;
+    }
+    return cb_(context_, current, length, dst, &action);
+  }
+  inline void Complete(Status result) const {
+    complete_(context_, static_cast<uint8_t>(result));
+  }
+
+ private:
+  rmw_context_callback cb_;
+  rmw_complete_callback complete_;
+  void* context_;
+  bool existing_only_;
+};
+
+// Decides through a callback that merges a modification, as borrowed from the caller
+class ModificationDecision {
+ public:
+  ModificationDecision(const uint8_t* modification, uint64_t length, rmw_conditional_callback cb)
+    : modification_{ modification, length }
+    , cb_{ cb } {
+  }
+
+  inline uint64_t operator()(const uint8_t* current, uint64_t length, uint8_t* dst,
+                             uint8_t& action) const {
+    return cb_(current, length, modification_.data(), modification_.length(), dst, &action);
+  }
+  inline void Complete(Status result) const {
+  }
+
+ private:
+  Bytes modification_;
+  rmw_conditional_callback cb_;
+};
+
+// Read-Modify-Write whose decision may also keep or delete the record. Records in the mutable
+// region are kept, or updated to a value of the same length, in place. Otherwise a kept record
+// is copied, while a deleted one, like a missing key that is kept absent, is replaced by a
+// cleared value. The decision is made on a snapshot of the current value when FASTER sizes
+// the record, or under its lock when updating it in place, and written by the copy that
+// follows.
+template <class D>
+class ConditionalRmwContext : public IAsyncContext {
+ public:
+  typedef Key key_t;
+  typedef Value value_t;
+
+  ConditionalRmwContext(const uint8_t* key, uint64_t key_length, const D& decision)
+    : key_bytes_{ key, untagged(key_length) }
+    , key_{ key, key_length }
+    , decision_{ decision }
+    , staged_{ false }
+    , staged_for_{ nullptr }
+    , absent_{ false } {
+  }
+  ConditionalRmwContext(const ConditionalRmwContext& other)
+    : key_bytes_{ other.key_bytes_ }
+    , key_{ key_bytes_.data(), other.key_.tagged_length() }
+    , decision_{ other.decision_ }
+    , staged_{ false }
+    , staged_for_{ nullptr }
+    , absent_{ false } {
+  }
+
+  inline const Key& key() const {
+    return key_;
+  }
+  inline uint32_t value_size() const {
+    Stage(nullptr);
+    return StagedSize();
+  }
+  inline uint32_t value_size(const Value& old_value) const {
+    Stage(&old_value);
+    return StagedSize();
+  }
+  inline void RmwInitial(Value& value) {
+    if(!staged_ || staged_for_ != nullptr) {
+      Stage(nullptr);
+    }
+    Write(value);
+  }
+  inline void RmwCopy(const Value& old_value, Value& value) {
+    if(!staged_ || staged_for_ != &old_value) {
+      Stage(&old_value);
+    }
+    Write(value);
+  }
+  inline bool RmwAtomic(Value& value) {
+    if(!value.TryLock()) {
+      return false;
+    }
+    if(!value.live()) {
+      value.Unlock(true);
+      return false;
+    }
+    Decide(&value, value.data(), value.length());
+    if(action_ == kRmwKeep) {
+      value.Unlock(false);
+    } else if(action_ == kRmwUpdate && staged_value_.size() == value.length()) {
+      value.Overwrite(staged_value_.data(), staged_value_.size());
+      value.Unlock(false);
+    } else {
+      value.Unlock(true);
+      return false;
+    }
+    absent_ = false;
+    return true;
+  }
+  // A missing key that stays absent is reported as not found
+  inline Status Result(Status result) const {
+    return result == Status::Ok && absent_ ? Status::NotFound : result;
+  }
+  inline void Complete(Status result) {
+    decision_.Complete(Result(result));
+  }
+
+ protected:
+  Status DeepCopy_Internal(IAsyncContext*& context_copy) {
+    return IAsyncContext::DeepCopy_Internal(*this, context_copy);
+  }
+
+ private:
+  // Deleted values are passed on as a missing key
+  inline void Stage(const Value* old_value) const {
+    if(old_value == nullptr || !old_value->live()) {
+      current_.clear();
+      Decide(old_value, nullptr, 0);
+      return;
+    }
+    old_value->CopyTo(current_);
+    // Present values are never passed as null, even when empty
+    Decide(old_value, current_.empty() ? &kNoBytes : current_.data(), current_.size());
+  }
+  inline void Decide(const Value* old_value, const uint8_t* current, uint64_t length) const {
+    present_ = current != nullptr;
+    action_ = kRmwUpdate;
+    uint64_t size = decision_(current, length, nullptr, action_);
+    if(action_ == kRmwUpdate) {
+      staged_value_.resize(size);
+      decision_(current, length, staged_value_.data(), action_);
+    }
+    staged_ = true;
+    staged_for_ = old_value;
+  }
+  inline uint32_t StagedSize() const {
+    if(action_ == kRmwUpdate) {
+      return Value::size(staged_value_.size());
+    }
+    if(action_ == kRmwKeep && present_) {
+      return Value::size(current_.size());
+    }
+    return Value::size(0);
+  }
+  inline void Write(Value& value) {
+    if(action_ == kRmwUpdate) {
+      value.Set(staged_value_.data(), staged_value_.size());
+    } else if(action_ == kRmwKeep && present_) {
+      value.Set(current_.data(), current_.size());
+    } else {
+      value.Clear();
+    }
+    absent_ = action_ != kRmwUpdate && !present_;
+  }
+
+  Bytes key_bytes_;
+  Key key_;
+  D decision_;
+  mutable bool staged_;
+  mutable const Value* staged_for_;
+  mutable uint8_t action_;
+  mutable bool present_;
+  mutable std::vector<uint8_t> current_;
+  mutable std::vector<uint8_t> staged_value_;
+  bool absent_;
+};
+
+class DeleteContext : public IAsyncContext {
+ public:
+  typedef Key key_t;
+  typedef Value value_t;
+
+  DeleteContext(const uint8_t* key, uint64_t key_length)
+    : key_bytes_{ key, untagged(key_length) }
+    , key_{ key, key_length } {
+  }
+  DeleteContext(const DeleteContext& other)
+    : key_bytes_{ other.key_bytes_ }
+    , key_{ key_bytes_.data(), other.key_.tagged_length() } {
+  }
+
+  inline const Key& key() const {
+    return key_;
+  }
+  // Tombstones carry an empty value, log pages are zeroed when allocated
+  inline uint32_t value_size() const {
+    return Value::size(0);
+  }
+
+ protected:
+  Status DeepCopy_Internal(IAsyncContext*& context_copy) {
+    return IAsyncContext::DeepCopy_Internal(*this, context_copy);
+  }
+
+ private:
+  Bytes key_bytes_;
+  Key key_;
+};
+
+typedef FASTER::environment::QueueIoHandler handler_t;
+typedef FASTER::device::FileSystemDisk<handler_t, 1073741824ull> disk_t;
+typedef FasterKv<Key, Value, FASTER::device::NullDisk> memory_store_t;
+typedef FasterKv<Key, Value, disk_t> disk_store_t;
+
+// Persistence callbacks a checkpoint waits for, numbered per kind. FASTER runs one checkpoint at
+// a time, so the n-th callback of a kind belongs to the n-th checkpoint that asked for one.
+struct Checkpoint {
+  // -1 if the checkpoint does not include that part
+  int64_t index;
+  int64_t hybrid_log;
+};
+
+class Checkpoints {
+ public:
+  Checkpoints()
+    : index_started_{ 0 }
+    , hybrid_log_started_{ 0 } {
+  }
+
+  // Held while a checkpoint is started, so it is numbered before its callbacks are counted.
+  // Callbacks may run on the starting thread, hence recursive.
+  inline std::recursive_mutex& mutex() {
+    return mutex_;
+  }
+
+  // Called with the mutex held, once FASTER accepted a checkpoint
+  inline void Started(const Guid& token, bool index, bool hybrid_log) {
+    Checkpoint checkpoint{ index ? index_started_++ : -1,
+                           hybrid_log ? hybrid_log_started_++ : -1 };
+    started_[token.ToString()] = checkpoint;
+  }
+
+  inline void IndexPersisted(Status result) {
+    std::lock_guard<std::recursive_mutex> lock{ mutex_ };
+    index_persisted_.push_back(result);
+  }
+  inline void HybridLogPersisted(Status result) {
+    std::lock_guard<std::recursive_mutex> lock{ mutex_ };
+    hybrid_log_persisted_.push_back(result);
+  }
+
+  // Ok once every part is persisted, Pending before, and the failure of a part that failed
+  inline Status Get(const std::string& token) {
+    std::lock_guard<std::recursive_mutex> lock{ mutex_ };
+    auto found = started_.find(token);
+    if(found == started_.end()) {
+      return Status::NotFound;
+    }
+    Status index = PartStatus(index_persisted_, found->second.index);
+    Status hybrid_log = PartStatus(hybrid_log_persisted_, found->second.hybrid_log);
+    if(index != Status::Ok && index != Status::Pending) {
+      return index;
+    }
+    if(hybrid_log != Status::Ok && hybrid_log != Status::Pending) {
+      return hybrid_log;
+    }
+    return index == Status::Pending ? index : hybrid_log;
+  }
+
+ private:
+  static inline Status PartStatus(const std::vector<Status>& persisted, int64_t part) {
+    if(part < 0) {
+      return Status::Ok;
+    }
+    if(static_cast<uint64_t>(part) >= persisted.size()) {
+      return Status::Pending;
+    }
+    return persisted[part];
+  }
+
+  std::recursive_mutex mutex_;
+  std::unordered_map<std::string, Checkpoint> started_;
+  int64_t index_started_;
+  int64_t hybrid_log_started_;
+  std::vector<Status> index_persisted_;
+  std::vector<Status> hybrid_log_persisted_;
+};
+
+}  // namespace
+
+// Exactly one of the stores is set, depending on whether the store has storage on disk
+struct faster_t {
+  std::unique_ptr<memory_store_t> memory;
+  std::unique_ptr<disk_store_t> disk;
+  Checkpoints checkpoints;
+  // Sessions started or continued and not stopped yet
+  std::atomic<uint64_t> active_sessions{ 0 };
+  // Buckets of the main hash table, as opened and grown by faster_grow_index
+  std::atomic<uint64_t> table_size{ 0 };
+};
+
+// Exactly one of the iterators is set, matching the store it scans
+struct faster_iterator {
+  std::unique_ptr<ScanIterator<memory_store_t>> memory;
+  std::unique_ptr<ScanIterator<disk_store_t>> disk;
+  // Where the record returned next starts, unless it did not fit on that page
+  Address next;
+  // The value of the record returned last
+  std::vector<uint8_t> value;
+};
+
+namespace {
+
+// Store whose FASTER call the current thread is in. Persistence callbacks carry no context, but
+// only run inside a call of the store being checkpointed.
+thread_local faster_t* calling_store = nullptr;
+
+class CallScope {
+ public:
+  explicit CallScope(faster_t* faster)
+    : previous_{ calling_store } {
+    calling_store = faster;
+  }
+  ~CallScope() {
+    calling_store = previous_;
+  }
+
+ private:
+  faster_t* previous_;
+};
+
+template <class F>
+inline auto with_store(faster_t* faster, F&& f) -> decltype(f(*faster->memory)) {
+  CallScope scope{ faster };
+  if(faster->memory) {
+    return f(*faster->memory);
+  }
+  return f(*faster->disk);
+}
+
+// Reads the log addresses, which FASTER keeps in public members of the hybrid log
+template <class S>
+void collect_stats(S& store, struct faster_stats* stats) {
+  Address begin = store.hlog.begin_address.load();
+  Address head = store.hlog.head_address.load();
+  Address tail = store.hlog.GetTailAddress();
+  Address flushed = store.hlog.flushed_until_address.load();
+  stats->begin_address = begin.control();
+  stats->head_address = head.control();
+  stats->read_only_address = store.hlog.read_only_address.load().control();
+  stats->tail_address = tail.control();
+  stats->in_memory_bytes = tail > head ? tail.control() - head.control() : 0;
+  stats->on_disk_bytes = flushed > begin ? flushed.control() - begin.control() : 0;
+}
+
+// Starts a checkpoint of a store on disk with `start`, which returns whether FASTER accepted it
+template <class F>
+faster_checkpoint_result* checkpoint(faster_t* faster, bool index, bool hybrid_log, F&& start) {
+  if(!faster->disk) {
+    return nullptr;
+  }
+  CallScope scope{ faster };
+  Guid token;
+  bool checked;
+  {
+    std::lock_guard<std::recursive_mutex> lock{ faster->checkpoints.mutex() };
+    checked = start(*faster->disk, token);
+    if(checked) {
+      faster->checkpoints.Started(token, index, hybrid_log);
+    }
+  }
+  faster_checkpoint_result* result = new faster_checkpoint_result;
+  result->checked = checked;
+  result->token = strdup(token.ToString().c_str());
+  return result;
+}
+
+template <class D>
+uint8_t conditional_rmw(faster_t* faster, const uint8_t* key, uint64_t key_length,
+                        uint64_t monotonic_serial_number, const D& decision) {
+  typedef ConditionalRmwContext<D> context_t;
+  auto callback = [](IAsyncContext* ctxt, Status result) {
+    CallbackContext<context_t> context{ ctxt };
+    context->Complete(result);
+  };
+  context_t context{ key, key_length, decision };
+  Status result = with_store(faster, [&](auto& store) {
+    return store.Rmw(context, callback, monotonic_serial_number);
+  });
+  if(result != Status::Pending) {
+    context.Complete(result);
+    result = context.Result(result);
+  }
+  return static_cast<uint8_t>(result);
+}
+
+// The items of a batch that are still pending
+struct BatchState {
+  std::atomic<uint64_t> pending{ 0 };
+};
+
+// Context of an item of a batch, which writes the status of the item once it completes and
+// counts it off the pending items of its batch
+template <class C>
+class BatchContext : public C {
+ public:
+  template <class... Args>
+  BatchContext(BatchState* batch, uint8_t* status, Args&&... args)
+    : C{ std::forward<Args>(args)... }
+    , batch_{ batch }
+    , status_{ status } {
+  }
+
+  inline void Report(Status result) {
+    *status_ = static_cast<uint8_t>(result);
+    batch_->pending.fetch_sub(1);
+  }
+
+ protected:
+  Status DeepCopy_Internal(IAsyncContext*& context_copy) {
+    return IAsyncContext::DeepCopy_Internal(*this, context_copy);
+  }
+
+ private:
+  BatchState* batch_;
+  uint8_t* status_;
+};
+
+// Issues the items of a batch with `issue`, which is called with the store, the batch, the index
+// of the item, its key and the serial number, and returns the status of the item. Returns once
+// every item has completed.
+//
+// FASTER invokes the callbacks of pending items from CompletePending on this thread, once the
+// disk reads they wait for have finished, which only CompletePending polls for. So the wait
+// completes pending operations until the callbacks have counted off every pending item, and
+// lets other threads run in between. Other pending operations of the session complete as far
+// as they are ready.
+template <class F>
+void batch(faster_t* faster, uint64_t count, const uint8_t* keys, const uint64_t* key_lengths,
+           uint64_t monotonic_serial_number, uint8_t* statuses, F&& issue) {
+  BatchState state;
+  with_store(faster, [&](auto& store) {
+    const uint8_t* key = keys;
+    for(uint64_t i = 0; i < count; ++i) {
+      // Counted first, as the item may complete before its status is returned
+      statuses[i] = static_cast<uint8_t>(Status::Pending);
+      state.pending.fetch_add(1);
+      Status result = issue(store, &state, i, key, monotonic_serial_number + i);
+      if(result != Status::Pending) {
+        statuses[i] = static_cast<uint8_t>(result);
+        state.pending.fetch_sub(1);
+      }
+      key += untagged(key_lengths[i]);
+    }
+    while(state.pending.load() > 0) {
+      store.CompletePending(false);
+      if(state.pending.load() > 0) {
+        std::this_thread::yield();
+      }
+    }
+  });
+}
+
+template <class S>
+inline ScanIterator<S>* scan(S& store, uint64_t begin, uint64_t end, Address& next) {
+  Address from = std::max(Address{ begin }, store.hlog.begin_address.load());
+  Address until = std::min(Address{ end }, store.hlog.GetTailAddress());
+  next = from;
+  return new ScanIterator<S>{ &store.hlog, Buffering::DOUBLE_PAGE, from, until, &store.disk };
+}
+
+template <class S>
+inline bool next_record(ScanIterator<S>& iterator, Address& next, std::vector<uint8_t>& value,
+                        faster_record* out) {
+  typename ScanIterator<S>::record_t* record = iterator.GetNext();
+  if(record == nullptr) {
+    return false;
+  }
+  // Records are laid out back to back and start a new page when they do not fit on the
+  // current one, which the iterator skips to
+  uint32_t size = record->size();
+  if(next.offset() + size > Address::kMaxOffset + 1) {
+    next = Address{ next.page() + 1, 0 };
+  }
+  out->address = next.control();
+  out->key = record->key().data();
+  out->key_length = record->key().tagged_length();
+  // Records in the mutable region may be updated in place while they are looked at
+  record->value().CopyTo(value);
+  out->value = value.data();
+  out->value_length = value.size();
+  out->tombstone = record->header.tombstone || !record->value().live();
+  next += size;
+  return true;
+}
+
+// Deletes issued by delete_filtered between two completions of its pending operations
+constexpr uint64_t kDeletesPerCompletion = 256;
+// How often the thread waiting for delete_filtered refreshes its session
+constexpr std::chrono::milliseconds kRefreshInterval{ 1 };
+
+// Deletes a record unless the filter keeps its value
+class FilterDecision {
+ public:
+  FilterDecision(compact_filter filter)
+    : filter_{ filter } {
+  }
+
+  inline uint64_t operator()(const uint8_t* current, uint64_t length, uint8_t* dst,
+                             uint8_t& action) const {
+    if(current == nullptr || filter_(current, length)) {
+      action = kRmwKeep;
+    } else {
+      action = kRmwDelete;
+    }
+    return 0;
+  }
+  inline void Complete(Status result) const {
+  }
+
+ private:
+  compact_filter filter_;
+};
+
+// Deletes the keys of records below until_address whose value the filter rejects. Their
+// current value is checked again, since a newer record may have replaced it. The cleared
+// values that delete them land beyond until_address, so compacting up to it drops the
+// rejected records. FASTER takes the cleared values for live records, so compaction keeps
+// them until their key is written again.
+//
+// The deletes are issued as the scan finds them, by a thread with a session of its own, so
+// that they neither use serial numbers of the calling thread's session nor complete its
+// pending operations. The calling thread keeps refreshing its session until they are done,
+// since FASTER cannot move on to new pages of the log while a session stands still.
+template <class S>
+void delete_filtered(faster_t* faster, S& store, uint64_t until_address, compact_filter filter) {
+  std::mutex mutex;
+  std::condition_variable finished;
+  bool done = false;
+  std::thread deleter{ [&]() {
+    CallScope scope{ faster };
+    store.StartSession();
+    uint64_t serial_number = 0;
+    // The key is copied out of the log, which may move on while the delete runs
+    std::vector<uint8_t> key;
+    std::vector<uint8_t> value;
+    Address next;
+    std::unique_ptr<ScanIterator<S>> iterator{ scan(store, 0, until_address, next) };
+    faster_record record;
+    while(next_record(*iterator, next, value, &record)) {
+      if(record.tombstone || filter(record.value, record.value_length)) {
+        continue;
+      }
+      key.assign(record.key, record.key + untagged(record.key_length));
+      conditional_rmw(faster, key.data(), record.key_length, ++serial_number,
+                      FilterDecision{ filter });
+      if(serial_number % kDeletesPerCompletion == 0) {
+        store.CompletePending(false);
+      }
+    }
+    iterator.reset();
+    store.CompletePending(true);
+    store.StopSession();
+    std::lock_guard<std::mutex> lock{ mutex };
+    done = true;
+    finished.notify_one();
+  } };
+  std::unique_lock<std::mutex> lock{ mutex };
+  while(!finished.wait_for(lock, kRefreshInterval, [&]() { return done; })) {
+    lock.unlock();
+    store.Refresh();
+    lock.lock();
+  }
+  lock.unlock();
+  deleter.join();
+}
+
+void index_persisted(Status result) {
+  if(calling_store != nullptr) {
+    calling_store->checkpoints.IndexPersisted(result);
+  }
+}
+
+void hybrid_log_persisted(Status result, uint64_t persistent_serial_num) {
+  if(calling_store != nullptr) {
+    calling_store->checkpoints.HybridLogPersisted(result);
+  }
+}
+
+}  // namespace
+
+extern "C" {
+
+  faster_t* faster_open(const uint64_t table_size, const uint64_t log_size,
+                        const bool pre_allocate_log) {
+    faster_t* faster = new faster_t;
+    faster->memory.reset(new memory_store_t{ table_size, log_size, "", 0.9, pre_allocate_log });
+    faster->table_size.store(table_size);
+    return faster;
+  }
+
+  faster_t* faster_open_with_disk(const uint64_t table_size, const uint64_t log_size,
+                                  const char* storage, const double log_mutable_fraction,
+                                  const bool pre_allocate_log) {
+    faster_t* faster = new faster_t;
+    faster->disk.reset(new disk_store_t{ table_size, log_size, storage, log_mutable_fraction,
+                                         pre_allocate_log });
+    faster->table_size.store(table_size);
+    return faster;
+  }
+
+  uint8_t faster_upsert(faster_t* faster, const uint8_t* key, const uint64_t key_length,
+                        const uint8_t* value, const uint64_t value_length,
+                        const uint64_t monotonic_serial_number) {
+    auto callback = [](IAsyncContext* ctxt, Status result) {
+      CallbackContext<UpsertContext> context{ ctxt };
+    };
+    UpsertContext context{ key, key_length, value, value_length };
+    Status result = with_store(faster, [&](auto& store) {
+      return store.Upsert(context, callback, monotonic_serial_number);
+    });
+    return static_cast<uint8_t>(result);
+  }
+
+  uint8_t faster_rmw(faster_t* faster, const uint8_t* key, const uint64_t key_length,
+                     const uint8_t* modification, const uint64_t length,
+                     const uint64_t monotonic_serial_number, rmw_callback cb) {
+    auto callback = [](IAsyncContext* ctxt, Status result) {
+      CallbackContext<RmwContext> context{ ctxt };
+    };
+    RmwContext context{ key, key_length, modification, length, cb };
+    Status result = with_store(faster, [&](auto& store) {
+      return store.Rmw(context, callback, monotonic_serial_number);
+    });
+    return static_cast<uint8_t>(result);
+  }
+
+  uint8_t faster_rmw_conditional(faster_t* faster, const uint8_t* key, const uint64_t key_length,
+                                 const uint8_t* modification, const uint64_t length,
+                                 const uint64_t monotonic_serial_number,
+                                 rmw_conditional_callback cb) {
+    return conditional_rmw(faster, key, key_length, monotonic_serial_number,
+                           ModificationDecision{ modification, length, cb });
+  }
+
+  uint8_t faster_rmw_with(faster_t* faster, const uint8_t* key, const uint64_t key_length,
+                          const uint64_t monotonic_serial_number, rmw_context_callback cb,
+                          rmw_complete_callback complete, void* context) {
+    return conditional_rmw(faster, key, key_length, monotonic_serial_number,
+                           ContextDecision{ cb, complete, context, false });
+  }
+
+  uint8_t faster_rmw_existing(faster_t* faster, const uint8_t* key, const uint64_t key_length,
+                              const uint64_t monotonic_serial_number, rmw_context_callback cb,
+                              rmw_complete_callback complete, void* context) {
+    return conditional_rmw(faster, key, key_length, monotonic_serial_number,
+                           ContextDecision{ cb, complete, context, true });
+  }
+
+  uint8_t faster_read(faster_t* faster, const uint8_t* key, const uint64_t key_length,
+                      const uint64_t monotonic_serial_number, read_callback cb, void* target) {
+    auto callback = [](IAsyncContext* ctxt, Status result) {
+      CallbackContext<ReadContext> context{ ctxt };
+      context->Complete(result);
+    };
+    ReadContext context{ key, key_length, cb, target };
+    Status result = with_store(faster, [&](auto& store) {
+      return store.Read(context, callback, monotonic_serial_number);
+    });
+    if(result != Status::Pending) {
+      context.Complete(result);
+    }
+    return static_cast<uint8_t>(result);
+  }
+
+  uint8_t faster_delete(faster_t* faster, const uint8_t* key, const uint64_t key_length,
+                        const uint64_t monotonic_serial_number) {
+    auto callback = [](IAsyncContext* ctxt, Status result) {
+      CallbackContext<DeleteContext> context{ ctxt };
+    };
+    DeleteContext context{ key, key_length };
+    Status result = with_store(faster, [&](auto& store) {
+      return store.Delete(context, callback, monotonic_serial_number);
+    });
+    return static_cast<uint8_t>(result);
+  }
+
+  void faster_upsert_batch(faster_t* faster, const uint64_t count, const uint8_t* keys,
+                           const uint64_t* key_lengths, const uint8_t* values,
+                           const uint64_t* value_lengths, const uint64_t monotonic_serial_number,
+                           uint8_t* statuses) {
+    typedef BatchContext<UpsertContext> context_t;
+    auto callback = [](IAsyncContext* ctxt, Status result) {
+      CallbackContext<context_t> context{ ctxt };
+      context->Report(result);
+    };
+    const uint8_t* value = values;
+    batch(faster, count, keys, key_lengths, monotonic_serial_number, statuses,
+          [&](auto& store, BatchState* state, uint64_t i, const uint8_t* key,
+              uint64_t serial_num) {
+      context_t context{ state, &statuses[i], key, key_lengths[i], value, value_lengths[i] };
+      value += value_lengths[i];
+      return store.Upsert(context, callback, serial_num);
+    });
+  }
+
+  void faster_read_batch(faster_t* faster, const uint64_t count, const uint8_t* keys,
+                         const uint64_t* key_lengths, const uint64_t monotonic_serial_number,
+                         read_callback cb, void* const* contexts, uint8_t* statuses) {
+    typedef BatchContext<ReadContext> context_t;
+    auto callback = [](IAsyncContext* ctxt, Status result) {
+      CallbackContext<context_t> context{ ctxt };
+      context->Complete(result);
+      context->Report(result);
+    };
+    batch(faster, count, keys, key_lengths, monotonic_serial_number, statuses,
+          [&](auto& store, BatchState* state, uint64_t i, const uint8_t* key,
+              uint64_t serial_num) {
+      context_t context{ state, &statuses[i], key, key_lengths[i], cb, contexts[i] };
+      Status result = store.Read(context, callback, serial_num);
+      if(result != Status::Pending) {
+        context.Complete(result);
+      }
+      return result;
+    });
+  }
+
+  void faster_rmw_batch(faster_t* faster, const uint64_t count, const uint8_t* keys,
+                        const uint64_t* key_lengths, const uint8_t* modifications,
+                        const uint64_t* modification_lengths,
+                        const uint64_t monotonic_serial_number, rmw_callback cb,
+                        uint8_t* statuses) {
+    typedef BatchContext<RmwContext> context_t;
+    auto callback = [](IAsyncContext* ctxt, Status result) {
+      CallbackContext<context_t> context{ ctxt };
+      context->Report(result);
+    };
+    const uint8_t* modification = modifications;
+    batch(faster, count, keys, key_lengths, monotonic_serial_number, statuses,
+          [&](auto& store, BatchState* state, uint64_t i, const uint8_t* key,
+              uint64_t serial_num) {
+      context_t context{ state, &statuses[i], key, key_lengths[i], modification,
+                         modification_lengths[i], cb };
+      modification += modification_lengths[i];
+      return store.Rmw(context, callback, serial_num);
+    });
+  }
+
+  void faster_rmw_with_batch(faster_t* faster, const uint64_t count, const uint8_t* keys,
+                             const uint64_t* key_lengths, const uint64_t monotonic_serial_number,
+                             rmw_context_callback cb, rmw_complete_callback complete,
+                             void* const* contexts, uint8_t* statuses) {
+    typedef BatchContext<ConditionalRmwContext<ContextDecision>> context_t;
+    auto callback = [](IAsyncContext* ctxt, Status result) {
+      CallbackContext<context_t> context{ ctxt };
+      context->Complete(result);
+      context->Report(context->Result(result));
+    };
+    batch(faster, count, keys, key_lengths, monotonic_serial_number, statuses,
+          [&](auto& store, BatchState* state, uint64_t i, const uint8_t* key,
+              uint64_t serial_num) {
+      context_t context{ state, &statuses[i], key, key_lengths[i],
+                         ContextDecision{ cb, complete, contexts[i], false } };
+      Status result = store.Rmw(context, callback, serial_num);
+      if(result != Status::Pending) {
+        context.Complete(result);
+        result = context.Result(result);
+      }
+      return result;
+    });
+  }
+
+  faster_checkpoint_result* faster_checkpoint(faster_t* faster) {
+    return checkpoint(faster, true, true, [](disk_store_t& store, Guid& token) {
+      return store.Checkpoint(index_persisted, hybrid_log_persisted, token);
+    });
+  }
+
+  faster_checkpoint_result* faster_checkpoint_index(faster_t* faster) {
+    return checkpoint(faster, true, false, [](disk_store_t& store, Guid& token) {
+      return store.CheckpointIndex(index_persisted, token);
+    });
+  }
+
+  faster_checkpoint_result* faster_checkpoint_hybrid_log(faster_t* faster) {
+    return checkpoint(faster, false, true, [](disk_store_t& store, Guid& token) {
+      return store.CheckpointHybridLog(hybrid_log_persisted, token);
+    });
+  }
+
+  uint8_t faster_checkpoint_status(faster_t* faster, const char* token) {
+    return static_cast<uint8_t>(faster->checkpoints.Get(token));
+  }
+
+  void faster_checkpoint_result_destroy(faster_checkpoint_result* result) {
+    std::free(result->token);
+    delete result;
+  }
+
+  void faster_destroy(faster_t* faster) {
+    delete faster;
+  }
+
+  uint64_t faster_size(faster_t* faster) {
+    return with_store(faster, [](auto& store) {
+      return store.Size();
+    });
+  }
+
+  faster_recover_result* faster_recover(faster_t* faster, const char* index_token,
+                                        const char* hybrid_log_token) {
+    if(!faster->disk) {
+      return nullptr;
+    }
+    uint32_t version;
+    std::vector<Guid> session_ids;
+    Status status = faster->disk->Recover(Guid::Parse(index_token), Guid::Parse(hybrid_log_token),
+                                          version, session_ids);
+
+    faster_recover_result* result = new faster_recover_result;
+    result->status = static_cast<uint8_t>(status);
+    result->version = version;
+    result->session_ids_count = session_ids.size();
+    result->session_ids = new char*[session_ids.size()];
+    for(size_t i = 0; i < session_ids.size(); ++i) {
+      std::string id = session_ids[i].ToString();
+      result->session_ids[i] = new char[id.size() + 1];
+      std::memcpy(result->session_ids[i], id.c_str(), id.size() + 1);
+    }
+    return result;
+  }
+
+  void faster_recover_result_destroy(faster_recover_result* result) {
+    for(uint64_t i = 0; i < result->session_ids_count; ++i) {
+      delete[] result->session_ids[i];
+    }
+    delete[] result->session_ids;
+    delete result;
+  }
+
+  bool faster_complete_pending(faster_t* faster, bool wait) {
+    return with_store(faster, [&](auto& store) {
+      return store.CompletePending(wait);
+    });
+  }
+
+  uint64_t faster_begin_address(faster_t* faster) {
+    return with_store(faster, [](auto& store) {
+      return store.hlog.begin_address.load().control();
+    });
+  }
+
+  uint64_t faster_head_address(faster_t* faster) {
+    return with_store(faster, [](auto& store) {
+      return store.hlog.head_address.load().control();
+    });
+  }
+
+  uint64_t faster_read_only_address(faster_t* faster) {
+    return with_store(faster, [](auto& store) {
+      return store.hlog.read_only_address.load().control();
+    });
+  }
+
+  uint64_t faster_tail_address(faster_t* faster) {
+    return with_store(faster, [](auto& store) {
+      return store.hlog.GetTailAddress().control();
+    });
+  }
+
+  bool faster_compact(faster_t* faster, const uint64_t until_address) {
+    return with_store(faster, [&](auto& store) {
+      return store.Compact(until_address);
+    });
+  }
+
+  bool faster_compact_with_filter(faster_t* faster, const uint64_t until_address,
+                                  compact_filter filter) {
+    return with_store(faster, [&](auto& store) {
+      delete_filtered(faster, store, until_address, filter);
+      return store.Compact(until_address);
+    });
+  }
+
+  bool faster_shift_begin_address(faster_t* faster, const uint64_t address) {
+    auto truncated = [](uint64_t offset) {
+    };
+    auto completed = []() {
+    };
+    return with_store(faster, [&](auto& store) {
+      return store.ShiftBeginAddress(Address{ address }, truncated, completed);
+    });
+  }
+
+  faster_iterator* faster_scan(faster_t* faster, const uint64_t begin, const uint64_t end) {
+    faster_iterator* iterator = new faster_iterator;
+    if(faster->memory) {
+      iterator->memory.reset(scan(*faster->memory, begin, end, iterator->next));
+    } else {
+      iterator->disk.reset(scan(*faster->disk, begin, end, iterator->next));
+    }
+    return iterator;
+  }
+
+  bool faster_iterator_get_next(faster_iterator* iterator, faster_record* record) {
+    if(iterator->memory) {
+      return next_record(*iterator->memory, iterator->next, iterator->value, record);
+    }
+    return next_record(*iterator->disk, iterator->next, iterator->value, record);
+  }
+
+  void faster_iterator_destroy(faster_iterator* iterator) {
+    delete iterator;
+  }
+
+  const char* faster_start_session(faster_t* faster) {
+    static thread_local std::string token;
+    token = with_store(faster, [](auto& store) {
+      return store.StartSession();
+    }).ToString();
+    ++faster->active_sessions;
+    return token.c_str();
+  }
+
+  uint64_t faster_continue_session(faster_t* faster, const char* token) {
+    Guid guid = Guid::Parse(token);
+    uint64_t serial_num = with_store(faster, [&](auto& store) {
+      return store.ContinueSession(guid);
+    });
+    ++faster->active_sessions;
+    return serial_num;
+  }
+
+  void faster_stop_session(faster_t* faster) {
+    with_store(faster, [](auto& store) {
+      store.StopSession();
+    });
+    --faster->active_sessions;
+  }
+
+  void faster_refresh_session(faster_t* faster) {
+    with_store(faster, [](auto& store) {
+      store.Refresh();
+    });
+  }
+
+  void faster_dump_distribution(faster_t* faster) {
+    with_store(faster, [](auto& store) {
+      store.DumpDistribution();
+    });
+  }
+
+  void faster_stats(faster_t* faster, struct faster_stats* stats) {
+    std::memset(stats, 0, sizeof(*stats));
+    with_store(faster, [&](auto& store) {
+      collect_stats(store, stats);
+    });
+    // The in-memory store never flushes its log
+    if(!faster->disk) {
+      stats->on_disk_bytes = 0;
+    }
+    stats->table_size = faster->table_size.load();
+    stats->active_sessions = faster->active_sessions.load();
+  }
+
+  bool faster_grow_index(faster_t* faster) {
+    // Runs once the index has grown, during a call into FASTER on the store
+    auto callback = [](uint64_t new_size) {
+      if(calling_store != nullptr) {
+        calling_store->table_size.store(new_size);
+      }
+    };
+    return with_store(faster, [&](auto& store) {
+      return store.GrowIndex(callback);
+    });
+  }
+
+}  // extern "C"
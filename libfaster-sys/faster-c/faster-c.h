@@ -0,0 +1,197 @@
+// C interface to FASTER used by faster-rs.
+//
+// Keys, values and modifications are borrowed from the caller for the duration of a call.
+// FASTER copies them into its own context if an operation goes pending.
+//
+// Key lengths may carry FASTER_INTERNAL_KEY, which puts the key in a key space of its own:
+// it never equals a key without the flag, whatever their bytes. faster-rs keeps the records it
+// writes for itself there, so the keys of callers are stored exactly as they are passed.
+
+#ifndef FASTER_C_H_
+#define FASTER_C_H_
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#define FASTER_INTERNAL_KEY (1ull << 63)
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+  typedef struct faster_t faster_t;
+  typedef struct faster_iterator faster_iterator;
+
+  typedef struct faster_checkpoint_result faster_checkpoint_result;
+  struct faster_checkpoint_result {
+    bool checked;
+    char* token;
+  };
+
+  typedef struct faster_recover_result faster_recover_result;
+  struct faster_recover_result {
+    uint8_t status;
+    uint32_t version;
+    uint64_t session_ids_count;
+    // NUL-terminated session ids, session_ids_count of them
+    char** session_ids;
+  };
+
+  // Snapshot of a store's hash index size, hybrid log and sessions
+  struct faster_stats {
+    uint64_t table_size;
+    uint64_t begin_address;
+    uint64_t head_address;
+    uint64_t read_only_address;
+    uint64_t tail_address;
+    uint64_t in_memory_bytes;
+    uint64_t on_disk_bytes;
+    uint64_t active_sessions;
+  };
+
+  // A record of the hybrid log. The key points into FASTER's buffers and the value into a copy
+  // held by the iterator, both only valid until the iterator moves on. The key length carries
+  // FASTER_INTERNAL_KEY for internal keys. Tombstones include the records of keys deleted by a
+  // conditional Read-Modify-Write.
+  typedef struct faster_record faster_record;
+  struct faster_record {
+    uint64_t address;
+    const uint8_t* key;
+    uint64_t key_length;
+    const uint8_t* value;
+    uint64_t value_length;
+    bool tombstone;
+  };
+
+  // Invoked exactly once per read: with the value if the key was found, otherwise with a null
+  // value and the status of the read. Reads that do not go pending invoke it before
+  // faster_read returns.
+  typedef void (*read_callback)(void* target, const uint8_t* value, uint64_t length,
+                                uint32_t status);
+  // Returns the size of the merged value, which is written to dst unless dst is null
+  typedef uint64_t (*rmw_callback)(const uint8_t* current, uint64_t length_current,
+                                   const uint8_t* modification, uint64_t length_modification,
+                                   uint8_t* dst);
+
+  // Decides what a conditional Read-Modify-Write does with the current value, which is null for
+  // a missing key. Sets action to 0 to store the new value, 1 to leave the record as it is, or
+  // the key absent, and 2 to delete the record. Returns the size of the new value, which is
+  // written to dst unless dst is null. Operations that leave a missing key absent report
+  // NotFound.
+  typedef uint64_t (*rmw_conditional_callback)(const uint8_t* current, uint64_t length_current,
+                                               const uint8_t* modification,
+                                               uint64_t length_modification, uint8_t* dst,
+                                               uint8_t* action);
+  // Like rmw_conditional_callback, for operations whose logic lives in the caller's context
+  typedef uint64_t (*rmw_context_callback)(void* context, const uint8_t* current,
+                                           uint64_t length_current, uint8_t* dst,
+                                           uint8_t* action);
+  // Invoked exactly once with the status of an operation carrying a context, before the call
+  // returns unless it went pending. The context has to stay valid until then.
+  typedef void (*rmw_complete_callback)(void* context, uint8_t status);
+  // Returns whether compaction keeps a record with the given value
+  typedef bool (*compact_filter)(const uint8_t* value, uint64_t length);
+
+  // Operations
+  faster_t* faster_open(const uint64_t table_size, const uint64_t log_size,
+                        const bool pre_allocate_log);
+  faster_t* faster_open_with_disk(const uint64_t table_size, const uint64_t log_size,
+                                  const char* storage, const double log_mutable_fraction,
+                                  const bool pre_allocate_log);
+  uint8_t faster_upsert(faster_t* faster_t, const uint8_t* key, const uint64_t key_length,
+                        const uint8_t* value, const uint64_t value_length,
+                        const uint64_t monotonic_serial_number);
+  uint8_t faster_rmw(faster_t* faster_t, const uint8_t* key, const uint64_t key_length,
+                     const uint8_t* modification, const uint64_t length,
+                     const uint64_t monotonic_serial_number, rmw_callback cb);
+  uint8_t faster_rmw_conditional(faster_t* faster_t, const uint8_t* key,
+                                 const uint64_t key_length, const uint8_t* modification,
+                                 const uint64_t length, const uint64_t monotonic_serial_number,
+                                 rmw_conditional_callback cb);
+  uint8_t faster_rmw_with(faster_t* faster_t, const uint8_t* key, const uint64_t key_length,
+                          const uint64_t monotonic_serial_number, rmw_context_callback cb,
+                          rmw_complete_callback complete, void* context);
+  // Like faster_rmw_with, but leaves a missing key absent without calling back
+  uint8_t faster_rmw_existing(faster_t* faster_t, const uint8_t* key, const uint64_t key_length,
+                              const uint64_t monotonic_serial_number, rmw_context_callback cb,
+                              rmw_complete_callback complete, void* context);
+  uint8_t faster_read(faster_t* faster_t, const uint8_t* key, const uint64_t key_length,
+                      const uint64_t monotonic_serial_number, read_callback cb, void* target);
+  uint8_t faster_delete(faster_t* faster_t, const uint8_t* key, const uint64_t key_length,
+                        const uint64_t monotonic_serial_number);
+  // Batched operations. Keys, values and modifications are laid out back to back, with the
+  // length of each item in the matching lengths array. Item i uses the serial number
+  // monotonic_serial_number + i and its status is written to statuses[i]. Items that go pending
+  // are completed before the call returns, which completes other pending operations of the
+  // session as far as they are ready.
+  void faster_upsert_batch(faster_t* faster_t, const uint64_t count, const uint8_t* keys,
+                           const uint64_t* key_lengths, const uint8_t* values,
+                           const uint64_t* value_lengths, const uint64_t monotonic_serial_number,
+                           uint8_t* statuses);
+  // Invokes cb with contexts[i] for item i, before its final status is written
+  void faster_read_batch(faster_t* faster_t, const uint64_t count, const uint8_t* keys,
+                         const uint64_t* key_lengths, const uint64_t monotonic_serial_number,
+                         read_callback cb, void* const* contexts, uint8_t* statuses);
+  void faster_rmw_batch(faster_t* faster_t, const uint64_t count, const uint8_t* keys,
+                        const uint64_t* key_lengths, const uint8_t* modifications,
+                        const uint64_t* modification_lengths,
+                        const uint64_t monotonic_serial_number, rmw_callback cb,
+                        uint8_t* statuses);
+  // Like faster_rmw_with for every item, with contexts[i] for item i
+  void faster_rmw_with_batch(faster_t* faster_t, const uint64_t count, const uint8_t* keys,
+                             const uint64_t* key_lengths, const uint64_t monotonic_serial_number,
+                             rmw_context_callback cb, rmw_complete_callback complete,
+                             void* const* contexts, uint8_t* statuses);
+  faster_checkpoint_result* faster_checkpoint(faster_t* faster_t);
+  faster_checkpoint_result* faster_checkpoint_index(faster_t* faster_t);
+  faster_checkpoint_result* faster_checkpoint_hybrid_log(faster_t* faster_t);
+  void faster_checkpoint_result_destroy(faster_checkpoint_result* result);
+  // Ok once every part of the checkpoint has been persisted, Pending until then, and NotFound
+  // for tokens of checkpoints this store did not start. Checkpoints only progress while the
+  // store's sessions refresh or complete pending operations.
+  uint8_t faster_checkpoint_status(faster_t* faster_t, const char* token);
+  void faster_destroy(faster_t* faster_t);
+  uint64_t faster_size(faster_t* faster_t);
+  faster_recover_result* faster_recover(faster_t* faster_t, const char* index_token,
+                                        const char* hybrid_log_token);
+  void faster_recover_result_destroy(faster_recover_result* result);
+  // Returns whether the session of the calling thread has no pending operations left
+  bool faster_complete_pending(faster_t* faster_t, bool wait);
+
+  // Log addresses and compaction
+  uint64_t faster_begin_address(faster_t* faster_t);
+  uint64_t faster_head_address(faster_t* faster_t);
+  uint64_t faster_read_only_address(faster_t* faster_t);
+  uint64_t faster_tail_address(faster_t* faster_t);
+  // Copies live records below until_address to the tail, then shifts the begin address to it
+  bool faster_compact(faster_t* faster_t, const uint64_t until_address);
+  // Like faster_compact, but deletes the records whose current value the filter rejects before
+  // compacting, so that they are dropped instead of copied. The deletes run on a thread with a
+  // session of its own, which calls the filter.
+  bool faster_compact_with_filter(faster_t* faster_t, const uint64_t until_address,
+                                  compact_filter filter);
+  bool faster_shift_begin_address(faster_t* faster_t, const uint64_t address);
+
+  // Log scans
+  // Iterates over the records with addresses in [begin, end), reading flushed pages from disk
+  faster_iterator* faster_scan(faster_t* faster_t, const uint64_t begin, const uint64_t end);
+  bool faster_iterator_get_next(faster_iterator* iterator, faster_record* record);
+  void faster_iterator_destroy(faster_iterator* iterator);
+
+  // Thread-related operations
+  // The returned token is valid until the thread starts another session
+  const char* faster_start_session(faster_t* faster_t);
+  uint64_t faster_continue_session(faster_t* faster_t, const char* token);
+  void faster_stop_session(faster_t* faster_t);
+  void faster_refresh_session(faster_t* faster_t);
+
+  // Statistics
+  void faster_dump_distribution(faster_t* faster_t);
+  void faster_stats(faster_t* faster_t, struct faster_stats* stats);
+  bool faster_grow_index(faster_t* faster_t);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif  // FASTER_C_H_
@@ -0,0 +1,317 @@
+use crate::codec::{Bincode, Codec};
+use crate::instrument::{callback_failed, Operation};
+use crate::keyspace::{encode_key, ROOT};
+use crate::status::{self, Status};
+use crate::ttl;
+use crate::{FasterError, FasterKv, FasterRmw};
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, ThreadId};
+
+struct ReadState<V> {
+    result: Option<Result<Option<V>, FasterError<'static>>>,
+    // Woken by the callback once the result is in
+    waker: Option<Waker>,
+    // Set once FASTER returned PENDING, so the callback has to release the boxed state handed
+    // to FASTER
+    pending: bool,
+}
+
+type SharedReadState<V> = Arc<Mutex<ReadState<V>>>;
+
+unsafe extern "C" fn read_async_callback<C, T, const EXPIRING: bool>(
+    state: *mut libc::c_void,
+    value: *const u8,
+    length: u64,
+    status: u32,
+) where
+    C: Codec<T>,
+{
+    let shared = state as *mut SharedReadState<T>;
+    let result = match status::to_result(status as u8) {
+        Ok(Status::Ok) => {
+            match ttl::unexpired::<EXPIRING>(std::slice::from_raw_parts(value, length as usize)) {
+                Some(value) => C::decode(value).map(Some),
+                None => Ok(None),
+            }
+        }
+        Ok(_) => Ok(None),
+        Err(err) => Err(err),
+    };
+    if let Err(err) = &result {
+        callback_failed("read_async", err);
+    }
+    let (pending, waker) = {
+        let mut state = (*shared)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.result = Some(result);
+        (state.pending, state.waker.take())
+    };
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+    if pending {
+        drop(Box::from_raw(shared));
+    }
+}
+
+enum ReadIssue<V> {
+    Failed(Option<FasterError<'static>>),
+    Issued(SharedReadState<V>),
+}
+
+/// Future returned by [read_async](../struct.FasterKv.html#method.read_async).
+///
+/// Resolves to `Ok(None)` if the key does not exist. FASTER only completes pending operations
+/// on the thread of the session that issued them. Polled on that thread, the future completes
+/// the pending operations of the session itself. Polled on any other thread, it is woken by the
+/// read callback once the session's thread has completed the read, e.g. through
+/// `complete_pending` or `complete_pending_async`, and makes no progress until then.
+pub struct ReadFuture<'a, V, C = Bincode> {
+    store: &'a FasterKv<C>,
+    issuer: ThreadId,
+    issue: ReadIssue<V>,
+}
+
+impl<'a, V, C> Future for ReadFuture<'a, V, C> {
+    type Output = Result<Option<V>, FasterError<'static>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let state = match &mut this.issue {
+            ReadIssue::Failed(err) => {
+                return Poll::Ready(Err(err.take().expect("ReadFuture polled after completion")))
+            }
+            ReadIssue::Issued(state) => Arc::clone(state),
+        };
+        if let Some(result) = take_result(&state, cx.waker()) {
+            return Poll::Ready(result);
+        }
+        if thread::current().id() == this.issuer {
+            // Errors of pending RMWs stay parked for the next rmw or complete_pending call
+            let _ = this.store.try_complete_pending();
+            if let Some(result) = take_result(&state, cx.waker()) {
+                return Poll::Ready(result);
+            }
+            // Nothing else completes the read, so poll again once the executor ran its other
+            // tasks, as complete_pending(true) yields between attempts
+            cx.waker().wake_by_ref();
+        }
+        Poll::Pending
+    }
+}
+
+// Takes the result of the read, or registers `waker` to be woken once it is delivered
+fn take_result<V>(
+    state: &SharedReadState<V>,
+    waker: &Waker,
+) -> Option<Result<Option<V>, FasterError<'static>>> {
+    let mut state = state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let result = state.result.take();
+    if result.is_none() {
+        state.waker = Some(waker.clone());
+    }
+    result
+}
+
+/// Future returned by [upsert_async](../struct.FasterKv.html#method.upsert_async) and
+/// [rmw_async](../struct.FasterKv.html#method.rmw_async).
+///
+/// It completes pending operations of the session that issued it, so it cannot be sent to
+/// another thread.
+pub struct OperationFuture<'a, C = Bincode> {
+    store: &'a FasterKv<C>,
+    result: Option<Result<Status, FasterError<'static>>>,
+    session_thread: PhantomData<*const ()>,
+}
+
+impl<'a, C> OperationFuture<'a, C> {
+    pub(crate) fn new(
+        store: &'a FasterKv<C>,
+        result: Result<Status, FasterError<'static>>,
+    ) -> Self {
+        OperationFuture {
+            store,
+            result: Some(result),
+            session_thread: PhantomData,
+        }
+    }
+}
+
+impl<'a, C> Future for OperationFuture<'a, C> {
+    type Output = Result<Status, FasterError<'static>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match this.result.take() {
+            // FASTER does not report when a pending upsert or RMW finishes, so wait until the
+            // session has nothing pending any more
+            Some(Ok(Status::Pending)) => match this.store.try_complete_pending() {
+                Ok(true) => Poll::Ready(Ok(Status::Ok)),
+                Ok(false) => {
+                    this.result = Some(Ok(Status::Pending));
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+                Err(err) => Poll::Ready(Err(err)),
+            },
+            Some(result) => Poll::Ready(result),
+            None => panic!("OperationFuture polled after completion"),
+        }
+    }
+}
+
+/// Future returned by [complete_pending_async](../struct.FasterKv.html#method.complete_pending_async).
+///
+/// Like [OperationFuture](struct.OperationFuture.html), it cannot be sent to another thread.
+pub struct CompletePendingFuture<'a, C = Bincode> {
+    store: &'a FasterKv<C>,
+    session_thread: PhantomData<*const ()>,
+}
+
+impl<'a, C> Future for CompletePendingFuture<'a, C> {
+    type Output = Result<(), FasterError<'static>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        match self.store.try_complete_pending()? {
+            true => Poll::Ready(Ok(())),
+            false => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Async variants of the store operations.
+///
+/// Operations are issued to FASTER immediately, so serial numbers keep the order in which the
+/// methods were called. FASTER completes pending operations only when the thread that owns the
+/// session calls `complete_pending`, so the futures drive it when polled on that thread, e.g.
+/// inside a single-threaded executor, and yield to the executor between attempts while I/O is
+/// in flight. `OperationFuture` and `CompletePendingFuture` cannot be sent to another thread.
+/// A `ReadFuture` awaited on another thread is woken by its callback, once the session's thread
+/// completed the read.
+impl<C> FasterKv<C> {
+    /// Reads the value stored for `key`, resolving once FASTER delivered it.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// use futures::executor::block_on;
+    ///
+    /// let store = FasterKv::default();
+    /// block_on(async {
+    ///     store.upsert_async(&1u64, &42u64, 1).await.unwrap();
+    ///     let value = store.read_async::<u64, u64>(&1, 2).await.unwrap();
+    ///     assert_eq!(value, Some(42));
+    /// });
+    /// ```
+    pub fn read_async<K, V>(&self, key: &K, monotonic_serial_number: u64) -> ReadFuture<'_, V, C>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.read_async_in(ROOT, key, monotonic_serial_number)
+    }
+
+    pub(crate) fn read_async_in<K, V>(
+        &self,
+        namespace: &[u8],
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> ReadFuture<'_, V, C>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        let encoded_key = match encode_key::<C, K>(namespace, key) {
+            Ok(encoded_key) => encoded_key,
+            Err(err) => {
+                return ReadFuture {
+                    store: self,
+                    issuer: thread::current().id(),
+                    issue: ReadIssue::Failed(Some(err)),
+                }
+            }
+        };
+        let state: SharedReadState<V> = Arc::new(Mutex::new(ReadState {
+            result: None,
+            waker: None,
+            pending: false,
+        }));
+        let state_ptr: *mut SharedReadState<V> = Box::into_raw(Box::new(Arc::clone(&state)));
+        let status = self.observed(Operation::Read, || {
+            status::to_result(unsafe {
+                ffi::faster_read(
+                    self.faster_t,
+                    encoded_key.as_ptr(),
+                    encoded_key.length(),
+                    monotonic_serial_number,
+                    Some(match self.expiring() {
+                        true => read_async_callback::<C, V, true>,
+                        false => read_async_callback::<C, V, false>,
+                    }),
+                    state_ptr as *mut libc::c_void,
+                )
+            })
+        });
+        // Unless the read went pending, the boxed state is no longer referenced by FASTER
+        match status {
+            Ok(Status::Pending) => {
+                state
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .pending = true;
+            }
+            _ => drop(unsafe { Box::from_raw(state_ptr) }),
+        }
+        let issue = match status {
+            Ok(_) => ReadIssue::Issued(state),
+            Err(err) => ReadIssue::Failed(Some(err)),
+        };
+        ReadFuture {
+            store: self,
+            issuer: thread::current().id(),
+            issue,
+        }
+    }
+
+    pub fn upsert_async<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> OperationFuture<'_, C>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        OperationFuture::new(self, self.upsert(key, value, monotonic_serial_number))
+    }
+
+    pub fn rmw_async<K, V>(
+        &self,
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> OperationFuture<'_, C>
+    where
+        V: FasterRmw,
+        C: Codec<K> + Codec<V>,
+    {
+        OperationFuture::new(self, self.rmw(key, modification, monotonic_serial_number))
+    }
+
+    /// Resolves once every pending operation of this thread's session has completed.
+    pub fn complete_pending_async(&self) -> CompletePendingFuture<'_, C> {
+        CompletePendingFuture {
+            store: self,
+            session_thread: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,379 @@
+use crate::codec::Codec;
+use crate::faster_traits::take_rmw_error;
+use crate::instrument::{callback_failed, Operation};
+use crate::keyspace::{self, encode_key, EncodedKey, ROOT};
+use crate::secondary_index::Write;
+use crate::status::{self, Status};
+use crate::ttl::{self, PinnedClock};
+use crate::{FasterError, FasterKv, FasterRmw};
+
+use std::borrow::Cow;
+
+// Encoded items laid out back to back, as the batched entry points expect them
+struct Packed {
+    bytes: Vec<u8>,
+    lengths: Vec<u64>,
+}
+
+impl Packed {
+    fn new() -> Self {
+        Packed {
+            bytes: Vec::new(),
+            lengths: Vec::new(),
+        }
+    }
+
+    fn push_key<C, K>(&mut self, namespace: &[u8], key: &K) -> Result<(), FasterError<'static>>
+    where
+        C: Codec<K>,
+    {
+        let key = encode_key::<C, K>(namespace, key)?;
+        self.bytes.extend_from_slice(key.bytes());
+        self.lengths.push(key.length());
+        Ok(())
+    }
+
+    fn push_encoded(&mut self, encoded: &[u8]) {
+        self.bytes.extend_from_slice(encoded);
+        self.lengths.push(encoded.len() as u64);
+    }
+
+    fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    fn items(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        self.keys().map(|key| key.0)
+    }
+
+    // The items with whether they are internal keys
+    fn keys(&self) -> impl Iterator<Item = (&[u8], bool)> + '_ {
+        let mut rest = &self.bytes[..];
+        self.lengths.iter().map(move |length| {
+            let (length, internal) = keyspace::split_length(*length);
+            let (item, tail) = rest.split_at(length);
+            rest = tail;
+            (item, internal)
+        })
+    }
+}
+
+unsafe extern "C" fn read_batch_callback<C, V, const EXPIRING: bool>(
+    context: *mut libc::c_void,
+    value: *const u8,
+    length: u64,
+    status: u32,
+) where
+    C: Codec<V>,
+{
+    let slot = &mut *(context as *mut Option<Result<V, FasterError<'static>>>);
+    *slot = match status::to_result(status as u8) {
+        Ok(Status::Ok) => {
+            ttl::unexpired::<EXPIRING>(std::slice::from_raw_parts(value, length as usize))
+                .map(C::decode)
+        }
+        Ok(_) => None,
+        Err(err) => Some(Err(err)),
+    };
+    if let Some(Err(err)) = slot {
+        callback_failed("read_batch", err);
+    }
+}
+
+impl<C> FasterKv<C> {
+    /// Upserts every key-value pair of `items` with a single call into FASTER.
+    ///
+    /// Item `i` is issued with serial number `monotonic_serial_number + i`. Items that go
+    /// pending are completed before returning and reported with the status they completed with;
+    /// other pending operations of the session are not waited for. The statuses are returned in
+    /// the order of `items`. If FASTER fails an item, the first such error is returned; the other
+    /// items have still been applied.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    ///
+    /// let pairs: Vec<(u64, u64)> = (0..100).map(|key| (key, key * 2)).collect();
+    /// let statuses = store
+    ///     .upsert_batch(pairs.iter().map(|(key, value)| (key, value)), 1)
+    ///     .unwrap();
+    /// assert_eq!(statuses.len(), 100);
+    /// ```
+    pub fn upsert_batch<'a, K, V, I>(
+        &self,
+        items: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Status>, FasterError<'static>>
+    where
+        K: 'a,
+        V: 'a,
+        C: Codec<K> + Codec<V>,
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        self.upsert_batch_in(ROOT, items, monotonic_serial_number)
+    }
+
+    pub(crate) fn upsert_batch_in<'a, K, V, I>(
+        &self,
+        namespace: &[u8],
+        items: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Status>, FasterError<'static>>
+    where
+        K: 'a,
+        V: 'a,
+        C: Codec<K> + Codec<V>,
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        self.observed(Operation::UpsertBatch, || {
+            let mut keys = Packed::new();
+            let mut values = Packed::new();
+            for (key, value) in items {
+                keys.push_key::<C, K>(namespace, key)?;
+                values.push_encoded(&self.encode_value(value, ttl::NEVER)?);
+            }
+            if keys.len() == 0 {
+                return Ok(Vec::new());
+            }
+            self.count_operations(keys.len() as u64);
+            if self.indexes.active() && namespace == ROOT {
+                let writes = values.items().map(|value| Write::Replace(value.to_vec()));
+                return self.write_indexed_batch(&keys, writes, monotonic_serial_number);
+            }
+            let mut statuses = vec![0u8; keys.len()];
+            unsafe {
+                ffi::faster_upsert_batch(
+                    self.faster_t,
+                    keys.len() as u64,
+                    keys.bytes.as_ptr(),
+                    keys.lengths.as_ptr(),
+                    values.bytes.as_ptr(),
+                    values.lengths.as_ptr(),
+                    monotonic_serial_number,
+                    statuses.as_mut_ptr(),
+                );
+            }
+            finish_batch(&statuses)
+        })
+    }
+
+    /// Reads the values stored for every key of `keys` with a single call into FASTER.
+    ///
+    /// Item `i` is issued with serial number `monotonic_serial_number + i`. Reads that go to
+    /// disk are completed before returning, so every value is available, without waiting for
+    /// other pending operations of the session. Each entry
+    /// of the result holds the value for the key at the same position, `None` if the key does
+    /// not exist, or the error that reading it caused.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1u64, &10u64, 1).unwrap();
+    /// let values = store.read_batch::<u64, u64, _>(&[1, 2], 2).unwrap();
+    /// assert_eq!(values[0].as_ref().unwrap(), &Some(10));
+    /// assert_eq!(values[1].as_ref().unwrap(), &None);
+    /// ```
+    pub fn read_batch<'a, K, V, I>(
+        &self,
+        keys: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Result<Option<V>, FasterError<'static>>>, FasterError<'static>>
+    where
+        K: 'a,
+        C: Codec<K> + Codec<V>,
+        I: IntoIterator<Item = &'a K>,
+    {
+        self.read_batch_in(ROOT, keys, monotonic_serial_number)
+    }
+
+    pub(crate) fn read_batch_in<'a, K, V, I>(
+        &self,
+        namespace: &[u8],
+        keys: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Result<Option<V>, FasterError<'static>>>, FasterError<'static>>
+    where
+        K: 'a,
+        C: Codec<K> + Codec<V>,
+        I: IntoIterator<Item = &'a K>,
+    {
+        self.observed(Operation::ReadBatch, || {
+            let mut packed = Packed::new();
+            for key in keys {
+                packed.push_key::<C, K>(namespace, key)?;
+            }
+            if packed.len() == 0 {
+                return Ok(Vec::new());
+            }
+            // Filled in by the callback. Pending reads are completed before the slots go away.
+            let mut slots: Vec<Option<Result<V, FasterError<'static>>>> =
+                (0..packed.len()).map(|_| None).collect();
+            let contexts: Vec<*mut libc::c_void> = (0..packed.len())
+                .map(|i| unsafe { slots.as_mut_ptr().add(i) as *mut libc::c_void })
+                .collect();
+            let mut statuses = vec![0u8; packed.len()];
+            unsafe {
+                ffi::faster_read_batch(
+                    self.faster_t,
+                    packed.len() as u64,
+                    packed.bytes.as_ptr(),
+                    packed.lengths.as_ptr(),
+                    monotonic_serial_number,
+                    Some(match self.expiring() {
+                        true => read_batch_callback::<C, V, true>,
+                        false => read_batch_callback::<C, V, false>,
+                    }),
+                    contexts.as_ptr(),
+                    statuses.as_mut_ptr(),
+                );
+            }
+            Ok(statuses
+                .iter()
+                .zip(slots)
+                .map(|(status, slot)| {
+                    status::to_result(*status)?;
+                    slot.transpose()
+                })
+                .collect())
+        })
+    }
+
+    /// Merges every modification of `items` into the value stored for its key with a single
+    /// call into FASTER, using the [FasterRmw](trait.FasterRmw.html) logic of `V`.
+    ///
+    /// Item `i` is issued with serial number `monotonic_serial_number + i`. Items that go
+    /// pending are completed before returning and reported with the status they completed with;
+    /// other pending operations of the session are not waited for. If a merge fails, its record
+    /// is left unchanged and an error is returned after the other items have been applied.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    ///
+    /// let increments = [(1u64, 1u64), (2, 1), (1, 1)];
+    /// store
+    ///     .rmw_batch(increments.iter().map(|(key, modification)| (key, modification)), 1)
+    ///     .unwrap();
+    /// let (_, recv) = store.read::<u64, u64>(&1, 4).unwrap();
+    /// assert_eq!(recv.recv().unwrap().unwrap(), 2);
+    /// ```
+    pub fn rmw_batch<'a, K, V, I>(
+        &self,
+        items: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Status>, FasterError<'static>>
+    where
+        K: 'a,
+        V: FasterRmw + 'a,
+        C: Codec<K> + Codec<V>,
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        self.rmw_batch_in(ROOT, items, monotonic_serial_number)
+    }
+
+    pub(crate) fn rmw_batch_in<'a, K, V, I>(
+        &self,
+        namespace: &[u8],
+        items: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Status>, FasterError<'static>>
+    where
+        K: 'a,
+        V: FasterRmw + 'a,
+        C: Codec<K> + Codec<V>,
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        self.observed(Operation::RmwBatch, || {
+            let mut keys = Packed::new();
+            let mut modifications = Packed::new();
+            for (key, modification) in items {
+                keys.push_key::<C, K>(namespace, key)?;
+                modifications.push_encoded(&self.encode_modification(modification)?);
+            }
+            if keys.len() == 0 {
+                return Ok(Vec::new());
+            }
+            self.count_operations(keys.len() as u64);
+            if self.indexes.active() && namespace == ROOT {
+                let merge = self.merge_fn::<V>();
+                let writes = modifications
+                    .items()
+                    .map(|modification| Write::Merge(modification.to_vec(), merge));
+                let statuses = self.write_indexed_batch(&keys, writes, monotonic_serial_number);
+                take_rmw_error()?;
+                return statuses;
+            }
+            let mut statuses = vec![0u8; keys.len()];
+            unsafe {
+                let _clock = PinnedClock::pin();
+                ffi::faster_rmw_batch(
+                    self.faster_t,
+                    keys.len() as u64,
+                    keys.bytes.as_ptr(),
+                    keys.lengths.as_ptr(),
+                    modifications.bytes.as_ptr(),
+                    modifications.lengths.as_ptr(),
+                    monotonic_serial_number,
+                    self.rmw_callback::<V>(),
+                    statuses.as_mut_ptr(),
+                );
+            }
+            let statuses = finish_batch(&statuses);
+            take_rmw_error()?;
+            statuses
+        })
+    }
+
+    /// Issues the items of a batch of an indexed store as Read-Modify-Writes with a single call
+    /// into FASTER, so that the entries of their keys are updated from the values they replace
+    /// once every item has completed.
+    fn write_indexed_batch<W>(
+        &self,
+        keys: &Packed,
+        writes: W,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Status>, FasterError<'static>>
+    where
+        W: Iterator<Item = Write>,
+    {
+        let logics = keys
+            .keys()
+            .zip(writes)
+            .enumerate()
+            .map(|(i, ((key, internal), write))| {
+                let key = EncodedKey::in_space(Cow::Borrowed(key), internal);
+                self.indexed(&key, write, monotonic_serial_number + i as u64)
+            })
+            .collect();
+        let completed =
+            self.rmw_batch_in_context(&keys.bytes, &keys.lengths, logics, monotonic_serial_number);
+        let mut statuses = Vec::with_capacity(completed.len());
+        let mut failed = None;
+        for (indexed, status) in completed {
+            match status::to_result(status) {
+                Ok(status) => {
+                    indexed.finish();
+                    statuses.push(status);
+                }
+                Err(err) => {
+                    failed.get_or_insert(err);
+                }
+            }
+        }
+        match failed {
+            Some(err) => Err(err),
+            None => Ok(statuses),
+        }
+    }
+}
+
+fn finish_batch(statuses: &[u8]) -> Result<Vec<Status>, FasterError<'static>> {
+    statuses
+        .iter()
+        .map(|status| status::to_result(*status))
+        .collect()
+}
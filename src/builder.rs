@@ -1,5 +1,15 @@
-use crate::{FasterError, FasterKv};
+use crate::catalog::Catalog;
+use crate::checkpointer::Checkpointer;
+use crate::codec::{Bincode, Codec};
+use crate::secondary_index::Indexes;
+use crate::{
+    CheckpointPolicy, FasterError, FasterKey, FasterKv, FasterValue, Recover, Status, TtlPolicy,
+    TypedFasterKv,
+};
 use std::ffi::CString;
+use std::marker::PhantomData;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
 
 pub struct FasterKvBuilder<'a> {
     table_size: u64,
@@ -7,6 +17,8 @@ pub struct FasterKvBuilder<'a> {
     storage: Option<&'a str>,
     log_mutable_fraction: f64,
     pre_allocate_log: bool,
+    ttl: Option<TtlPolicy>,
+    checkpoint_policy: Option<CheckpointPolicy>,
 }
 
 impl<'a> FasterKvBuilder<'a> {
@@ -17,6 +29,8 @@ impl<'a> FasterKvBuilder<'a> {
             storage: None,
             log_mutable_fraction: 0.9,
             pre_allocate_log: false,
+            ttl: None,
+            checkpoint_policy: None,
         }
     }
 
@@ -35,12 +49,133 @@ impl<'a> FasterKvBuilder<'a> {
         self
     }
 
+    /// Stores an expiry with every value, so that records written by
+    /// [upsert_with_ttl](struct.FasterKv.html#method.upsert_with_ttl) expire.
+    ///
+    /// `policy` decides whether Read-Modify-Writes keep or refresh the expiry of the records
+    /// they update. Values are laid out differently in the log, so a store recovered from a
+    /// checkpoint has to be built with a TTL if and only if the checkpointed store was.
+    /// Checkpoints record whether it was, and `recover` fails with `FasterError::TtlMismatch`
+    /// otherwise.
+    pub fn with_ttl(&mut self, policy: TtlPolicy) -> &mut FasterKvBuilder<'a> {
+        self.ttl = Some(policy);
+        self
+    }
+
+    /// Takes full checkpoints in a background thread according to `policy`.
+    ///
+    /// The resulting tokens are available from
+    /// [checkpoints](struct.FasterKv.html#method.checkpoints). The thread is stopped when the
+    /// store is dropped. Requires a store built `with_disk`.
+    pub fn with_checkpoint_policy(&mut self, policy: CheckpointPolicy) -> &mut FasterKvBuilder<'a> {
+        self.checkpoint_policy = Some(policy);
+        self
+    }
+
     pub fn build(&self) -> Result<FasterKv, FasterError<'static>> {
+        self.build_with_codec::<Bincode>()
+    }
+
+    /// Builds a store that encodes its keys and values with the codec `C`.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::codec::FixedWidth;
+    /// use faster_rs::{FasterKvBuilder, Status};
+    ///
+    /// let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+    ///     .build_with_codec::<FixedWidth>()
+    ///     .unwrap();
+    /// store.upsert(&1u64, &42u64, 1).unwrap();
+    /// let (res, recv) = store.read::<u64, u64>(&1, 1).unwrap();
+    /// assert_eq!(Status::Ok, res);
+    /// assert_eq!(42, recv.recv().unwrap().unwrap());
+    /// ```
+    pub fn build_with_codec<C>(&self) -> Result<FasterKv<C>, FasterError<'static>> {
+        let mut store = self.open()?;
+        self.start_checkpointer(&mut store);
+        Ok(store)
+    }
+
+    /// Builds a store and recovers it from the most recent checkpoint in its storage directory.
+    ///
+    /// The newest index and hybrid log checkpoints that can be recovered together are picked
+    /// as by [latest_checkpoint](struct.FasterKv.html#method.latest_checkpoint). If recovering
+    /// them fails, older pairs are tried in turn and the error of the oldest is returned when
+    /// none recovers. A configured checkpoint policy only starts once recovery has finished.
+    /// Returns `FasterError::NoCheckpoint` if the storage directory holds no recoverable
+    /// checkpoint.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use faster_rs::FasterKvBuilder;
+    /// let (store, recovered) = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+    ///     .with_disk("storage")
+    ///     .recover_latest()
+    ///     .unwrap();
+    /// for session_id in recovered.session_ids {
+    ///     let persisted_until = store.continue_session(session_id.to_string()).unwrap();
+    /// }
+    /// ```
+    pub fn recover_latest(&self) -> Result<(FasterKv, Recover), FasterError<'static>> {
+        self.recover_latest_with_codec::<Bincode>()
+    }
+
+    /// Like [recover_latest](#method.recover_latest), for a store that encodes its keys and
+    /// values with the codec `C`.
+    pub fn recover_latest_with_codec<C>(
+        &self,
+    ) -> Result<(FasterKv<C>, Recover), FasterError<'static>> {
+        if self.storage.is_none() {
+            return Err(FasterError::BuilderError(
+                "Recovery requires a store with disk",
+            ));
+        }
+        let mut store = self.open()?;
+        let mut last_error = None;
+        for pair in store.recoverable_checkpoints()? {
+            // A failed recovery may leave the store half restored, so every attempt starts
+            // from a fresh one
+            if last_error.is_some() {
+                store = self.open()?;
+            }
+            match store.recover(pair.index_token, pair.hybrid_log_token) {
+                Ok(recovered) if recovered.status == Status::Ok => {
+                    self.start_checkpointer(&mut store);
+                    return Ok((store, recovered));
+                }
+                Ok(_) => last_error = Some(FasterError::RecoveryError),
+                Err(err) => last_error = Some(err),
+            }
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                error = %last_error.as_ref().unwrap(),
+                "falling back to an older checkpoint"
+            );
+        }
+        Err(last_error
+            .unwrap_or_else(|| FasterError::NoCheckpoint(store.storage_dir.clone().unwrap())))
+    }
+
+    fn open<C>(&self) -> Result<FasterKv<C>, FasterError<'static>> {
         if !(self.log_mutable_fraction > 0.0 && self.log_mutable_fraction <= 1.0) {
             return Err(FasterError::BuilderError(
                 "Log mutable fraction must be between 0 and 1",
             ));
         }
+        if let Some(TtlPolicy::Refresh(ttl)) = self.ttl {
+            // Expiries are kept in milliseconds
+            if ttl.as_millis() == 0 {
+                return Err(FasterError::BuilderError(
+                    "TTL refreshed by Read-Modify-Writes must be at least a millisecond",
+                ));
+            }
+        }
+        if self.checkpoint_policy.is_some() && self.storage.is_none() {
+            return Err(FasterError::BuilderError(
+                "Checkpoint policy requires a store with disk",
+            ));
+        }
         unsafe {
             let mut storage_dir = None;
             let faster_t = match self.storage {
@@ -59,17 +194,78 @@ impl<'a> FasterKvBuilder<'a> {
                     ft
                 }
             };
+            let operations = match self.checkpoint_policy {
+                Some(CheckpointPolicy::Operations(_)) => Some(Arc::new(AtomicU64::new(0))),
+                _ => None,
+            };
+            let catalog = storage_dir
+                .clone()
+                .map(|storage_dir| Arc::new(Catalog::new(storage_dir, self.ttl.is_some())));
             Ok(FasterKv {
                 faster_t,
                 storage_dir,
+                catalog,
+                operations,
+                ttl: self.ttl,
+                indexes: Arc::new(Indexes::new(self.ttl.is_some())),
+                #[cfg(feature = "metrics")]
+                metrics: Default::default(),
+                checkpointer: None,
+                codec: PhantomData,
             })
         }
     }
+
+    fn start_checkpointer<C>(&self, store: &mut FasterKv<C>) {
+        store.checkpointer = self.checkpoint_policy.map(|policy| {
+            Checkpointer::spawn(
+                store.faster_t,
+                policy,
+                store.operations.clone(),
+                store.catalog.clone(),
+            )
+        });
+    }
+
+    /// Builds a store whose key and value types are fixed to `K` and `V`.
+    pub fn build_typed<K, V>(&self) -> Result<TypedFasterKv<K, V>, FasterError<'static>>
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        self.build().map(TypedFasterKv::new)
+    }
+
+    /// Builds a store whose key and value types are fixed to `K` and `V`, encoded with the
+    /// codec `C`.
+    pub fn build_typed_with_codec<K, V, C>(
+        &self,
+    ) -> Result<TypedFasterKv<K, V, C>, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.build_with_codec().map(TypedFasterKv::new)
+    }
+
+    /// Like [recover_latest](#method.recover_latest), for a store whose key and value types
+    /// are fixed to `K` and `V`.
+    pub fn recover_latest_typed<K, V>(
+        &self,
+    ) -> Result<(TypedFasterKv<K, V>, Recover), FasterError<'static>>
+    where
+        K: FasterKey,
+        V: FasterValue,
+    {
+        let (store, recovered) = self.recover_latest()?;
+        Ok((TypedFasterKv::new(store), recovered))
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::FasterKvBuilder;
+    use crate::{CheckpointPolicy, TtlPolicy};
+    use std::time::Duration;
     use tempfile::TempDir;
     #[test]
     fn can_build_with_disk() {
@@ -84,4 +280,29 @@ pub mod tests {
         let storage = &kv.storage_dir;
         assert_eq!(storage.as_ref().unwrap(), dir_str);
     }
+
+    #[test]
+    fn checkpoint_policy_requires_disk() {
+        let mut builder = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024);
+        builder.with_checkpoint_policy(CheckpointPolicy::Operations(1000));
+        assert!(builder.build().is_err());
+    }
+
+    #[test]
+    fn ttl_refresh_must_be_at_least_a_millisecond() {
+        let mut builder = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024);
+        builder.with_ttl(TtlPolicy::Refresh(Duration::from_micros(10)));
+        assert!(builder.build().is_err());
+        builder.with_ttl(TtlPolicy::Refresh(Duration::from_secs(60)));
+        assert_eq!(
+            builder.build().unwrap().ttl,
+            Some(TtlPolicy::Refresh(Duration::from_secs(60)))
+        );
+    }
+
+    #[test]
+    fn recover_latest_requires_disk() {
+        let builder = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024);
+        assert!(builder.recover_latest().is_err());
+    }
 }
@@ -0,0 +1,366 @@
+use crate::{CheckPoint, FasterError, FasterKv};
+
+use std::collections::HashSet;
+use std::convert::TryInto;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+// Directories FASTER writes checkpoints to, relative to the storage directory
+const INDEX_CHECKPOINTS: &str = "index-checkpoints";
+const HYBRID_LOG_CHECKPOINTS: &str = "cpr-checkpoints";
+// The metadata faster-rs saves with every checkpoint it takes, under its token: a byte of flags
+// describing how the store lays out its values, followed by the version of the checkpoint as a
+// little endian u32
+const STORE_METADATA: &str = "store-metadata";
+const TTL_FLAG: u8 = 1;
+const METADATA_LENGTH: usize = 5;
+// Written last, so a checkpoint without it never completed
+const INFO_FILE: &str = "info.dat";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointKind {
+    Index,
+    HybridLog,
+    /// Index and hybrid log checkpoint sharing one token, as taken by `checkpoint()`
+    Full,
+}
+
+/// A completed checkpoint found in the storage directory.
+#[derive(Debug, Clone)]
+pub struct CheckpointInfo {
+    pub token: String,
+    pub kind: CheckpointKind,
+    /// When the checkpoint was completed
+    pub timestamp: SystemTime,
+    /// Version faster-rs recorded for the checkpoint when it was taken. Checkpoints taken
+    /// later in the same storage directory have higher versions.
+    pub version: u32,
+    /// Bytes used on disk
+    pub size: u64,
+}
+
+impl CheckpointInfo {
+    fn has_index(&self) -> bool {
+        self.kind != CheckpointKind::HybridLog
+    }
+
+    fn has_hybrid_log(&self) -> bool {
+        self.kind != CheckpointKind::Index
+    }
+}
+
+/// Index and hybrid log checkpoint that can be passed to `recover` together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointPair {
+    pub index_token: String,
+    pub hybrid_log_token: String,
+}
+
+struct Found {
+    token: String,
+    path: PathBuf,
+    timestamp: SystemTime,
+}
+
+// What faster-rs saved with a checkpoint
+struct Metadata {
+    expiring: bool,
+    version: u32,
+}
+
+impl Metadata {
+    fn to_bytes(&self) -> [u8; METADATA_LENGTH] {
+        let mut bytes = [0; METADATA_LENGTH];
+        if self.expiring {
+            bytes[0] |= TTL_FLAG;
+        }
+        bytes[1..].copy_from_slice(&self.version.to_le_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Metadata> {
+        let bytes: &[u8; METADATA_LENGTH] = bytes.try_into().ok()?;
+        Some(Metadata {
+            expiring: bytes[0] & TTL_FLAG != 0,
+            version: u32::from_le_bytes(bytes[1..].try_into().unwrap()),
+        })
+    }
+}
+
+fn dir_size(path: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        size += match metadata.is_dir() {
+            true => dir_size(&entry.path())?,
+            false => metadata.len(),
+        };
+    }
+    Ok(size)
+}
+
+fn find_checkpoints(dir: &Path) -> io::Result<Vec<Found>> {
+    let mut found = Vec::new();
+    if !dir.exists() {
+        return Ok(found);
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let info = entry.path().join(INFO_FILE);
+        if !info.is_file() {
+            continue;
+        }
+        found.push(Found {
+            token: entry.file_name().to_string_lossy().into_owned(),
+            path: entry.path(),
+            timestamp: fs::metadata(&info)?.modified()?,
+        });
+    }
+    Ok(found)
+}
+
+fn index_path(storage_dir: &str, token: &str) -> PathBuf {
+    Path::new(storage_dir).join(INDEX_CHECKPOINTS).join(token)
+}
+
+fn hybrid_log_path(storage_dir: &str, token: &str) -> PathBuf {
+    Path::new(storage_dir)
+        .join(HYBRID_LOG_CHECKPOINTS)
+        .join(token)
+}
+
+fn store_metadata_path(storage_dir: &str, token: &str) -> PathBuf {
+    Path::new(storage_dir).join(STORE_METADATA).join(token)
+}
+
+// The metadata saved with the checkpoint with `token`, or `None` for checkpoints taken without
+fn load_metadata(storage_dir: &str, token: &str) -> io::Result<Option<Metadata>> {
+    match fs::read(store_metadata_path(storage_dir, token)) {
+        Ok(bytes) => Ok(Metadata::from_bytes(&bytes)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+// The version after the highest one saved in the storage directory
+fn next_version(storage_dir: &str) -> io::Result<u32> {
+    let dir = Path::new(storage_dir).join(STORE_METADATA);
+    let mut next = 1;
+    if !dir.exists() {
+        return Ok(next);
+    }
+    for entry in fs::read_dir(dir)? {
+        if let Some(metadata) = Metadata::from_bytes(&fs::read(entry?.path())?) {
+            next = next.max(metadata.version + 1);
+        }
+    }
+    Ok(next)
+}
+
+/// Saves the metadata of the checkpoints a store takes in its storage directory.
+///
+/// Checkpoints are started through it one at a time, so the versions it records follow the
+/// order FASTER took them in.
+pub(crate) struct Catalog {
+    storage_dir: String,
+    expiring: bool,
+    // Held while a checkpoint is started and its metadata saved
+    starting: Mutex<()>,
+}
+
+impl Catalog {
+    pub(crate) fn new(storage_dir: String, expiring: bool) -> Self {
+        Catalog {
+            storage_dir,
+            expiring,
+            starting: Mutex::new(()),
+        }
+    }
+
+    /// Starts a checkpoint with `start` and, if FASTER took it, saves its metadata. The
+    /// checkpoint is returned along with whether its metadata was saved.
+    pub(crate) fn take<F>(
+        &self,
+        start: F,
+    ) -> Result<(CheckPoint, io::Result<()>), FasterError<'static>>
+    where
+        F: FnOnce() -> Result<CheckPoint, FasterError<'static>>,
+    {
+        let _starting = self
+            .starting
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let version = next_version(&self.storage_dir);
+        let checkpoint = start()?;
+        if !checkpoint.checked {
+            return Ok((checkpoint, Ok(())));
+        }
+        let saved = version.and_then(|version| {
+            fs::create_dir_all(Path::new(&self.storage_dir).join(STORE_METADATA))?;
+            let metadata = Metadata {
+                expiring: self.expiring,
+                version,
+            };
+            fs::write(
+                store_metadata_path(&self.storage_dir, &checkpoint.token),
+                metadata.to_bytes(),
+            )
+        });
+        Ok((checkpoint, saved))
+    }
+}
+
+// The newest index checkpoint taken before the given hybrid log checkpoint
+fn pair_for(hybrid_log: &CheckpointInfo, checkpoints: &[CheckpointInfo]) -> Option<CheckpointPair> {
+    if hybrid_log.kind == CheckpointKind::Full {
+        return Some(CheckpointPair {
+            index_token: hybrid_log.token.clone(),
+            hybrid_log_token: hybrid_log.token.clone(),
+        });
+    }
+    checkpoints
+        .iter()
+        .filter(|index| index.has_index() && index.version < hybrid_log.version)
+        .max_by_key(|index| index.version)
+        .map(|index| CheckpointPair {
+            index_token: index.token.clone(),
+            hybrid_log_token: hybrid_log.token.clone(),
+        })
+}
+
+impl<C> FasterKv<C> {
+    /// Starts a checkpoint with `start` and saves its metadata.
+    pub(crate) fn take_checkpoint<F>(&self, start: F) -> Result<CheckPoint, FasterError<'static>>
+    where
+        F: FnOnce() -> Result<CheckPoint, FasterError<'static>>,
+    {
+        let catalog = self.catalog.as_ref().ok_or(FasterError::InvalidType)?;
+        let (checkpoint, saved) = catalog.take(start)?;
+        saved?;
+        Ok(checkpoint)
+    }
+
+    /// Fails if the hybrid log checkpoint with `token` was taken by a store that lays out its
+    /// values differently.
+    pub(crate) fn check_store_metadata(&self, token: &str) -> Result<(), FasterError<'static>> {
+        let storage_dir = self.storage_dir.as_ref().ok_or(FasterError::InvalidType)?;
+        match load_metadata(storage_dir, token)? {
+            Some(metadata) if metadata.expiring != self.expiring() => {
+                Err(FasterError::TtlMismatch(metadata.expiring))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Lists the completed checkpoints in the storage directory, oldest first.
+    ///
+    /// Only checkpoints taken through faster-rs are listed, as their versions are read from the
+    /// metadata it saves with them.
+    pub fn list_checkpoints(&self) -> Result<Vec<CheckpointInfo>, FasterError<'static>> {
+        let storage_dir = self.storage_dir.as_ref().ok_or(FasterError::InvalidType)?;
+        let indexes = find_checkpoints(&Path::new(storage_dir).join(INDEX_CHECKPOINTS))?;
+        let hybrid_logs = find_checkpoints(&Path::new(storage_dir).join(HYBRID_LOG_CHECKPOINTS))?;
+        let index_tokens: HashSet<&str> =
+            indexes.iter().map(|found| found.token.as_str()).collect();
+        let hybrid_log_tokens: HashSet<&str> = hybrid_logs
+            .iter()
+            .map(|found| found.token.as_str())
+            .collect();
+
+        let mut checkpoints = Vec::new();
+        for found in &indexes {
+            if hybrid_log_tokens.contains(found.token.as_str()) {
+                continue;
+            }
+            let metadata = match load_metadata(storage_dir, &found.token)? {
+                Some(metadata) => metadata,
+                None => continue,
+            };
+            checkpoints.push(CheckpointInfo {
+                token: found.token.clone(),
+                kind: CheckpointKind::Index,
+                timestamp: found.timestamp,
+                version: metadata.version,
+                size: dir_size(&found.path)?,
+            });
+        }
+        for found in &hybrid_logs {
+            let metadata = match load_metadata(storage_dir, &found.token)? {
+                Some(metadata) => metadata,
+                None => continue,
+            };
+            let (kind, size) = match index_tokens.contains(found.token.as_str()) {
+                true => (
+                    CheckpointKind::Full,
+                    dir_size(&found.path)? + dir_size(&index_path(storage_dir, &found.token))?,
+                ),
+                false => (CheckpointKind::HybridLog, dir_size(&found.path)?),
+            };
+            checkpoints.push(CheckpointInfo {
+                token: found.token.clone(),
+                kind,
+                timestamp: found.timestamp,
+                version: metadata.version,
+                size,
+            });
+        }
+        checkpoints.sort_by_key(|checkpoint| checkpoint.version);
+        Ok(checkpoints)
+    }
+
+    /// Finds the most recent index and hybrid log checkpoint that can be recovered together.
+    pub fn latest_checkpoint(&self) -> Result<Option<CheckpointPair>, FasterError<'static>> {
+        Ok(self.recoverable_checkpoints()?.into_iter().next())
+    }
+
+    // Every index and hybrid log checkpoint pair that can be recovered together, newest first
+    pub(crate) fn recoverable_checkpoints(
+        &self,
+    ) -> Result<Vec<CheckpointPair>, FasterError<'static>> {
+        let checkpoints = self.list_checkpoints()?;
+        Ok(checkpoints
+            .iter()
+            .rev()
+            .filter(|checkpoint| checkpoint.has_hybrid_log())
+            .filter_map(|hybrid_log| pair_for(hybrid_log, &checkpoints))
+            .collect())
+    }
+
+    /// Deletes all but the `retain` most recent recoverable checkpoints.
+    ///
+    /// Index checkpoints needed to recover a retained hybrid log checkpoint are kept as well.
+    /// The metadata faster-rs saved with a checkpoint is deleted along with it.
+    /// Returns the checkpoints that were deleted.
+    pub fn prune_checkpoints(
+        &self,
+        retain: usize,
+    ) -> Result<Vec<CheckpointInfo>, FasterError<'static>> {
+        let storage_dir = self.storage_dir.as_ref().ok_or(FasterError::InvalidType)?;
+        let checkpoints = self.list_checkpoints()?;
+        let mut keep: HashSet<String> = HashSet::new();
+        for pair in self.recoverable_checkpoints()?.into_iter().take(retain) {
+            keep.insert(pair.index_token);
+            keep.insert(pair.hybrid_log_token);
+        }
+
+        let mut removed = Vec::new();
+        for checkpoint in checkpoints {
+            if keep.contains(&checkpoint.token) {
+                continue;
+            }
+            if checkpoint.has_index() {
+                fs::remove_dir_all(index_path(storage_dir, &checkpoint.token))?;
+            }
+            if checkpoint.has_hybrid_log() {
+                fs::remove_dir_all(hybrid_log_path(storage_dir, &checkpoint.token))?;
+            }
+            fs::remove_file(store_metadata_path(storage_dir, &checkpoint.token))?;
+            removed.push(checkpoint);
+        }
+        Ok(removed)
+    }
+}
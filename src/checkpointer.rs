@@ -0,0 +1,171 @@
+use crate::catalog::Catalog;
+use crate::status::Status;
+use crate::util::*;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// How often operation and log growth policies check whether a checkpoint is due
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// How often a running checkpoint is driven and checked for durability
+const PERSIST_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// When the background checkpointer configured with
+/// [with_checkpoint_policy](struct.FasterKvBuilder.html#method.with_checkpoint_policy)
+/// takes a full checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckpointPolicy {
+    /// Checkpoint every time the given duration has passed
+    Interval(Duration),
+    /// Checkpoint once the given number of upserts, RMWs and deletes has been issued
+    Operations(u64),
+    /// Checkpoint once the tail of the log has grown by the given number of bytes
+    LogGrowth(u64),
+}
+
+// The store outlives the checkpointer thread, which is joined before the store is destroyed
+struct StorePtr(*mut ffi::faster_t);
+unsafe impl Send for StorePtr {}
+
+type Shutdown = Arc<(Mutex<bool>, Condvar)>;
+
+// Waits for `timeout`, returning whether the checkpointer was stopped meanwhile
+fn stopped_within(shutdown: &Shutdown, timeout: Duration) -> bool {
+    let (lock, condvar) = &**shutdown;
+    let stopped = lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let (stopped, _) = condvar
+        .wait_timeout_while(stopped, timeout, |stopped| !*stopped)
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *stopped
+}
+
+// Drives the checkpoint with `token` through the checkpointer's session until it is durable.
+// Returns false if it failed or the checkpointer was stopped first.
+fn wait_until_persisted(store: &StorePtr, token: &str, shutdown: &Shutdown) -> bool {
+    loop {
+        match checkpoint_status(store.0, token) {
+            Ok(Status::Ok) => return true,
+            Ok(Status::Pending) => {}
+            _ => return false,
+        }
+        // Every session has to move through the phases of the checkpoint
+        unsafe {
+            ffi::faster_complete_pending(store.0, false);
+            ffi::faster_refresh_session(store.0);
+        }
+        if stopped_within(shutdown, PERSIST_POLL_INTERVAL) {
+            return false;
+        }
+    }
+}
+
+pub(crate) struct Checkpointer {
+    shutdown: Shutdown,
+    handle: Option<JoinHandle<()>>,
+    checkpoints: Arc<Mutex<Vec<CheckPoint>>>,
+}
+
+impl Checkpointer {
+    pub(crate) fn spawn(
+        faster_t: *mut ffi::faster_t,
+        policy: CheckpointPolicy,
+        operations: Option<Arc<AtomicU64>>,
+        catalog: Option<Arc<Catalog>>,
+    ) -> Checkpointer {
+        let shutdown = Arc::new((Mutex::new(false), Condvar::new()));
+        let checkpoints = Arc::new(Mutex::new(Vec::new()));
+        let store = StorePtr(faster_t);
+        let handle = {
+            let shutdown = Arc::clone(&shutdown);
+            let checkpoints = Arc::clone(&checkpoints);
+            thread::spawn(move || {
+                let store = store;
+                let wait = match policy {
+                    CheckpointPolicy::Interval(interval) => interval,
+                    _ => POLL_INTERVAL,
+                };
+                let mut last_operations = 0;
+                let mut last_tail = unsafe { ffi::faster_tail_address(store.0) };
+                loop {
+                    if stopped_within(&shutdown, wait) {
+                        break;
+                    }
+
+                    let due = match policy {
+                        CheckpointPolicy::Interval(_) => true,
+                        CheckpointPolicy::Operations(count) => {
+                            let current = operations
+                                .as_ref()
+                                .map_or(0, |operations| operations.load(Ordering::Relaxed));
+                            current - last_operations >= count
+                        }
+                        CheckpointPolicy::LogGrowth(bytes) => {
+                            let tail = unsafe { ffi::faster_tail_address(store.0) };
+                            tail - last_tail >= bytes
+                        }
+                    };
+                    if !due {
+                        continue;
+                    }
+
+                    // FASTER takes checkpoints through the session of the calling thread. It is
+                    // only open while checkpointing, as an idle session would hold up the others.
+                    unsafe { ffi::faster_start_session(store.0) };
+                    let start =
+                        || unsafe { checkpoint_from_result(ffi::faster_checkpoint(store.0)) };
+                    let result = match &catalog {
+                        Some(catalog) => catalog.take(start),
+                        None => start().map(|checkpoint| (checkpoint, Ok(()))),
+                    };
+                    // A checkpoint that was not started because another one is still running is
+                    // retried on the next tick
+                    if let Ok((checkpoint, saved)) = result {
+                        if checkpoint.checked {
+                            if let Some(operations) = &operations {
+                                last_operations = operations.load(Ordering::Relaxed);
+                            }
+                            last_tail = unsafe { ffi::faster_tail_address(store.0) };
+                            // A checkpoint without metadata still recovers, but is not listed
+                            if let Err(_err) = saved {
+                                #[cfg(feature = "tracing")]
+                                tracing::warn!(
+                                    error = %_err,
+                                    token = %checkpoint.token,
+                                    "failed to save the metadata of a checkpoint"
+                                );
+                            }
+                            // Only durable checkpoints are reported
+                            if wait_until_persisted(&store, &checkpoint.token, &shutdown) {
+                                checkpoints
+                                    .lock()
+                                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                                    .push(checkpoint);
+                            }
+                        }
+                    }
+                    unsafe { ffi::faster_stop_session(store.0) };
+                }
+            })
+        };
+        Checkpointer {
+            shutdown,
+            handle: Some(handle),
+            checkpoints,
+        }
+    }
+
+    pub(crate) fn checkpoints(&self) -> Vec<CheckPoint> {
+        self.checkpoints.lock().unwrap().clone()
+    }
+
+    pub(crate) fn stop(&mut self) {
+        let (lock, condvar) = &*self.shutdown;
+        *lock.lock().unwrap() = true;
+        condvar.notify_all();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Encodings used to store keys and values in FASTER.
+//!
+//! A store encodes with [Bincode](struct.Bincode.html) unless it is built with another codec
+//! through [build_with_codec](../struct.FasterKvBuilder.html#method.build_with_codec).
+//! Records written with one codec can only be read back with the same one.
+use crate::keyspace::{EncodedKey, ROOT};
+use crate::status::Status;
+use crate::{FasterError, FasterKv};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::convert::TryInto;
+use std::sync::mpsc::Receiver;
+
+// The receiver of a read of encoded bytes
+type BytesReceiver = Receiver<Result<Vec<u8>, FasterError<'static>>>;
+
+/// Encodes values of type `T` to the bytes stored in FASTER and decodes them again.
+///
+/// Codecs are zero-sized types that are only used as a type parameter of the store.
+pub trait Codec<T>: 'static {
+    fn encode(value: &T) -> Result<Vec<u8>, FasterError<'static>>;
+
+    fn decode(bytes: &[u8]) -> Result<T, FasterError<'static>>;
+
+    /// Encodes `value` for a write, borrowing it if it already is the encoding. Copies the
+    /// result of `encode` by default.
+    fn encode_borrowed(value: &T) -> Result<Cow<'_, [u8]>, FasterError<'static>> {
+        Self::encode(value).map(Cow::Owned)
+    }
+}
+
+/// Compact binary encoding of any serde type, the default codec.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bincode;
+
+impl<T> Codec<T> for Bincode
+where
+    T: Serialize + DeserializeOwned,
+{
+    fn encode(value: &T) -> Result<Vec<u8>, FasterError<'static>> {
+        Ok(bincode::serialize(value)?)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, FasterError<'static>> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+/// Plain numbers stored as their little-endian bytes, without going through serde.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FixedWidth;
+
+/// Types with a fixed-width little-endian representation, see [FixedWidth](struct.FixedWidth.html).
+pub trait Pod: Sized {
+    const WIDTH: usize;
+
+    fn write_le(&self, bytes: &mut Vec<u8>);
+
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+macro_rules! pod_impl {
+    ($($ty:ident)*) => {
+        $(
+            impl Pod for $ty {
+                const WIDTH: usize = std::mem::size_of::<$ty>();
+
+                #[inline]
+                fn write_le(&self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(&self.to_le_bytes());
+                }
+
+                #[inline]
+                fn read_le(bytes: &[u8]) -> Self {
+                    $ty::from_le_bytes(bytes.try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+pod_impl!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128 f32 f64);
+
+impl<T> Codec<T> for FixedWidth
+where
+    T: Pod,
+{
+    fn encode(value: &T) -> Result<Vec<u8>, FasterError<'static>> {
+        let mut bytes = Vec::with_capacity(T::WIDTH);
+        value.write_le(&mut bytes);
+        Ok(bytes)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, FasterError<'static>> {
+        if bytes.len() != T::WIDTH {
+            return Err(FasterError::CodecError(format!(
+                "expected {} bytes, found {}",
+                T::WIDTH,
+                bytes.len()
+            )));
+        }
+        Ok(T::read_le(bytes))
+    }
+}
+
+/// Stores byte vectors as they are, for sharing a store with producers that use their own
+/// encoding.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Raw;
+
+impl Codec<Vec<u8>> for Raw {
+    fn encode(value: &Vec<u8>) -> Result<Vec<u8>, FasterError<'static>> {
+        Ok(value.clone())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Vec<u8>, FasterError<'static>> {
+        Ok(bytes.to_vec())
+    }
+
+    fn encode_borrowed(value: &Vec<u8>) -> Result<Cow<'_, [u8]>, FasterError<'static>> {
+        Ok(Cow::Borrowed(value))
+    }
+}
+
+/// Operations on borrowed byte slices, which are handed to FASTER without being copied.
+impl FasterKv<Raw> {
+    /// Like [upsert](struct.FasterKv.html#method.upsert), for a key and value that are
+    /// borrowed rather than owned.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::codec::Raw;
+    /// use faster_rs::{FasterKvBuilder, Status};
+    ///
+    /// let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+    ///     .build_with_codec::<Raw>()
+    ///     .unwrap();
+    /// let frame = b"key:value";
+    /// store.upsert_bytes(&frame[..3], &frame[4..], 1).unwrap();
+    /// let (status, recv) = store.read_bytes(b"key", 2).unwrap();
+    /// assert_eq!(status, Status::Ok);
+    /// assert_eq!(recv.recv().unwrap().unwrap(), b"value");
+    /// ```
+    pub fn upsert_bytes(
+        &self,
+        key: &[u8],
+        value: &[u8],
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        let value = self.expiring_value(Cow::Borrowed(value), crate::ttl::NEVER);
+        let key = EncodedKey::new(ROOT, Cow::Borrowed(key));
+        self.upsert_encoded(&key, &value, monotonic_serial_number)
+    }
+
+    /// Like [read](struct.FasterKv.html#method.read), for a borrowed key.
+    pub fn read_bytes(
+        &self,
+        key: &[u8],
+        monotonic_serial_number: u64,
+    ) -> Result<(Status, BytesReceiver), FasterError<'static>> {
+        let key = EncodedKey::new(ROOT, Cow::Borrowed(key));
+        self.read_encoded(&key, monotonic_serial_number)
+    }
+
+    /// Like [delete](struct.FasterKv.html#method.delete), for a borrowed key.
+    pub fn delete_bytes(
+        &self,
+        key: &[u8],
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        let key = EncodedKey::new(ROOT, Cow::Borrowed(key));
+        self.delete_encoded(&key, monotonic_serial_number)
+    }
+}
+
+/// MessagePack encoding of any serde type, with structs encoded as maps.
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePack;
+
+#[cfg(feature = "msgpack")]
+impl<T> Codec<T> for MessagePack
+where
+    T: Serialize + DeserializeOwned,
+{
+    fn encode(value: &T) -> Result<Vec<u8>, FasterError<'static>> {
+        rmp_serde::to_vec_named(value).map_err(|err| FasterError::CodecError(err.to_string()))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, FasterError<'static>> {
+        rmp_serde::from_slice(bytes).map_err(|err| FasterError::CodecError(err.to_string()))
+    }
+}
+
+/// CBOR encoding of any serde type.
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl<T> Codec<T> for Cbor
+where
+    T: Serialize + DeserializeOwned,
+{
+    fn encode(value: &T) -> Result<Vec<u8>, FasterError<'static>> {
+        serde_cbor::to_vec(value).map_err(|err| FasterError::CodecError(err.to_string()))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, FasterError<'static>> {
+        serde_cbor::from_slice(bytes).map_err(|err| FasterError::CodecError(err.to_string()))
+    }
+}
@@ -0,0 +1,389 @@
+//! Value types with ready-made Read-Modify-Write logic for common aggregations.
+//!
+//! Each type wraps the aggregated state and implements [FasterRmw](../trait.FasterRmw.html), so
+//! it can be passed straight to `rmw`. The modification is a value of the same type holding the
+//! new observation, for example `Max(sample)`.
+use crate::{FasterError, FasterRmw};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Keeps the largest value seen.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct Max<T>(pub T);
+
+impl<T> FasterRmw for Max<T>
+where
+    T: PartialOrd + Clone + Serialize + DeserializeOwned,
+{
+    #[inline]
+    fn rmw(&self, modification: Self) -> Self {
+        match modification.0 > self.0 {
+            true => modification,
+            false => self.clone(),
+        }
+    }
+}
+
+/// Keeps the smallest value seen.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct Min<T>(pub T);
+
+impl<T> FasterRmw for Min<T>
+where
+    T: PartialOrd + Clone + Serialize + DeserializeOwned,
+{
+    #[inline]
+    fn rmw(&self, modification: Self) -> Self {
+        match modification.0 < self.0 {
+            true => modification,
+            false => self.clone(),
+        }
+    }
+}
+
+/// Keeps the most recently written value.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct Last<T>(pub T);
+
+impl<T> FasterRmw for Last<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    #[inline]
+    fn rmw(&self, modification: Self) -> Self {
+        modification
+    }
+}
+
+/// Counts events. Each modification adds its count, wrapping around on overflow.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(transparent)]
+pub struct Counter(pub u64);
+
+impl Counter {
+    /// A modification counting a single event
+    pub fn one() -> Self {
+        Counter(1)
+    }
+}
+
+impl FasterRmw for Counter {
+    #[inline]
+    fn rmw(&self, modification: Self) -> Self {
+        Counter(self.0.wrapping_add(modification.0))
+    }
+}
+
+/// Integers that can be added without overflowing, see [SaturatingAdd](struct.SaturatingAdd.html).
+pub trait Saturating: Copy {
+    fn saturating_add(self, other: Self) -> Self;
+}
+
+macro_rules! saturating_impl {
+    ($($ty:ident)*) => {
+        $(
+            impl Saturating for $ty {
+                #[inline]
+                fn saturating_add(self, other: Self) -> Self {
+                    $ty::saturating_add(self, other)
+                }
+            }
+        )*
+    };
+}
+
+saturating_impl!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+/// Sums values, clamping at the numeric bounds instead of overflowing.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(transparent)]
+pub struct SaturatingAdd<T>(pub T);
+
+impl<T> FasterRmw for SaturatingAdd<T>
+where
+    T: Saturating + Serialize + DeserializeOwned,
+{
+    #[inline]
+    fn rmw(&self, modification: Self) -> Self {
+        SaturatingAdd(self.0.saturating_add(modification.0))
+    }
+}
+
+/// Appends values, keeping only the `N` most recent ones.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct BoundedVec<T, const N: usize>(pub Vec<T>);
+
+impl<T, const N: usize> FasterRmw for BoundedVec<T, N>
+where
+    T: Clone + Serialize + DeserializeOwned,
+{
+    fn rmw(&self, modification: Self) -> Self {
+        let total = self.0.len() + modification.0.len();
+        let skip = total.saturating_sub(N);
+        let values = self
+            .0
+            .iter()
+            .cloned()
+            .chain(modification.0)
+            .skip(skip)
+            .collect();
+        BoundedVec(values)
+    }
+}
+
+// Hashes eight bytes at a time through the MurmurHash3 finalizer, so sketches persisted by one
+// build can be merged by another. The hashers in std are not guaranteed to be stable across
+// releases.
+struct StableHasher(u64);
+
+fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.0 = mix(self.0 ^ u64::from_le_bytes(word)).wrapping_add(chunk.len() as u64);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        mix(self.0)
+    }
+}
+
+fn stable_hash<T: Hash + ?Sized>(item: &T) -> u64 {
+    let mut hasher = StableHasher(0);
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Estimates the number of distinct items seen, using `2^P` one-byte registers.
+///
+/// The standard error is about `1.04 / sqrt(2^P)`, so `P = 12` gives roughly 1.6% with 4 KiB
+/// per value. `P` must be between 4 and 16.
+///
+/// # Example
+/// ```
+/// use faster_rs::combinators::HyperLogLog;
+/// use faster_rs::FasterKv;
+///
+/// let store = FasterKv::default();
+/// for user in &["alice", "bob", "alice"] {
+///     store.rmw(&1u64, &HyperLogLog::<12>::of(user), 1).unwrap();
+/// }
+/// let (_, recv) = store.read::<u64, HyperLogLog<12>>(&1u64, 2).unwrap();
+/// assert_eq!(recv.recv().unwrap().unwrap().estimate().round(), 2.0);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HyperLogLog<const P: u8> {
+    registers: Vec<u8>,
+}
+
+impl<const P: u8> HyperLogLog<P> {
+    // Evaluated when `new` is instantiated, so an unsupported precision fails to compile
+    const VALID_PRECISION: () = assert!(
+        P >= 4 && P <= 16,
+        "HyperLogLog precision must be between 4 and 16"
+    );
+
+    /// An empty sketch
+    pub fn new() -> Self {
+        let () = Self::VALID_PRECISION;
+        HyperLogLog {
+            registers: vec![0; 1 << P],
+        }
+    }
+
+    /// A sketch containing a single item, to be used as a modification
+    pub fn of<T: Hash + ?Sized>(item: &T) -> Self {
+        let mut sketch = Self::new();
+        sketch.insert(item);
+        sketch
+    }
+
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        let hash = stable_hash(item);
+        let index = (hash >> (64 - P)) as usize;
+        // Position of the first set bit in the remaining bits, counting from one
+        let rank = ((hash << P) | (1 << (P - 1))).leading_zeros() as u8 + 1;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Estimated number of distinct items inserted
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match P {
+            4 => 0.673,
+            5 => 0.697,
+            6 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|register| 2f64.powi(-i32::from(*register)))
+            .sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self
+            .registers
+            .iter()
+            .filter(|register| **register == 0)
+            .count();
+        // Linear counting is more accurate while many registers are still empty
+        if estimate <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
+
+impl<const P: u8> Default for HyperLogLog<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const P: u8> FasterRmw for HyperLogLog<P> {
+    /// # Panics
+    /// If either sketch does not have `2^P` registers, see [try_rmw](#method.try_rmw).
+    fn rmw(&self, modification: Self) -> Self {
+        self.try_rmw(modification)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Fails if either sketch does not have `2^P` registers, e.g. because it was stored with
+    /// another precision under the same key.
+    fn try_rmw(&self, modification: Self) -> Result<Self, FasterError<'static>> {
+        for sketch in &[self, &modification] {
+            if sketch.registers.len() != 1 << P {
+                return Err(FasterError::MergeError(format!(
+                    "HyperLogLog<{}> expects {} registers, found {}",
+                    P,
+                    1 << P,
+                    sketch.registers.len()
+                )));
+            }
+        }
+        let registers = self
+            .registers
+            .iter()
+            .zip(modification.registers.iter())
+            .map(|(current, new)| *current.max(new))
+            .collect();
+        Ok(HyperLogLog { registers })
+    }
+}
+
+/// Tracks the `K` most frequent items with the mergeable Misra-Gries summary.
+///
+/// Counts are lower bounds: each may be short by at most `n / (K + 1)` for `n` total
+/// occurrences, and any item occurring more often than that is guaranteed to be tracked.
+///
+/// # Example
+/// ```
+/// use faster_rs::combinators::TopK;
+/// use faster_rs::FasterKv;
+///
+/// let store = FasterKv::default();
+/// for word in &["a", "b", "a", "c", "a"] {
+///     store.rmw(&1u64, &TopK::<String, 2>::of(word.to_string()), 1).unwrap();
+/// }
+/// let (_, recv) = store.read::<u64, TopK<String, 2>>(&1u64, 2).unwrap();
+/// assert_eq!(recv.recv().unwrap().unwrap().top()[0].0, "a");
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TopK<T, const K: usize> {
+    // Sorted by descending count
+    counts: Vec<(T, u64)>,
+}
+
+impl<T, const K: usize> TopK<T, K>
+where
+    T: Hash + Eq + Clone,
+{
+    /// An empty summary
+    pub fn new() -> Self {
+        TopK { counts: Vec::new() }
+    }
+
+    /// A summary of a single occurrence of `item`, to be used as a modification
+    pub fn of(item: T) -> Self {
+        Self::with_count(item, 1)
+    }
+
+    /// A summary of `count` occurrences of `item`, to be used as a modification
+    pub fn with_count(item: T, count: u64) -> Self {
+        let mut summary = Self::new();
+        if count > 0 && K > 0 {
+            summary.counts.push((item, count));
+        }
+        summary
+    }
+
+    /// The tracked items and their counts, most frequent first
+    pub fn top(&self) -> &[(T, u64)] {
+        &self.counts
+    }
+
+    fn merge(&self, other: &Self) -> Self {
+        let mut merged: HashMap<&T, u64> = HashMap::with_capacity(self.counts.len() * 2);
+        for (item, count) in self.counts.iter().chain(other.counts.iter()) {
+            *merged.entry(item).or_insert(0) += count;
+        }
+        let mut counts: Vec<(T, u64)> = merged
+            .into_iter()
+            .map(|(item, count)| (item.clone(), count))
+            .collect();
+        counts.sort_by_key(|entry| Reverse(entry.1));
+        if counts.len() > K {
+            let cutoff = counts[K].1;
+            counts.truncate(K);
+            for entry in counts.iter_mut() {
+                entry.1 -= cutoff;
+            }
+            counts.retain(|entry| entry.1 > 0);
+        }
+        TopK { counts }
+    }
+}
+
+impl<T, const K: usize> Default for TopK<T, K>
+where
+    T: Hash + Eq + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const K: usize> FasterRmw for TopK<T, K>
+where
+    T: Hash + Eq + Clone + Serialize + DeserializeOwned,
+{
+    fn rmw(&self, modification: Self) -> Self {
+        self.merge(&modification)
+    }
+}
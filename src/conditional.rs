@@ -0,0 +1,176 @@
+use crate::codec::Codec;
+use crate::faster_traits::{conditional_decision, RmwAction};
+use crate::instrument::{callback_failed, Operation};
+use crate::keyspace::{encode_key, ROOT};
+use crate::rmw_with::RmwLogic;
+use crate::{FasterConditionalRmw, FasterError, FasterKv};
+
+use std::marker::PhantomData;
+
+// Applies the FasterConditionalRmw logic of the encoded modification, for stores whose values
+// have to be rewritten by `rmw_in_context`
+pub(crate) struct ConditionalRmw<'m, C, V> {
+    pub(crate) modification: &'m [u8],
+    pub(crate) error: Option<FasterError<'static>>,
+    pub(crate) codec: PhantomData<fn() -> (C, V)>,
+}
+
+impl<'m, C, V> RmwLogic for ConditionalRmw<'m, C, V>
+where
+    C: Codec<V>,
+    V: FasterConditionalRmw,
+{
+    fn decide(&mut self, current: Option<&[u8]>) -> RmwAction<Vec<u8>> {
+        match conditional_decision::<C, V>(current, self.modification) {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                callback_failed("rmw_conditional", &err);
+                self.error = Some(err);
+                // Leave the record untouched
+                RmwAction::Keep
+            }
+        }
+    }
+}
+
+// Replaces the value if its encoding equals `expected`
+struct CompareAndSwap {
+    expected: Vec<u8>,
+    new: Vec<u8>,
+    swapped: bool,
+}
+
+impl RmwLogic for CompareAndSwap {
+    fn decide(&mut self, current: Option<&[u8]>) -> RmwAction<Vec<u8>> {
+        self.swapped = current == Some(&self.expected[..]);
+        match self.swapped {
+            true => RmwAction::Update(self.new.clone()),
+            false => RmwAction::Keep,
+        }
+    }
+}
+
+// Inserts the value unless the key already holds one
+struct InsertIfAbsent {
+    value: Vec<u8>,
+    inserted: bool,
+}
+
+impl RmwLogic for InsertIfAbsent {
+    fn decide(&mut self, current: Option<&[u8]>) -> RmwAction<Vec<u8>> {
+        self.inserted = current.is_none();
+        match current {
+            None => RmwAction::Update(self.value.clone()),
+            Some(_) => RmwAction::Keep,
+        }
+    }
+}
+
+impl<C> FasterKv<C> {
+    /// Replaces the value stored for `key` with `new` if it currently equals `expected`.
+    ///
+    /// Values are compared by their encoded bytes, not with `PartialEq`: `expected` is encoded
+    /// with the store's codec and matches if the bytes equal those stored. Values that are equal
+    /// but encode differently, such as a `HashMap` serialized in another iteration order or
+    /// floats with different NaN payloads, do not match. The comparison and the write happen
+    /// within a single Read-Modify-Write, so they are atomic with respect to other sessions.
+    /// Returns whether the value was replaced, which is never the case for a missing key. A
+    /// pending operation is completed before returning, without waiting for the other pending
+    /// operations of the session.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1u64, &10u64, 1).unwrap();
+    /// assert!(!store.compare_and_swap(&1u64, &11u64, &12u64, 2).unwrap());
+    /// assert!(store.compare_and_swap(&1u64, &10u64, &12u64, 3).unwrap());
+    /// ```
+    pub fn compare_and_swap<K, V>(
+        &self,
+        key: &K,
+        expected: &V,
+        new: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<bool, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.compare_and_swap_in(ROOT, key, expected, new, monotonic_serial_number)
+    }
+
+    pub(crate) fn compare_and_swap_in<K, V>(
+        &self,
+        namespace: &[u8],
+        key: &K,
+        expected: &V,
+        new: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<bool, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.observed(Operation::CompareAndSwap, || {
+            self.count_operation();
+            let encoded_key = encode_key::<C, K>(namespace, key)?;
+            let mut swap = CompareAndSwap {
+                expected: <C as Codec<V>>::encode(expected)?,
+                new: <C as Codec<V>>::encode(new)?,
+                swapped: false,
+            };
+            // A missing key never reaches the comparison
+            self.rmw_in_context(&encoded_key, &mut swap, true, monotonic_serial_number)?;
+            Ok(swap.swapped)
+        })
+    }
+
+    /// Inserts `value` for `key` unless the key already holds a value.
+    ///
+    /// The check and the insert happen within a single Read-Modify-Write, so they are atomic
+    /// with respect to other sessions. Returns whether the value was inserted. A pending
+    /// operation is completed before returning, without waiting for the other pending
+    /// operations of the session.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    ///
+    /// assert!(store.upsert_if_absent(&1u64, &10u64, 1).unwrap());
+    /// assert!(!store.upsert_if_absent(&1u64, &20u64, 2).unwrap());
+    /// ```
+    pub fn upsert_if_absent<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<bool, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.upsert_if_absent_in(ROOT, key, value, monotonic_serial_number)
+    }
+
+    pub(crate) fn upsert_if_absent_in<K, V>(
+        &self,
+        namespace: &[u8],
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<bool, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.observed(Operation::UpsertIfAbsent, || {
+            self.count_operation();
+            let encoded_key = encode_key::<C, K>(namespace, key)?;
+            let mut insert = InsertIfAbsent {
+                value: <C as Codec<V>>::encode(value)?,
+                inserted: false,
+            };
+            self.rmw_in_context(&encoded_key, &mut insert, false, monotonic_serial_number)?;
+            Ok(insert.inserted)
+        })
+    }
+}
@@ -8,6 +8,25 @@ pub enum FasterError<'a> {
     RecoveryError,
     CheckpointError,
     BuilderError(&'a str),
+    SerializationError(bincode::Error),
+    OutOfMemory,
+    Corruption,
+    Aborted,
+    UnknownStatus(u8),
+    CallbackPanic,
+    InvalidToken(String),
+    InvalidAddress(u64),
+    ScanError,
+    CompactionError,
+    NoCheckpoint(String),
+    InvalidSessionId(String),
+    CodecError(String),
+    TtlNotEnabled,
+    TtlMismatch(bool),
+    MergeError(String),
+    InvalidIndexName(String),
+    DuplicateIndex(String),
+    InvalidTableName(String),
 }
 
 impl<'a> fmt::Display for FasterError<'a> {
@@ -18,6 +37,34 @@ impl<'a> fmt::Display for FasterError<'a> {
             FasterError::RecoveryError => write!(f, "Failed to recover"),
             FasterError::CheckpointError => write!(f, "Checkpoint failed"),
             FasterError::BuilderError(err) => write!(f, "Builder error: {}", err),
+            FasterError::SerializationError(err) => write!(f, "Serialization error: {}", err),
+            FasterError::OutOfMemory => write!(f, "FASTER ran out of memory"),
+            FasterError::Corruption => write!(f, "FASTER detected corrupted data"),
+            FasterError::Aborted => write!(f, "Operation was aborted"),
+            FasterError::UnknownStatus(status) => write!(f, "Unknown status code {}", status),
+            FasterError::CallbackPanic => write!(f, "Read-Modify-Write logic panicked"),
+            FasterError::InvalidToken(token) => write!(f, "Invalid token {:?}", token),
+            FasterError::InvalidAddress(address) => write!(f, "Invalid log address {}", address),
+            FasterError::ScanError => write!(f, "Failed to scan the log"),
+            FasterError::CompactionError => write!(f, "Failed to compact the log"),
+            FasterError::NoCheckpoint(dir) => {
+                write!(f, "No recoverable checkpoint found in {}", dir)
+            }
+            FasterError::InvalidSessionId(id) => write!(f, "Invalid session id {}", id),
+            FasterError::CodecError(err) => write!(f, "Codec error: {}", err),
+            FasterError::TtlNotEnabled => write!(f, "Store was built without a TTL"),
+            FasterError::TtlMismatch(true) => {
+                write!(f, "Checkpoint was taken by a store with a TTL")
+            }
+            FasterError::TtlMismatch(false) => {
+                write!(f, "Checkpoint was taken by a store without a TTL")
+            }
+            FasterError::MergeError(err) => write!(f, "Cannot merge values: {}", err),
+            FasterError::InvalidIndexName(name) => write!(f, "Invalid index name {:?}", name),
+            FasterError::DuplicateIndex(name) => {
+                write!(f, "An index named {:?} is already registered", name)
+            }
+            FasterError::InvalidTableName(name) => write!(f, "Invalid table name {:?}", name),
         }
     }
 }
@@ -28,4 +75,10 @@ impl<'a> From<io::Error> for FasterError<'a> {
     }
 }
 
+impl<'a> From<bincode::Error> for FasterError<'a> {
+    fn from(e: bincode::Error) -> Self {
+        FasterError::SerializationError(e)
+    }
+}
+
 impl<'a> Error for FasterError<'a> {}
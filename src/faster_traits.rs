@@ -2,69 +2,238 @@ extern crate bincode;
 extern crate libc;
 extern crate libfaster_sys as ffi;
 
-use crate::status;
+use crate::codec::Codec;
+use crate::instrument::{callback_failed, receiver_dropped, sender_dropped};
+use crate::status::{self, Status};
+use crate::ttl;
+use crate::FasterError;
 
-use bincode::deserialize;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::cell::RefCell;
+use std::panic;
 use std::sync::mpsc::Sender;
 
 pub trait FasterKey: DeserializeOwned + Serialize {}
 
 pub trait FasterValue: DeserializeOwned + Serialize {}
 
+thread_local! {
+    // Failures inside rmw_callback cannot be returned through FASTER, so they are parked here
+    // and picked up by the rmw or complete_pending call running on the same thread.
+    static RMW_ERROR: RefCell<Option<FasterError<'static>>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn take_rmw_error() -> Result<(), FasterError<'static>> {
+    match RMW_ERROR.with(|error| error.borrow_mut().take()) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Context handed to FASTER with a read.
+///
+/// The callback runs exactly once per read, before `faster_read` returns unless the read went
+/// pending. The context stays owned by the caller, who releases it once the status is known,
+/// and only reads that went pending hand it over to the callback.
+pub(crate) struct ReadContext<T> {
+    sender: Sender<Result<T, FasterError<'static>>>,
+    pending: bool,
+    // Set by the callback when the record found had expired
+    expired: bool,
+}
+
+impl<T> ReadContext<T> {
+    pub(crate) fn into_raw(sender: Sender<Result<T, FasterError<'static>>>) -> *mut Self {
+        Box::into_raw(Box::new(ReadContext {
+            sender,
+            pending: false,
+            expired: false,
+        }))
+    }
+
+    /// Whether the read completed on an expired record, which is reported as missing.
+    pub(crate) unsafe fn expired(context: *mut Self) -> bool {
+        (*context).expired
+    }
+
+    /// Releases the context after `faster_read` returned `status`, unless the read went
+    /// pending, in which case the callback releases it on completion.
+    pub(crate) unsafe fn release_unless_pending<E>(context: *mut Self, status: &Result<Status, E>) {
+        match status {
+            Ok(Status::Pending) => (*context).pending = true,
+            // Dropping the sender signals that there is no value
+            _ => drop(Box::from_raw(context)),
+        }
+    }
+}
+
 #[inline(always)]
-pub unsafe extern "C" fn read_callback<T>(
-    sender: *mut libc::c_void,
+pub unsafe extern "C" fn read_callback<C, T, const EXPIRING: bool>(
+    context: *mut libc::c_void,
     value: *const u8,
     length: u64,
     status: u32,
 ) where
-    T: DeserializeOwned,
+    C: Codec<T>,
 {
-    let boxed_sender = Box::from_raw(sender as *mut Sender<T>);
-    let sender = *boxed_sender;
-    if status == status::OK.into() {
-        let val = deserialize(std::slice::from_raw_parts(value, length as usize)).unwrap();
-        // TODO: log error
-        let _ = sender.send(val);
+    let context = context as *mut ReadContext<T>;
+    let result = match status::to_result(status as u8) {
+        Ok(Status::Ok) => {
+            match ttl::unexpired::<EXPIRING>(std::slice::from_raw_parts(value, length as usize)) {
+                Some(value) => Some(C::decode(value)),
+                None => {
+                    (*context).expired = true;
+                    None
+                }
+            }
+        }
+        Ok(_) => None,
+        Err(err) => Some(Err(err)),
+    };
+    match result {
+        Some(result) => {
+            if let Err(err) = &result {
+                callback_failed("read", err);
+            }
+            // The receiver may already be gone, which is fine
+            if (*context).sender.send(result).is_err() {
+                receiver_dropped("read");
+            }
+        }
+        None => sender_dropped("read"),
+    }
+    if (*context).pending {
+        drop(Box::from_raw(context));
     }
 }
 
 #[inline(always)]
-pub unsafe extern "C" fn rmw_callback<T>(
+pub unsafe extern "C" fn rmw_callback<C, T>(
     current: *const u8,
     length_current: u64,
-    modification: *mut u8,
+    modification: *const u8,
     length_modification: u64,
     dst: *mut u8,
 ) -> u64
 where
-    T: Serialize + DeserializeOwned + FasterRmw,
+    C: Codec<T>,
+    T: FasterRmw,
 {
-    let val: T = deserialize(std::slice::from_raw_parts(current, length_current as usize)).unwrap();
-    let modif = deserialize(std::slice::from_raw_parts_mut(
-        modification,
-        length_modification as usize,
-    ))
-    .unwrap();
-    let modified = val.rmw(modif);
-    let encoded = bincode::serialize(&modified).unwrap();
+    let current = std::slice::from_raw_parts(current, length_current as usize);
+    let modification = std::slice::from_raw_parts(modification, length_modification as usize);
+    let encoded = merge::<C, T>(current, modification);
     let size = encoded.len();
-    if dst != std::ptr::null_mut() {
+    if !dst.is_null() {
         encoded.as_ptr().copy_to(dst, size);
     }
     size as u64
 }
 
-pub trait FasterRmw: DeserializeOwned + Serialize {
+/// Merges the encoded `modification` into the encoded `current` value with the
+/// [FasterRmw](trait.FasterRmw.html) logic of `T`.
+///
+/// If that fails, the error is parked for the issuing call and `current` is returned, which
+/// leaves the record untouched.
+pub(crate) fn merge<C, T>(current: &[u8], modification: &[u8]) -> Vec<u8>
+where
+    C: Codec<T>,
+    T: FasterRmw,
+{
+    let result = panic::catch_unwind(|| -> Result<Vec<u8>, FasterError<'static>> {
+        let val = C::decode(current)?;
+        let modif = C::decode(modification)?;
+        let modified = val.try_rmw(modif)?;
+        C::encode(&modified)
+    });
+    match result.unwrap_or(Err(FasterError::CallbackPanic)) {
+        Ok(encoded) => encoded,
+        Err(err) => {
+            callback_failed("rmw", &err);
+            RMW_ERROR.with(|error| *error.borrow_mut() = Some(err));
+            current.to_vec()
+        }
+    }
+}
+
+// Actions reported to FASTER by the callbacks of conditional Read-Modify-Writes
+pub(crate) const RMW_UPDATE: u8 = 0;
+pub(crate) const RMW_KEEP: u8 = 1;
+pub(crate) const RMW_DELETE: u8 = 2;
+
+/// Decides what a conditional Read-Modify-Write of the encoded `modification` does with the
+/// encoded `current` value, which is `None` for a missing key. Panics are caught.
+pub(crate) fn conditional_decision<C, T>(
+    current: Option<&[u8]>,
+    modification: &[u8],
+) -> Result<RmwAction<Vec<u8>>, FasterError<'static>>
+where
+    C: Codec<T>,
+    T: FasterConditionalRmw,
+{
+    panic::catch_unwind(|| -> Result<RmwAction<Vec<u8>>, FasterError<'static>> {
+        let modif = C::decode(modification)?;
+        let outcome = match current {
+            None => T::initial(modif),
+            Some(current) => C::decode(current)?.conditional_rmw(modif),
+        };
+        Ok(match outcome {
+            RmwAction::Update(value) => RmwAction::Update(C::encode(&value)?),
+            RmwAction::Keep => RmwAction::Keep,
+            RmwAction::Delete => RmwAction::Delete,
+        })
+    })
+    .unwrap_or(Err(FasterError::CallbackPanic))
+}
+
+pub unsafe extern "C" fn conditional_rmw_callback<C, T>(
+    current: *const u8,
+    length_current: u64,
+    modification: *const u8,
+    length_modification: u64,
+    dst: *mut u8,
+    action: *mut u8,
+) -> u64
+where
+    C: Codec<T>,
+    T: FasterConditionalRmw,
+{
+    // A missing key is passed as a null pointer
+    let current = match current.is_null() {
+        true => None,
+        false => Some(std::slice::from_raw_parts(current, length_current as usize)),
+    };
+    let modification = std::slice::from_raw_parts(modification, length_modification as usize);
+    let outcome = match conditional_decision::<C, T>(current, modification) {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            callback_failed("rmw_conditional", &err);
+            RMW_ERROR.with(|error| *error.borrow_mut() = Some(err));
+            // Leave the record untouched
+            RmwAction::Keep
+        }
+    };
+    let (code, size) = match outcome {
+        RmwAction::Update(encoded) => {
+            if !dst.is_null() {
+                encoded.as_ptr().copy_to(dst, encoded.len());
+            }
+            (RMW_UPDATE, encoded.len())
+        }
+        RmwAction::Keep => (RMW_KEEP, 0),
+        RmwAction::Delete => (RMW_DELETE, 0),
+    };
+    *action = code;
+    size as u64
+}
+
+pub trait FasterRmw {
     /// Specify custom Read-Modify-Write logic
     ///
     /// # Example
     /// ```
-    /// use faster_rs::{status, FasterKv, FasterRmw};
+    /// use faster_rs::{FasterKv, FasterRmw, Status};
     /// use serde_derive::{Deserialize, Serialize};
-    /// use std::sync::mpsc::Receiver;
     ///
     /// #[derive(Serialize, Deserialize)]
     /// struct MyU64 {
@@ -82,10 +251,67 @@ pub trait FasterRmw: DeserializeOwned + Serialize {
     /// let key = 5 as u64;
     /// let value = MyU64 { value: 12 };
     /// let modification = MyU64 { value: 17 };
-    /// store.upsert(&key, &value, 1);
-    /// store.rmw(&key, &modification, 1);
-    /// let (status, recv): (u8, Receiver<MyU64>) = store.read(&key, 1);
-    /// assert!(status == status::OK);
-    /// assert_eq!(recv.recv().unwrap().value, value.value + modification.value);
+    /// store.upsert(&key, &value, 1).unwrap();
+    /// store.rmw(&key, &modification, 1).unwrap();
+    /// let (status, recv) = store.read::<u64, MyU64>(&key, 1).unwrap();
+    /// assert_eq!(status, Status::Ok);
+    /// let read = recv.recv().unwrap().unwrap();
+    /// assert_eq!(read.value, value.value + modification.value);
     fn rmw(&self, modification: Self) -> Self;
+
+    /// Read-Modify-Write logic that can reject a modification, which is what the store calls.
+    ///
+    /// A returned error fails the Read-Modify-Write and leaves the record as it is. Defaults to
+    /// [rmw](#tymethod.rmw).
+    fn try_rmw(&self, modification: Self) -> Result<Self, FasterError<'static>>
+    where
+        Self: Sized,
+    {
+        Ok(self.rmw(modification))
+    }
+}
+
+/// What a [FasterConditionalRmw](trait.FasterConditionalRmw.html) does with a record.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RmwAction<V> {
+    /// Store the given value
+    Update(V),
+    /// Leave the record as it is, or absent for a missing key
+    Keep,
+    /// Delete the record
+    Delete,
+}
+
+pub trait FasterConditionalRmw: DeserializeOwned + Serialize {
+    /// Specify Read-Modify-Write logic that may also keep or delete the record
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{FasterConditionalRmw, FasterKv, RmwAction, Status};
+    /// use serde_derive::{Deserialize, Serialize};
+    ///
+    /// // Stock level that is removed once it runs out
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Stock(i64);
+    /// impl FasterConditionalRmw for Stock {
+    ///     fn conditional_rmw(&self, modification: Self) -> RmwAction<Self> {
+    ///         match self.0 + modification.0 {
+    ///             level if level <= 0 => RmwAction::Delete,
+    ///             level => RmwAction::Update(Stock(level)),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let store = FasterKv::default();
+    /// store.rmw_conditional(&1u64, &Stock(2), 1).unwrap();
+    /// store.rmw_conditional(&1u64, &Stock(-2), 2).unwrap();
+    /// let (status, _) = store.read::<u64, Stock>(&1u64, 3).unwrap();
+    /// assert_eq!(status, Status::NotFound);
+    /// ```
+    fn conditional_rmw(&self, modification: Self) -> RmwAction<Self>;
+
+    /// Decides what to do for a key without a value. Stores the modification by default.
+    fn initial(modification: Self) -> RmwAction<Self> {
+        RmwAction::Update(modification)
+    }
 }
@@ -1,9 +1,9 @@
-use crate::{FasterKey, FasterRmw, FasterValue};
+use crate::{FasterError, FasterKey, FasterRmw, FasterValue};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::ops::Add;
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::hash::Hash;
 
 impl<T> FasterKey for T where T: Serialize + DeserializeOwned {}
@@ -87,3 +87,39 @@ impl<T: Clone + Serialize + DeserializeOwned + Hash + Eq> FasterRmw for HashSet<
         union.cloned().collect()
     }
 }
+
+impl<T: Clone + Serialize + DeserializeOwned + Ord> FasterRmw for BTreeSet<T> {
+    #[inline]
+    fn rmw(&self, new: BTreeSet<T>) -> BTreeSet<T> {
+        let mut union = self.clone();
+        union.extend(new);
+        union
+    }
+}
+
+impl<K, V> FasterRmw for HashMap<K, V>
+where
+    K: Clone + Serialize + DeserializeOwned + Hash + Eq,
+    V: Clone + FasterRmw,
+{
+    /// # Panics
+    /// If merging the values of a key fails, see [try_rmw](#method.try_rmw).
+    #[inline]
+    fn rmw(&self, new: HashMap<K, V>) -> HashMap<K, V> {
+        self.try_rmw(new).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Merges the values of keys present in both maps with their own `try_rmw`, failing if any
+    /// of them does.
+    fn try_rmw(&self, new: HashMap<K, V>) -> Result<HashMap<K, V>, FasterError<'static>> {
+        let mut merged = self.clone();
+        for (key, value) in new {
+            let merged_value = match merged.get(&key) {
+                Some(current) => current.try_rmw(value)?,
+                None => value,
+            };
+            merged.insert(key, merged_value);
+        }
+        Ok(merged)
+    }
+}
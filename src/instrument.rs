@@ -0,0 +1,353 @@
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+use crate::status::Status;
+use crate::CheckPoint;
+use crate::FasterError;
+use crate::FasterKv;
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+use crate::{ReadWith, Recover};
+
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Store operations instrumented by the `metrics` and `tracing` features.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Operation {
+    Upsert,
+    Read,
+    Rmw,
+    RmwConditional,
+    Delete,
+    ReadWith,
+    RmwWith,
+    CompareAndSwap,
+    UpsertIfAbsent,
+    UpsertBatch,
+    ReadBatch,
+    RmwBatch,
+    Compact,
+    ShiftBeginAddress,
+    CompletePending,
+    Checkpoint,
+    CheckpointIndex,
+    CheckpointHybridLog,
+    Recover,
+}
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+impl Operation {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Operation::Upsert => "upsert",
+            Operation::Read => "read",
+            Operation::Rmw => "rmw",
+            Operation::RmwConditional => "rmw_conditional",
+            Operation::Delete => "delete",
+            Operation::ReadWith => "read_with",
+            Operation::RmwWith => "rmw_with",
+            Operation::CompareAndSwap => "compare_and_swap",
+            Operation::UpsertIfAbsent => "upsert_if_absent",
+            Operation::UpsertBatch => "upsert_batch",
+            Operation::ReadBatch => "read_batch",
+            Operation::RmwBatch => "rmw_batch",
+            Operation::Compact => "compact",
+            Operation::ShiftBeginAddress => "shift_begin_address",
+            Operation::CompletePending => "complete_pending",
+            Operation::Checkpoint => "checkpoint",
+            Operation::CheckpointIndex => "checkpoint_index",
+            Operation::CheckpointHybridLog => "checkpoint_hybrid_log",
+            Operation::Recover => "recover",
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+pub(crate) const OPERATIONS: [Operation; 19] = [
+    Operation::Upsert,
+    Operation::Read,
+    Operation::Rmw,
+    Operation::RmwConditional,
+    Operation::Delete,
+    Operation::ReadWith,
+    Operation::RmwWith,
+    Operation::CompareAndSwap,
+    Operation::UpsertIfAbsent,
+    Operation::UpsertBatch,
+    Operation::ReadBatch,
+    Operation::RmwBatch,
+    Operation::Compact,
+    Operation::ShiftBeginAddress,
+    Operation::CompletePending,
+    Operation::Checkpoint,
+    Operation::CheckpointIndex,
+    Operation::CheckpointHybridLog,
+    Operation::Recover,
+];
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Outcome {
+    Ok,
+    Pending,
+    NotFound,
+    Error,
+}
+
+#[cfg(feature = "metrics")]
+pub(crate) const OUTCOMES: [Outcome; 4] = [
+    Outcome::Ok,
+    Outcome::Pending,
+    Outcome::NotFound,
+    Outcome::Error,
+];
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+impl Outcome {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::Pending => "pending",
+            Outcome::NotFound => "not_found",
+            Outcome::Error => "error",
+        }
+    }
+}
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+/// Results of instrumented operations, classified by the status they carry.
+pub(crate) trait Observe {
+    fn outcome(&self) -> Outcome;
+}
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+pub(crate) trait Completed {
+    fn status(&self) -> Option<Status>;
+}
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+impl Completed for Status {
+    fn status(&self) -> Option<Status> {
+        Some(*self)
+    }
+}
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+impl<R> Completed for (Status, R) {
+    fn status(&self) -> Option<Status> {
+        Some(self.0)
+    }
+}
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+impl<R> Completed for ReadWith<R> {
+    fn status(&self) -> Option<Status> {
+        Some(match self {
+            ReadWith::Found(_) => Status::Ok,
+            ReadWith::NotFound => Status::NotFound,
+            ReadWith::Pending(_) => Status::Pending,
+        })
+    }
+}
+
+// Batches report the outcome of each item separately
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+impl<T> Completed for Vec<T> {
+    fn status(&self) -> Option<Status> {
+        None
+    }
+}
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+impl Completed for bool {
+    fn status(&self) -> Option<Status> {
+        None
+    }
+}
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+impl Completed for () {
+    fn status(&self) -> Option<Status> {
+        None
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Completed for CheckPoint {
+    fn status(&self) -> Option<Status> {
+        None
+    }
+}
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+impl Completed for Recover {
+    fn status(&self) -> Option<Status> {
+        None
+    }
+}
+
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+impl<T: Completed, E> Observe for Result<T, E> {
+    fn outcome(&self) -> Outcome {
+        match self.as_ref().map(Completed::status) {
+            Ok(Some(Status::Pending)) => Outcome::Pending,
+            Ok(Some(Status::NotFound)) => Outcome::NotFound,
+            Ok(_) => Outcome::Ok,
+            Err(_) => Outcome::Error,
+        }
+    }
+}
+
+impl<C> FasterKv<C> {
+    /// Runs `operation` inside a span, recording its outcome and latency.
+    #[cfg(any(feature = "metrics", feature = "tracing"))]
+    #[inline(always)]
+    pub(crate) fn observed<T, F>(&self, operation: Operation, f: F) -> T
+    where
+        T: Observe,
+        F: FnOnce() -> T,
+    {
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "faster_operation",
+            operation = operation.name(),
+            outcome = tracing::field::Empty
+        );
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+        #[cfg(feature = "metrics")]
+        let started = Instant::now();
+        let result = f();
+        let outcome = result.outcome();
+        #[cfg(feature = "metrics")]
+        self.metrics
+            .record(operation, started.elapsed().as_nanos() as u64, outcome);
+        #[cfg(feature = "tracing")]
+        span.record("outcome", outcome.label());
+        result
+    }
+
+    #[cfg(not(any(feature = "metrics", feature = "tracing")))]
+    #[inline(always)]
+    pub(crate) fn observed<T, F>(&self, _operation: Operation, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        f()
+    }
+
+    /// Like `observed`, for starting a checkpoint. The metrics record a started checkpoint once
+    /// `checkpoint_status` reports it durable, so that its latency is the time to durability.
+    #[inline(always)]
+    pub(crate) fn observed_checkpoint<E, F>(
+        &self,
+        operation: Operation,
+        f: F,
+    ) -> Result<CheckPoint, E>
+    where
+        F: FnOnce() -> Result<CheckPoint, E>,
+    {
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "faster_operation",
+            operation = operation.name(),
+            outcome = tracing::field::Empty
+        );
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+        #[cfg(feature = "metrics")]
+        let started = Instant::now();
+        let result = f();
+        #[cfg(feature = "metrics")]
+        match &result {
+            Ok(checkpoint) if checkpoint.checked => {
+                self.metrics
+                    .checkpoint_started(operation, &checkpoint.token, started)
+            }
+            // Checkpoints that were not started never become durable
+            _ => self.metrics.record(
+                operation,
+                started.elapsed().as_nanos() as u64,
+                Outcome::Error,
+            ),
+        }
+        #[cfg(feature = "tracing")]
+        span.record("outcome", result.outcome().label());
+        #[cfg(not(any(feature = "metrics", feature = "tracing")))]
+        let _ = operation;
+        result
+    }
+}
+
+/// Session lifecycle events reported by the `tracing` feature.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SessionEvent {
+    Start,
+    Continue,
+    Stop,
+}
+
+#[cfg(feature = "tracing")]
+impl SessionEvent {
+    fn name(self) -> &'static str {
+        match self {
+            SessionEvent::Start => "start",
+            SessionEvent::Continue => "continue",
+            SessionEvent::Stop => "stop",
+        }
+    }
+}
+
+/// Runs a session lifecycle call inside a span.
+#[inline(always)]
+pub(crate) fn session_span<T, F>(event: SessionEvent, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    #[cfg(feature = "tracing")]
+    let span = tracing::debug_span!(
+        "faster_session",
+        event = event.name(),
+        thread = ?std::thread::current().id()
+    );
+    #[cfg(feature = "tracing")]
+    let _entered = span.enter();
+    #[cfg(not(feature = "tracing"))]
+    let _ = event;
+    f()
+}
+
+// The helpers below report what happens inside the callbacks FASTER invokes, which have no
+// way of returning errors to the caller of the operation. They compile to nothing without the
+// `tracing` feature.
+
+/// Reports a callback that failed to decode, encode or merge a value, or whose logic panicked.
+#[inline(always)]
+pub(crate) fn callback_failed(callback: &'static str, error: &FasterError) {
+    #[cfg(feature = "tracing")]
+    match error {
+        FasterError::SerializationError(_) | FasterError::CodecError(_) => {
+            tracing::warn!(callback, error = %error, "failed to deserialize a value")
+        }
+        FasterError::CallbackPanic => tracing::error!(callback, "callback panicked"),
+        _ => tracing::warn!(callback, error = %error, "callback failed"),
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (callback, error);
+}
+
+/// Reports a read whose receiver was dropped before its value could be delivered.
+#[inline(always)]
+pub(crate) fn receiver_dropped(callback: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(callback, "receiver dropped before the value was delivered");
+    #[cfg(not(feature = "tracing"))]
+    let _ = callback;
+}
+
+/// Reports a read that completed without a value, so its sender is dropped unused.
+#[inline(always)]
+pub(crate) fn sender_dropped(callback: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(callback, "no value found, dropping the sender");
+    #[cfg(not(feature = "tracing"))]
+    let _ = callback;
+}
@@ -0,0 +1,110 @@
+// The records of tables and those faster-rs writes for itself, such as the entries of
+// secondary indexes, have keys in a key space of their own. FASTER tells them apart by a flag
+// in the key length, FASTER_INTERNAL_KEY in faster-c.h, so the keys of callers outside of
+// tables are stored exactly as they are encoded and can never collide with them. Internal keys
+// start with a namespace: a tag for what they belong to, followed by its name.
+use crate::codec::Codec;
+use crate::FasterError;
+
+use std::borrow::Cow;
+
+const INTERNAL_KEY: u64 = 1 << 63;
+
+/// Tag of the records of a secondary index.
+pub(crate) const INDEX: u8 = 0;
+/// Tag of the records of a table.
+pub(crate) const TABLE: u8 = 1;
+
+/// The namespace of the keys of callers outside of tables, which are not prefixed.
+pub(crate) const ROOT: &[u8] = &[];
+
+/// Longest name of something kept in the internal key space, such as an index.
+pub(crate) const MAX_NAME_LENGTH: usize = u8::MAX as usize;
+
+/// The key length to pass to FASTER for an internal key.
+pub(crate) fn internal_length(key: &[u8]) -> u64 {
+    key.len() as u64 | INTERNAL_KEY
+}
+
+/// Splits the key length of a record found in the log into its length in bytes and whether
+/// the key is internal.
+pub(crate) fn split_length(key_length: u64) -> (usize, bool) {
+    (
+        (key_length & !INTERNAL_KEY) as usize,
+        key_length & INTERNAL_KEY != 0,
+    )
+}
+
+/// Whether `name` may name something kept in the internal key space: between 1 and 255 ASCII
+/// letters, digits, `-` and `_`.
+pub(crate) fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// The prefix of the internal keys of what `tag` names `name`, which must be valid.
+pub(crate) fn named(tag: u8, name: &str) -> Vec<u8> {
+    let mut prefix = Vec::with_capacity(2 + name.len());
+    prefix.push(tag);
+    prefix.push(name.len() as u8);
+    prefix.extend_from_slice(name.as_bytes());
+    prefix
+}
+
+/// An encoded key with the key space it belongs to.
+pub(crate) struct EncodedKey<'k> {
+    bytes: Cow<'k, [u8]>,
+    internal: bool,
+}
+
+impl<'k> EncodedKey<'k> {
+    /// The key `encoded` has in `namespace`, which is `ROOT` or the prefix of internal keys.
+    pub(crate) fn new(namespace: &[u8], encoded: Cow<'k, [u8]>) -> Self {
+        if namespace.is_empty() {
+            return EncodedKey::in_space(encoded, false);
+        }
+        let mut bytes = Vec::with_capacity(namespace.len() + encoded.len());
+        bytes.extend_from_slice(namespace);
+        bytes.extend_from_slice(&encoded);
+        EncodedKey::in_space(Cow::Owned(bytes), true)
+    }
+
+    /// A key whose bytes already carry their namespace.
+    pub(crate) fn in_space(bytes: Cow<'k, [u8]>, internal: bool) -> Self {
+        EncodedKey { bytes, internal }
+    }
+
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const u8 {
+        self.bytes.as_ptr()
+    }
+
+    /// The key length to pass to FASTER.
+    pub(crate) fn length(&self) -> u64 {
+        match self.internal {
+            true => internal_length(&self.bytes),
+            false => self.bytes.len() as u64,
+        }
+    }
+
+    pub(crate) fn internal(&self) -> bool {
+        self.internal
+    }
+}
+
+/// Encodes `key` for `namespace`, which is `ROOT` or the id of a table.
+pub(crate) fn encode_key<'k, C, K>(
+    namespace: &[u8],
+    key: &'k K,
+) -> Result<EncodedKey<'k>, FasterError<'static>>
+where
+    C: Codec<K>,
+{
+    Ok(EncodedKey::new(namespace, C::encode_borrowed(key)?))
+}
@@ -2,305 +2,635 @@ extern crate bincode;
 extern crate libc;
 extern crate libfaster_sys as ffi;
 
+#[cfg(feature = "async")]
+mod async_ops;
+mod batch;
 mod builder;
+mod catalog;
+mod checkpointer;
+pub mod codec;
+pub mod combinators;
+mod conditional;
 mod faster_error;
 mod faster_traits;
 mod impls;
+mod instrument;
+mod keyspace;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod read_with;
+mod recovery;
+mod rmw_with;
+mod scan;
+mod secondary_index;
+mod session;
+mod stats;
 pub mod status;
+mod table;
+mod ttl;
+mod typed;
 mod util;
 
+#[cfg(feature = "async")]
+pub use crate::async_ops::{CompletePendingFuture, OperationFuture, ReadFuture};
 pub use crate::builder::FasterKvBuilder;
+use crate::catalog::Catalog;
+pub use crate::catalog::{CheckpointInfo, CheckpointKind, CheckpointPair};
+pub use crate::checkpointer::CheckpointPolicy;
+use crate::checkpointer::Checkpointer;
+use crate::codec::{Bincode, Codec};
+use crate::conditional::ConditionalRmw;
 pub use crate::faster_error::FasterError;
-use crate::faster_traits::{read_callback, rmw_callback};
-pub use crate::faster_traits::{FasterKey, FasterRmw, FasterValue};
+use crate::faster_traits::{conditional_rmw_callback, read_callback, take_rmw_error, ReadContext};
+pub use crate::faster_traits::{
+    FasterConditionalRmw, FasterKey, FasterRmw, FasterValue, RmwAction,
+};
+use crate::instrument::{session_span, Operation, SessionEvent};
+use crate::keyspace::{encode_key, EncodedKey, ROOT};
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+pub use crate::read_with::ReadWith;
+use crate::recovery::session_ids_from_result;
+pub use crate::recovery::{RecoveredSession, SessionId};
+pub use crate::scan::{Iter, LogRecord, LogScan};
+pub use crate::secondary_index::SecondaryIndex;
+use crate::secondary_index::{Indexes, Write};
+pub use crate::session::Session;
+pub use crate::stats::Stats;
+pub use crate::status::Status;
+pub use crate::table::{Table, TableSize};
+use crate::ttl::PinnedClock;
+pub use crate::ttl::TtlPolicy;
+pub use crate::typed::TypedFasterKv;
 use crate::util::*;
+pub use crate::util::{CheckPoint, Recover};
 
-use std::ffi::CStr;
-use std::ffi::CString;
 use std::fs;
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
 
-#[no_mangle]
-pub unsafe extern "C" fn deallocate_vec(vec: *mut u8, length: u64) {
-    drop(Vec::from_raw_parts(vec, length as usize, length as usize));
-}
-
-pub struct FasterKv {
+/// A FASTER key-value store whose keys and values are encoded with the codec `C`.
+pub struct FasterKv<C = Bincode> {
     faster_t: *mut ffi::faster_t,
     storage_dir: Option<String>,
+    catalog: Option<Arc<Catalog>>,
+    // Only counted when a checkpoint policy needs it, to keep the operations contention free
+    operations: Option<Arc<AtomicU64>>,
+    ttl: Option<TtlPolicy>,
+    indexes: Arc<Indexes>,
+    #[cfg(feature = "metrics")]
+    metrics: Metrics,
+    checkpointer: Option<Checkpointer>,
+    codec: PhantomData<fn() -> C>,
 }
 
-impl FasterKv {
-    pub fn upsert<K, V>(&self, key: &K, value: &V, monotonic_serial_number: u64) -> u8
+impl<C> FasterKv<C> {
+    pub fn upsert<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
     where
-        K: FasterKey,
-        V: FasterValue,
+        C: Codec<K> + Codec<V>,
     {
-        let mut encoded_key = bincode::serialize(key).unwrap();
-        let encoded_key_length = encoded_key.len();
-        let encoded_key_ptr = encoded_key.as_mut_ptr();
-        let mut encoded_value = bincode::serialize(value).unwrap();
-        let encoded_value_length = encoded_value.len();
-        let encoded_value_ptr = encoded_value.as_mut_ptr();
-        std::mem::forget(encoded_key);
-        std::mem::forget(encoded_value);
-        unsafe {
-            ffi::faster_upsert(
-                self.faster_t,
-                encoded_key_ptr,
-                encoded_key_length as u64,
-                encoded_value_ptr,
-                encoded_value_length as u64,
-                monotonic_serial_number,
-            )
-        }
+        self.upsert_expiring(ROOT, key, value, ttl::NEVER, monotonic_serial_number)
     }
 
-    pub fn read<K, V>(&self, key: &K, monotonic_serial_number: u64) -> (u8, Receiver<V>)
+    // Upserts into `namespace`, ROOT or the id of a table
+    fn upsert_expiring<K, V>(
+        &self,
+        namespace: &[u8],
+        key: &K,
+        value: &V,
+        expiry: u64,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
     where
-        K: FasterKey,
-        V: FasterValue,
+        C: Codec<K> + Codec<V>,
     {
-        let mut encoded_key = bincode::serialize(key).unwrap();
-        let encoded_key_length = encoded_key.len();
-        let encoded_key_ptr = encoded_key.as_mut_ptr();
-        let (sender, receiver) = channel();
-        let sender_ptr: *mut Sender<V> = Box::into_raw(Box::new(sender));
-        std::mem::forget(encoded_key);
-        let status = unsafe {
-            ffi::faster_read(
-                self.faster_t,
-                encoded_key_ptr,
-                encoded_key_length as u64,
-                monotonic_serial_number,
-                Some(read_callback::<V>),
-                sender_ptr as *mut libc::c_void,
-            )
-        };
-        (status, receiver)
-    }
-
-    pub fn rmw<K, V>(&self, key: &K, value: &V, monotonic_serial_number: u64) -> u8
+        let encoded_key = encode_key::<C, K>(namespace, key)?;
+        let encoded_value = self.encode_value(value, expiry)?;
+        self.upsert_encoded(&encoded_key, &encoded_value, monotonic_serial_number)
+    }
+
+    fn upsert_encoded(
+        &self,
+        encoded_key: &EncodedKey,
+        encoded_value: &[u8],
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.observed(Operation::Upsert, || {
+            self.count_operation();
+            if self.maintains_indexes(encoded_key) {
+                let write = Write::Replace(encoded_value.to_vec());
+                return self.write_indexed(encoded_key, write, monotonic_serial_number);
+            }
+            // FASTER copies the buffers before returning, so they are only borrowed for the call
+            let status = unsafe {
+                ffi::faster_upsert(
+                    self.faster_t,
+                    encoded_key.as_ptr(),
+                    encoded_key.length(),
+                    encoded_value.as_ptr(),
+                    encoded_value.len() as u64,
+                    monotonic_serial_number,
+                )
+            };
+            status::to_result(status)
+        })
+    }
+
+    /// Reads the value stored for `key`.
+    ///
+    /// The value is delivered through the returned `Receiver` once FASTER has found it, which
+    /// for [Pending](status/enum.Status.html) reads happens during `complete_pending`. The
+    /// sender is dropped without a value if the key does not exist.
+    pub fn read<K, V>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<(Status, Receiver<Result<V, FasterError<'static>>>), FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.read_in(ROOT, key, monotonic_serial_number)
+    }
+
+    fn read_in<K, V>(
+        &self,
+        namespace: &[u8],
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<(Status, Receiver<Result<V, FasterError<'static>>>), FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        let encoded_key = encode_key::<C, K>(namespace, key)?;
+        self.read_encoded(&encoded_key, monotonic_serial_number)
+    }
+
+    fn read_encoded<V>(
+        &self,
+        encoded_key: &EncodedKey,
+        monotonic_serial_number: u64,
+    ) -> Result<(Status, Receiver<Result<V, FasterError<'static>>>), FasterError<'static>>
+    where
+        C: Codec<V>,
+    {
+        self.observed(Operation::Read, || {
+            let (sender, receiver) = channel();
+            let context = ReadContext::into_raw(sender);
+            let callback = match self.expiring() {
+                true => read_callback::<C, V, true>,
+                false => read_callback::<C, V, false>,
+            };
+            let status = unsafe {
+                ffi::faster_read(
+                    self.faster_t,
+                    encoded_key.as_ptr(),
+                    encoded_key.length(),
+                    monotonic_serial_number,
+                    Some(callback),
+                    context as *mut libc::c_void,
+                )
+            };
+            let status = match status::to_result(status) {
+                // Expired records are reported like missing ones
+                Ok(Status::Ok) if unsafe { ReadContext::expired(context) } => Ok(Status::NotFound),
+                status => status,
+            };
+            unsafe { ReadContext::release_unless_pending(context, &status) };
+            status.map(|status| (status, receiver))
+        })
+    }
+
+    /// Merges `modification` into the value stored for `key` using its
+    /// [FasterRmw](trait.FasterRmw.html) logic.
+    ///
+    /// If the merge fails to decode the stored value or panics, the record is left unchanged
+    /// and the error is returned here, or from `complete_pending` for pending operations.
+    pub fn rmw<K, V>(
+        &self,
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
     where
-        K: FasterKey,
         V: FasterRmw,
+        C: Codec<K> + Codec<V>,
     {
-        let mut encoded_key = bincode::serialize(key).unwrap();
-        let encoded_key_length = encoded_key.len();
-        let encoded_key_ptr = encoded_key.as_mut_ptr();
-        let mut encoded_value = bincode::serialize(value).unwrap();
-        let encoded_value_length = encoded_value.len();
-        let encoded_value_ptr = encoded_value.as_mut_ptr();
-        std::mem::forget(encoded_key);
-        std::mem::forget(encoded_value);
-        unsafe {
-            ffi::faster_rmw(
-                self.faster_t,
-                encoded_key_ptr,
-                encoded_key_length as u64,
-                encoded_value_ptr,
-                encoded_value_length as u64,
-                monotonic_serial_number,
-                Some(rmw_callback::<V>),
-            )
-        }
+        self.rmw_in(ROOT, key, modification, monotonic_serial_number)
+    }
+
+    fn rmw_in<K, V>(
+        &self,
+        namespace: &[u8],
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        V: FasterRmw,
+        C: Codec<K> + Codec<V>,
+    {
+        self.observed(Operation::Rmw, || {
+            self.count_operation();
+            // FASTER copies the buffers before returning, so they are only borrowed for the call
+            let encoded_key = encode_key::<C, K>(namespace, key)?;
+            let encoded_value = self.encode_modification(modification)?;
+            if self.maintains_indexes(&encoded_key) {
+                let write = Write::Merge(encoded_value, self.merge_fn::<V>());
+                let status = self.write_indexed(&encoded_key, write, monotonic_serial_number);
+                take_rmw_error()?;
+                return status;
+            }
+            let _clock = PinnedClock::pin();
+            let status = unsafe {
+                ffi::faster_rmw(
+                    self.faster_t,
+                    encoded_key.as_ptr(),
+                    encoded_key.length(),
+                    encoded_value.as_ptr(),
+                    encoded_value.len() as u64,
+                    monotonic_serial_number,
+                    self.rmw_callback::<V>(),
+                )
+            };
+            take_rmw_error()?;
+            status::to_result(status)
+        })
+    }
+
+    /// Applies `modification` to the value stored for `key` using its
+    /// [FasterConditionalRmw](trait.FasterConditionalRmw.html) logic, which may also leave the
+    /// record unchanged or delete it.
+    ///
+    /// Returns [NotFound](status/enum.Status.html) if the key is missing and its
+    /// `initial` action does not store a value. Errors are reported like for `rmw`. On a store
+    /// with a TTL or secondary indexes, a pending operation is completed before returning.
+    pub fn rmw_conditional<K, V>(
+        &self,
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        V: FasterConditionalRmw,
+        C: Codec<K> + Codec<V>,
+    {
+        self.rmw_conditional_in(ROOT, key, modification, monotonic_serial_number)
+    }
+
+    fn rmw_conditional_in<K, V>(
+        &self,
+        namespace: &[u8],
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        V: FasterConditionalRmw,
+        C: Codec<K> + Codec<V>,
+    {
+        self.observed(Operation::RmwConditional, || {
+            self.count_operation();
+            let encoded_key = encode_key::<C, K>(namespace, key)?;
+            let encoded_value = <C as Codec<V>>::encode(modification)?;
+            if self.expiring() || self.maintains_indexes(&encoded_key) {
+                let mut conditional = ConditionalRmw::<C, V> {
+                    modification: &encoded_value,
+                    error: None,
+                    codec: PhantomData,
+                };
+                let status = self.rmw_in_context(
+                    &encoded_key,
+                    &mut conditional,
+                    false,
+                    monotonic_serial_number,
+                )?;
+                return match conditional.error.take() {
+                    Some(err) => Err(err),
+                    None => Ok(status),
+                };
+            }
+            let status = unsafe {
+                ffi::faster_rmw_conditional(
+                    self.faster_t,
+                    encoded_key.as_ptr(),
+                    encoded_key.length(),
+                    encoded_value.as_ptr(),
+                    encoded_value.len() as u64,
+                    monotonic_serial_number,
+                    Some(conditional_rmw_callback::<C, V>),
+                )
+            };
+            take_rmw_error()?;
+            status::to_result(status)
+        })
     }
 
     /// Deletes a previously inserted key.
     ///
-    /// Returns [NOT_FOUND](status/constant.NOT_FOUND.html) for un-inserted keys.
+    /// Returns [NotFound](status/enum.Status.html) for un-inserted keys.
     ///
     /// # Example
     /// ```
-    /// use faster_rs::{FasterKv, status};
+    /// use faster_rs::{FasterKv, Status};
     /// let store = FasterKv::default();
     ///
     /// let key = 1;
     /// let value = 42;
     ///
     /// // Insert key-value
-    /// store.upsert(&key, &value, 1);
+    /// store.upsert(&key, &value, 1).unwrap();
     ///
     /// // Read key-value
-    /// let (res, recv) = store.read(&key, 1);
-    /// assert_eq!(status::OK, res);
-    /// assert_eq!(value, recv.recv().unwrap());
+    /// let (res, recv) = store.read(&key, 1).unwrap();
+    /// assert_eq!(Status::Ok, res);
+    /// assert_eq!(value, recv.recv().unwrap().unwrap());
     ///
     /// // Delete key-value
-    /// store.delete(&key, 1);
+    /// store.delete(&key, 1).unwrap();
     ///
     /// // Re-read key-value and confirm deleted
-    /// let (res, recv) = store.read::<i32, i32>(&key, 1);
-    /// assert_eq!(status::NOT_FOUND, res);
+    /// let (res, recv) = store.read::<i32, i32>(&key, 1).unwrap();
+    /// assert_eq!(Status::NotFound, res);
     /// assert!(recv.recv().is_err());
     /// ```
-    pub fn delete<K>(&self, key: &K, monotonic_serial_number: u64) -> u8
+    pub fn delete<K>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
     where
-        K: FasterKey,
+        C: Codec<K>,
     {
-        let mut encoded_key = bincode::serialize(key).unwrap();
-        let encoded_key_length = encoded_key.len();
-        let encoded_key_ptr = encoded_key.as_mut_ptr();
-        std::mem::forget(encoded_key);
-        unsafe {
-            ffi::faster_delete(
-                self.faster_t,
-                encoded_key_ptr,
-                encoded_key_length as u64,
-                monotonic_serial_number,
-            )
-        }
+        self.delete_in(ROOT, key, monotonic_serial_number)
+    }
+
+    fn delete_in<K>(
+        &self,
+        namespace: &[u8],
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        C: Codec<K>,
+    {
+        let encoded_key = encode_key::<C, K>(namespace, key)?;
+        self.delete_encoded(&encoded_key, monotonic_serial_number)
+    }
+
+    fn delete_encoded(
+        &self,
+        encoded_key: &EncodedKey,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.observed(Operation::Delete, || {
+            self.count_operation();
+            if self.maintains_indexes(encoded_key) {
+                return self.write_indexed(encoded_key, Write::Delete, monotonic_serial_number);
+            }
+            let status = unsafe {
+                ffi::faster_delete(
+                    self.faster_t,
+                    encoded_key.as_ptr(),
+                    encoded_key.length(),
+                    monotonic_serial_number,
+                )
+            };
+            status::to_result(status)
+        })
     }
 
     pub fn size(&self) -> u64 {
         unsafe { ffi::faster_size(self.faster_t) }
     }
 
-    pub fn checkpoint(&self) -> Result<CheckPoint, FasterError> {
-        if self.storage_dir.is_none() {
-            return Err(FasterError::InvalidType);
-        }
+    /// Lowest address of the hybrid log that still holds records.
+    pub fn begin_address(&self) -> u64 {
+        unsafe { ffi::faster_begin_address(self.faster_t) }
+    }
 
-        let result = unsafe { ffi::faster_checkpoint(self.faster_t) };
-        match result.is_null() {
-            true => Err(FasterError::CheckpointError),
-            false => {
-                let boxed = unsafe { Box::from_raw(result) }; // makes sure memory is dropped
-                let token_str =
-                    unsafe { CStr::from_ptr((*boxed).token).to_str().unwrap().to_owned() };
-
-                let checkpoint = CheckPoint {
-                    checked: (*boxed).checked,
-                    token: token_str,
-                };
-                Ok(checkpoint)
-            }
-        }
+    /// Lowest address of the hybrid log that is held in memory.
+    pub fn head_address(&self) -> u64 {
+        unsafe { ffi::faster_head_address(self.faster_t) }
     }
 
-    pub fn checkpoint_index(&self) -> Result<CheckPoint, FasterError> {
-        if self.storage_dir.is_none() {
-            return Err(FasterError::InvalidType);
-        }
+    /// Records below this address are immutable and updated by copying them to the tail.
+    pub fn read_only_address(&self) -> u64 {
+        unsafe { ffi::faster_read_only_address(self.faster_t) }
+    }
 
-        let result = unsafe { ffi::faster_checkpoint_index(self.faster_t) };
-        match result.is_null() {
-            true => Err(FasterError::CheckpointError),
-            false => {
-                let boxed = unsafe { Box::from_raw(result) }; // makes sure memory is dropped
-                let token_str =
-                    unsafe { CStr::from_ptr((*boxed).token).to_str().unwrap().to_owned() };
-
-                let checkpoint = CheckPoint {
-                    checked: (*boxed).checked,
-                    token: token_str,
-                };
-                Ok(checkpoint)
+    /// Address at which the next record will be appended.
+    pub fn tail_address(&self) -> u64 {
+        unsafe { ffi::faster_tail_address(self.faster_t) }
+    }
+
+    /// Reclaims the log below `until_address`.
+    ///
+    /// Records in `[begin_address, until_address)` that are still the latest version of their
+    /// key are copied to the tail of the log, after which the begin address is shifted to
+    /// `until_address`. Deleted keys, superseded versions and, on stores with a TTL, expired
+    /// records are dropped. `until_address` must lie in the read-only region of the log.
+    pub fn compact(&self, until_address: u64) -> Result<(), FasterError<'static>> {
+        self.observed(Operation::Compact, || {
+            if until_address < self.begin_address() || until_address > self.read_only_address() {
+                return Err(FasterError::InvalidAddress(until_address));
             }
+            match self.compact_log(until_address) {
+                true => Ok(()),
+                false => Err(FasterError::CompactionError),
+            }
+        })
+    }
+
+    /// Truncates the log below `address` without preserving any of the records there.
+    ///
+    /// Disk segments below the new begin address are deleted. Keys whose latest version lies
+    /// below `address` are lost, so this should normally only follow a `compact`.
+    pub fn shift_begin_address(&self, address: u64) -> Result<(), FasterError<'static>> {
+        self.observed(Operation::ShiftBeginAddress, || {
+            if address < self.begin_address() || address > self.read_only_address() {
+                return Err(FasterError::InvalidAddress(address));
+            }
+            match unsafe { ffi::faster_shift_begin_address(self.faster_t, address) } {
+                true => Ok(()),
+                false => Err(FasterError::CompactionError),
+            }
+        })
+    }
+
+    /// Checkpoints taken by the background checkpointer, oldest first.
+    ///
+    /// Empty unless the store was built with a
+    /// [checkpoint policy](struct.FasterKvBuilder.html#method.with_checkpoint_policy).
+    pub fn checkpoints(&self) -> Vec<CheckPoint> {
+        match &self.checkpointer {
+            Some(checkpointer) => checkpointer.checkpoints(),
+            None => Vec::new(),
         }
     }
 
-    pub fn checkpoint_hybrid_log(&self) -> Result<CheckPoint, FasterError> {
+    pub fn checkpoint(&self) -> Result<CheckPoint, FasterError<'_>> {
+        self.observed_checkpoint(Operation::Checkpoint, || {
+            self.take_checkpoint(|| unsafe {
+                checkpoint_from_result(ffi::faster_checkpoint(self.faster_t))
+            })
+        })
+    }
+
+    pub fn checkpoint_index(&self) -> Result<CheckPoint, FasterError<'_>> {
+        self.observed_checkpoint(Operation::CheckpointIndex, || {
+            self.take_checkpoint(|| unsafe {
+                checkpoint_from_result(ffi::faster_checkpoint_index(self.faster_t))
+            })
+        })
+    }
+
+    pub fn checkpoint_hybrid_log(&self) -> Result<CheckPoint, FasterError<'_>> {
+        self.observed_checkpoint(Operation::CheckpointHybridLog, || {
+            self.take_checkpoint(|| unsafe {
+                checkpoint_from_result(ffi::faster_checkpoint_hybrid_log(self.faster_t))
+            })
+        })
+    }
+
+    /// Whether the checkpoint with `token` is durable.
+    ///
+    /// Returns [Ok](status/enum.Status.html) once every part of the checkpoint has been
+    /// persisted and `Pending` while it is still being taken, which requires the sessions of
+    /// the store to keep refreshing. Checkpoints not taken by this store are `NotFound`.
+    pub fn checkpoint_status(&self, token: &str) -> Result<Status, FasterError<'static>> {
         if self.storage_dir.is_none() {
             return Err(FasterError::InvalidType);
         }
-
-        let result = unsafe { ffi::faster_checkpoint_hybrid_log(self.faster_t) };
-        match result.is_null() {
-            true => Err(FasterError::CheckpointError),
-            false => {
-                let boxed = unsafe { Box::from_raw(result) }; // makes sure memory is dropped
-                let token_str =
-                    unsafe { CStr::from_ptr((*boxed).token).to_str().unwrap().to_owned() };
-
-                let checkpoint = CheckPoint {
-                    checked: (*boxed).checked,
-                    token: token_str,
-                };
-                Ok(checkpoint)
-            }
-        }
+        let status = checkpoint_status(self.faster_t, token);
+        #[cfg(feature = "metrics")]
+        self.metrics.checkpoint_status(token, &status);
+        status
     }
 
     pub fn recover(
         &self,
         index_token: String,
         hybrid_log_token: String,
-    ) -> Result<Recover, FasterError> {
-        if self.storage_dir.is_none() {
-            return Err(FasterError::InvalidType);
-        }
-        let index_token_c = CString::new(index_token).unwrap();
-        let index_token_ptr = index_token_c.into_raw();
-
-        let hybrid_token_c = CString::new(hybrid_log_token).unwrap();
-        let hybrid_token_ptr = hybrid_token_c.into_raw();
-
-        let recover_result = unsafe {
-            let rec = ffi::faster_recover(self.faster_t, index_token_ptr, hybrid_token_ptr);
-            let _ = CString::from_raw(index_token_ptr);
-            let _ = CString::from_raw(hybrid_token_ptr);
-            rec
-        };
-
-        match recover_result.is_null() {
-            true => Err(FasterError::RecoveryError),
-            false => {
-                let boxed = unsafe { Box::from_raw(recover_result) }; // makes sure mem is freed
-                let sessions_count = (*boxed).session_ids_count;
-                let mut session_ids_vec: Vec<String> = Vec::new();
-                for i in 0..sessions_count {
-                    let id = unsafe {
-                        CStr::from_ptr(((*boxed).session_ids).offset(37 * i as isize))
-                            .to_str()
-                            .unwrap()
-                            .to_owned()
+    ) -> Result<Recover, FasterError<'static>> {
+        self.observed(Operation::Recover, || {
+            if self.storage_dir.is_none() {
+                return Err(FasterError::InvalidType);
+            }
+            #[cfg(feature = "tracing")]
+            tracing::info!(
+                index_token = %index_token,
+                hybrid_log_token = %hybrid_log_token,
+                "recovering checkpoint"
+            );
+            self.check_store_metadata(&hybrid_log_token)?;
+            let index_token_c = token_to_c(index_token)?;
+            let hybrid_token_c = token_to_c(hybrid_log_token)?;
+
+            let recover_result = unsafe {
+                ffi::faster_recover(
+                    self.faster_t,
+                    index_token_c.as_ptr(),
+                    hybrid_token_c.as_ptr(),
+                )
+            };
+
+            match recover_result.is_null() {
+                true => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!("FASTER failed to recover the checkpoint");
+                    Err(FasterError::RecoveryError)
+                }
+                false => {
+                    let (status, version, session_ids) = unsafe {
+                        let result = &*recover_result;
+                        let session_ids = session_ids_from_result(
+                            result.session_ids,
+                            result.session_ids_count as usize,
+                        );
+                        let parts = (result.status, result.version, session_ids);
+                        ffi::faster_recover_result_destroy(recover_result);
+                        parts
+                    };
+                    let recover = Recover {
+                        status: status::to_result(status)?,
+                        version,
+                        session_ids: session_ids?,
                     };
-                    session_ids_vec.push(id);
+                    #[cfg(feature = "tracing")]
+                    tracing::info!(
+                        version = recover.version,
+                        sessions = recover.session_ids.len(),
+                        "recovered checkpoint"
+                    );
+                    Ok(recover)
                 }
-                let recover = Recover {
-                    status: (*boxed).status,
-                    version: (*boxed).version,
-                    session_ids: session_ids_vec,
-                };
-                Ok(recover)
             }
-        }
+        })
     }
 
-    pub fn complete_pending(&self, b: bool) -> () {
-        unsafe { ffi::faster_complete_pending(self.faster_t, b) }
+    /// Completes outstanding pending operations of this thread's session, blocking until all
+    /// of them are done if `b` is `true`.
+    ///
+    /// Returns the error of a pending Read-Modify-Write whose merge failed.
+    pub fn complete_pending(&self, b: bool) -> Result<(), FasterError<'static>> {
+        self.observed(Operation::CompletePending, || {
+            let _clock = PinnedClock::pin();
+            unsafe { ffi::faster_complete_pending(self.faster_t, b) };
+            take_rmw_error()
+        })
     }
 
-    pub fn start_session(&self) -> String {
-        unsafe {
-            let c_guid = ffi::faster_start_session(self.faster_t);
-            let rust_str = CStr::from_ptr(c_guid).to_str().unwrap().to_owned();
-            rust_str
-        }
+    /// Completes the pending operations of this thread's session that are ready without
+    /// blocking, and returns whether none are left.
+    #[cfg(feature = "async")]
+    pub(crate) fn try_complete_pending(&self) -> Result<bool, FasterError<'static>> {
+        self.observed(Operation::CompletePending, || {
+            let done = unsafe { ffi::faster_complete_pending(self.faster_t, false) };
+            take_rmw_error().map(|_| done)
+        })
     }
 
-    pub fn continue_session(&self, token: String) -> u64 {
-        let token_str = CString::new(token).unwrap();
-        let token_ptr = token_str.into_raw();
-        unsafe {
-            let result = ffi::faster_continue_session(self.faster_t, token_ptr);
-            let _ = CString::from_raw(token_ptr);
-            result
-        }
+    pub fn start_session(&self) -> Result<String, FasterError<'static>> {
+        session_span(SessionEvent::Start, || {
+            let rust_str = unsafe { token_from_c(ffi::faster_start_session(self.faster_t))? };
+            #[cfg(feature = "tracing")]
+            tracing::debug!(session = %rust_str, "started session");
+            Ok(rust_str)
+        })
     }
 
-    pub fn stop_session(&self) -> () {
-        unsafe { ffi::faster_stop_session(self.faster_t) }
+    pub fn continue_session(&self, token: String) -> Result<u64, FasterError<'static>> {
+        session_span(SessionEvent::Continue, || {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(session = %token, "continuing session");
+            let token_str = token_to_c(token)?;
+            let result = unsafe { ffi::faster_continue_session(self.faster_t, token_str.as_ptr()) };
+            #[cfg(feature = "tracing")]
+            tracing::debug!(serial_number = result, "continued session");
+            Ok(result)
+        })
     }
 
-    pub fn refresh(&self) -> () {
+    pub fn stop_session(&self) {
+        session_span(SessionEvent::Stop, || unsafe {
+            ffi::faster_stop_session(self.faster_t)
+        })
+    }
+
+    pub fn refresh(&self) {
         unsafe {
             ffi::faster_refresh_session(self.faster_t);
         }
     }
 
-    pub fn dump_distribution(&self) -> () {
+    pub fn dump_distribution(&self) {
         unsafe {
             ffi::faster_dump_distribution(self.faster_t);
         }
@@ -311,7 +641,7 @@ impl FasterKv {
     }
 
     // Warning: Calling this will remove the stored data
-    pub fn clean_storage(&self) -> Result<(), FasterError> {
+    pub fn clean_storage(&self) -> Result<(), FasterError<'_>> {
         match &self.storage_dir {
             None => Err(FasterError::InvalidType),
             Some(dir) => {
@@ -321,7 +651,17 @@ impl FasterKv {
         }
     }
 
-    fn destroy(&self) -> () {
+    fn count_operation(&self) {
+        self.count_operations(1);
+    }
+
+    fn count_operations(&self, count: u64) {
+        if let Some(operations) = &self.operations {
+            operations.fetch_add(count, Ordering::Relaxed);
+        }
+    }
+
+    fn destroy(&self) {
         unsafe {
             ffi::faster_destroy(self.faster_t);
         }
@@ -337,11 +677,14 @@ impl Default for FasterKv {
 }
 
 // In order to make sure we release the resources the C interface has allocated for the store
-impl Drop for FasterKv {
+impl<C> Drop for FasterKv<C> {
     fn drop(&mut self) {
+        if let Some(checkpointer) = &mut self.checkpointer {
+            checkpointer.stop();
+        }
         self.destroy();
     }
 }
 
-unsafe impl Send for FasterKv {}
-unsafe impl Sync for FasterKv {}
+unsafe impl<C> Send for FasterKv<C> {}
+unsafe impl<C> Sync for FasterKv<C> {}
@@ -0,0 +1,195 @@
+use crate::instrument::{Operation, Outcome, OPERATIONS, OUTCOMES};
+use crate::status::Status;
+use crate::util::checkpoint_status;
+use crate::{FasterError, FasterKv};
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
+
+// Upper bounds of the latency buckets in nanoseconds, from 1µs to 10s
+const LATENCY_BUCKETS: [u64; 15] = [
+    1_000,
+    5_000,
+    10_000,
+    50_000,
+    100_000,
+    500_000,
+    1_000_000,
+    5_000_000,
+    10_000_000,
+    50_000_000,
+    100_000_000,
+    500_000_000,
+    1_000_000_000,
+    5_000_000_000,
+    10_000_000_000,
+];
+
+#[derive(Default)]
+struct OperationMetrics {
+    outcomes: [AtomicU64; 4],
+    // Not cumulative; the last slot counts latencies above the largest bound
+    buckets: [AtomicU64; 16],
+    sum_nanos: AtomicU64,
+}
+
+/// Counters and latency histograms of a store's operations.
+#[derive(Default)]
+pub(crate) struct Metrics {
+    operations: [OperationMetrics; OPERATIONS.len()],
+    // Checkpoints started and not durable yet, by token
+    checkpoints: Mutex<HashMap<String, (Operation, Instant)>>,
+}
+
+impl Metrics {
+    pub(crate) fn record(&self, operation: Operation, nanos: u64, outcome: Outcome) {
+        let metrics = &self.operations[operation as usize];
+        metrics.outcomes[outcome as usize].fetch_add(1, Ordering::Relaxed);
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| nanos <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        metrics.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        metrics.sum_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// Records the checkpoint with `token` once `checkpoint_status` reports it durable or
+    /// failed.
+    pub(crate) fn checkpoint_started(&self, operation: Operation, token: &str, started: Instant) {
+        self.pending_checkpoints()
+            .insert(token.to_owned(), (operation, started));
+    }
+
+    pub(crate) fn checkpoint_status(&self, token: &str, status: &Result<Status, FasterError>) {
+        if let Ok(Status::Pending) = status {
+            return;
+        }
+        if let Some((operation, started)) = self.pending_checkpoints().remove(token) {
+            let outcome = match status {
+                Ok(Status::Ok) => Outcome::Ok,
+                _ => Outcome::Error,
+            };
+            self.record(operation, started.elapsed().as_nanos() as u64, outcome);
+        }
+    }
+
+    fn pending_checkpoints(&self) -> MutexGuard<'_, HashMap<String, (Operation, Instant)>> {
+        self.checkpoints
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn render_operations(&self, out: &mut String) {
+        out.push_str(
+            "# HELP faster_operations_total Operations issued against the store by outcome.\n",
+        );
+        out.push_str("# TYPE faster_operations_total counter\n");
+        for operation in OPERATIONS.iter() {
+            let metrics = &self.operations[*operation as usize];
+            for outcome in OUTCOMES.iter() {
+                let count = metrics.outcomes[*outcome as usize].load(Ordering::Relaxed);
+                let _ = writeln!(
+                    out,
+                    "faster_operations_total{{operation=\"{}\",outcome=\"{}\"}} {}",
+                    operation.name(),
+                    outcome.label(),
+                    count
+                );
+            }
+        }
+    }
+
+    fn render_latencies(&self, out: &mut String) {
+        out.push_str("# HELP faster_operation_duration_seconds Latency of store operations.\n");
+        out.push_str("# TYPE faster_operation_duration_seconds histogram\n");
+        for operation in OPERATIONS.iter() {
+            let name = operation.name();
+            let metrics = &self.operations[*operation as usize];
+            let mut cumulative = 0;
+            for (i, bound) in LATENCY_BUCKETS.iter().enumerate() {
+                cumulative += metrics.buckets[i].load(Ordering::Relaxed);
+                let _ = writeln!(
+                    out,
+                    "faster_operation_duration_seconds_bucket{{operation=\"{}\",le=\"{}\"}} {}",
+                    name,
+                    *bound as f64 / 1e9,
+                    cumulative
+                );
+            }
+            cumulative += metrics.buckets[LATENCY_BUCKETS.len()].load(Ordering::Relaxed);
+            let _ = writeln!(
+                out,
+                "faster_operation_duration_seconds_bucket{{operation=\"{}\",le=\"+Inf\"}} {}",
+                name, cumulative
+            );
+            let _ = writeln!(
+                out,
+                "faster_operation_duration_seconds_sum{{operation=\"{}\"}} {}",
+                name,
+                metrics.sum_nanos.load(Ordering::Relaxed) as f64 / 1e9
+            );
+            let _ = writeln!(
+                out,
+                "faster_operation_duration_seconds_count{{operation=\"{}\"}} {}",
+                name, cumulative
+            );
+        }
+    }
+}
+
+impl<C> FasterKv<C> {
+    /// Renders the operation counters, latency histograms and log gauges of the store in the
+    /// Prometheus text exposition format, to be served from a scrape endpoint.
+    ///
+    /// Checkpoints are recorded once they are durable, with the time from starting them until
+    /// [checkpoint_status](struct.FasterKv.html#method.checkpoint_status) first reported them
+    /// durable. Checkpoints still pending are checked when rendering.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1u64, &1u64, 1).unwrap();
+    /// let rendered = store.render_metrics();
+    /// assert!(rendered.contains("faster_operations_total{operation=\"upsert\",outcome=\"ok\"} 1"));
+    /// ```
+    pub fn render_metrics(&self) -> String {
+        let pending: Vec<String> = self.metrics.pending_checkpoints().keys().cloned().collect();
+        for token in pending {
+            let status = checkpoint_status(self.faster_t, &token);
+            self.metrics.checkpoint_status(&token, &status);
+        }
+        let mut out = String::new();
+        self.metrics.render_operations(&mut out);
+        self.metrics.render_latencies(&mut out);
+        out.push_str("# HELP faster_log_address Addresses of the hybrid log regions.\n");
+        out.push_str("# TYPE faster_log_address gauge\n");
+        let regions = [
+            ("begin", self.begin_address()),
+            ("head", self.head_address()),
+            ("read_only", self.read_only_address()),
+            ("tail", self.tail_address()),
+        ];
+        for (region, address) in regions.iter() {
+            let _ = writeln!(
+                out,
+                "faster_log_address{{region=\"{}\"}} {}",
+                region, address
+            );
+        }
+        out.push_str(
+            "# HELP faster_log_size_bytes Bytes between the begin and the tail of the log.\n",
+        );
+        out.push_str("# TYPE faster_log_size_bytes gauge\n");
+        let _ = writeln!(
+            out,
+            "faster_log_size_bytes {}",
+            self.tail_address() - self.begin_address()
+        );
+        out
+    }
+}
@@ -0,0 +1,160 @@
+use crate::codec::Codec;
+use crate::instrument::{callback_failed, receiver_dropped, sender_dropped, Operation};
+use crate::keyspace::{encode_key, ROOT};
+use crate::status::{self, Status};
+use crate::ttl;
+use crate::{FasterError, FasterKv};
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// Result of [read_with](struct.FasterKv.html#method.read_with).
+#[derive(Debug)]
+pub enum ReadWith<R> {
+    /// The read completed immediately and the reader returned this
+    Found(R),
+    NotFound,
+    /// The value is read from disk during `complete_pending`, which sends the reader's result.
+    /// The sender is dropped without a value if the key does not exist.
+    Pending(Receiver<Result<R, FasterError<'static>>>),
+}
+
+struct ReadWithContext<F, R> {
+    reader: Option<F>,
+    // Filled in when FASTER completes the read before faster_read returns
+    result: Option<Result<R, FasterError<'static>>>,
+    // Set once the read went pending; the callback then owns and frees the context
+    sender: Option<Sender<Result<R, FasterError<'static>>>>,
+}
+
+fn run_reader<F, R>(reader: F, value: &[u8]) -> Result<R, FasterError<'static>>
+where
+    F: FnOnce(&[u8]) -> R,
+{
+    // Unwinding into FASTER is undefined behaviour
+    panic::catch_unwind(AssertUnwindSafe(|| reader(value))).map_err(|_| FasterError::CallbackPanic)
+}
+
+unsafe extern "C" fn read_with_callback<F, R, const EXPIRING: bool>(
+    context: *mut libc::c_void,
+    value: *const u8,
+    length: u64,
+    status: u32,
+) where
+    F: FnOnce(&[u8]) -> R,
+{
+    let context = context as *mut ReadWithContext<F, R>;
+    let result = match status::to_result(status as u8) {
+        Ok(Status::Ok) => {
+            let value =
+                ttl::unexpired::<EXPIRING>(std::slice::from_raw_parts(value, length as usize));
+            match (value, (*context).reader.take()) {
+                (Some(value), Some(reader)) => Some(run_reader(reader, value)),
+                // An expired record is reported as missing
+                _ => None,
+            }
+        }
+        Ok(_) => None,
+        Err(err) => Some(Err(err)),
+    };
+    if let Some(Err(err)) = &result {
+        callback_failed("read_with", err);
+    }
+    match (*context).sender.take() {
+        Some(sender) => {
+            match result {
+                Some(result) => {
+                    if sender.send(result).is_err() {
+                        receiver_dropped("read_with");
+                    }
+                }
+                None => sender_dropped("read_with"),
+            }
+            drop(Box::from_raw(context));
+        }
+        None => (*context).result = result,
+    }
+}
+
+impl<C> FasterKv<C> {
+    /// Reads the value stored for `key` by running `reader` on its encoded bytes.
+    ///
+    /// The bytes are borrowed from FASTER's buffers for the duration of the call, so nothing is
+    /// copied or decoded unless `reader` does so. Reads served from memory return the reader's
+    /// result directly; reads that go to disk run `reader` during `complete_pending`.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{FasterKv, ReadWith};
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1u64, &String::from("Hello"), 1).unwrap();
+    /// match store.read_with(&1u64, 1, |bytes| bytes.len()).unwrap() {
+    ///     // bincode prefixes the string with its length as a u64
+    ///     ReadWith::Found(length) => assert_eq!(length, 8 + 5),
+    ///     _ => panic!("Value should be in memory"),
+    /// }
+    /// ```
+    pub fn read_with<K, F, R>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+        reader: F,
+    ) -> Result<ReadWith<R>, FasterError<'static>>
+    where
+        C: Codec<K>,
+        F: FnOnce(&[u8]) -> R + 'static,
+        R: 'static,
+    {
+        self.read_with_in(ROOT, key, monotonic_serial_number, reader)
+    }
+
+    pub(crate) fn read_with_in<K, F, R>(
+        &self,
+        namespace: &[u8],
+        key: &K,
+        monotonic_serial_number: u64,
+        reader: F,
+    ) -> Result<ReadWith<R>, FasterError<'static>>
+    where
+        C: Codec<K>,
+        F: FnOnce(&[u8]) -> R + 'static,
+        R: 'static,
+    {
+        self.observed(Operation::ReadWith, || {
+            let encoded_key = encode_key::<C, K>(namespace, key)?;
+            let context = Box::into_raw(Box::new(ReadWithContext {
+                reader: Some(reader),
+                result: None,
+                sender: None,
+            }));
+            let status = unsafe {
+                ffi::faster_read(
+                    self.faster_t,
+                    encoded_key.as_ptr(),
+                    encoded_key.length(),
+                    monotonic_serial_number,
+                    Some(match self.expiring() {
+                        true => read_with_callback::<F, R, true>,
+                        false => read_with_callback::<F, R, false>,
+                    }),
+                    context as *mut libc::c_void,
+                )
+            };
+            match status::to_result(status) {
+                Ok(Status::Pending) => {
+                    let (sender, receiver) = channel();
+                    unsafe { (*context).sender = Some(sender) };
+                    Ok(ReadWith::Pending(receiver))
+                }
+                result => {
+                    let context = unsafe { Box::from_raw(context) };
+                    match (result?, context.result) {
+                        (Status::Ok, Some(result)) => result.map(ReadWith::Found),
+                        _ => Ok(ReadWith::NotFound),
+                    }
+                }
+            }
+        })
+    }
+}
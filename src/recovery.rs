@@ -0,0 +1,140 @@
+use crate::session::{Session, DEFAULT_REFRESH_INTERVAL};
+use crate::util::token_from_c;
+use crate::{FasterError, FasterKv, Recover};
+
+use std::fmt;
+use std::str::FromStr;
+
+// FASTER hands out session GUIDs as hyphenated strings, e.g. 936a1db2-5c4f-4f4b-a6ef-8e1a6b2f1e6d
+const SESSION_ID_LENGTH: usize = 36;
+const HYPHEN_POSITIONS: [usize; 4] = [8, 13, 18, 23];
+
+/// The GUID identifying a FASTER session.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SessionId(String);
+
+impl SessionId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for SessionId {
+    type Err = FasterError<'static>;
+
+    /// Parses a hyphenated GUID, which is normalised to lower case.
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        let valid = id.len() == SESSION_ID_LENGTH
+            && id
+                .char_indices()
+                .all(|(i, c)| match HYPHEN_POSITIONS.contains(&i) {
+                    true => c == '-',
+                    false => c.is_ascii_hexdigit(),
+                });
+        match valid {
+            true => Ok(SessionId(id.to_ascii_lowercase())),
+            false => Err(FasterError::InvalidSessionId(id.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for SessionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for SessionId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A session found by recovery, with the serial number of its last persisted operation.
+///
+/// Operations of the session with a serial number above `serial_number` were lost and have to
+/// be issued again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveredSession {
+    pub id: SessionId,
+    pub serial_number: u64,
+}
+
+/// Parses the session ids of a recovery result.
+pub(crate) unsafe fn session_ids_from_result(
+    session_ids: *const *mut libc::c_char,
+    count: usize,
+) -> Result<Vec<SessionId>, FasterError<'static>> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    std::slice::from_raw_parts(session_ids, count)
+        .iter()
+        .map(|id| token_from_c(*id)?.parse())
+        .collect()
+}
+
+impl<C> FasterKv<C> {
+    /// Continues a recovered session on the current thread.
+    ///
+    /// Returns a session guard issuing operations under the recovered id, together with the
+    /// serial number from which the session's operations have to be issued again.
+    pub fn resume_session(
+        &self,
+        id: &SessionId,
+    ) -> Result<(Session<'_, C>, RecoveredSession), FasterError<'static>> {
+        let serial_number = self.continue_session(id.to_string())?;
+        let recovered = RecoveredSession {
+            id: id.clone(),
+            serial_number,
+        };
+        let session = Session::new(self, id.clone(), DEFAULT_REFRESH_INTERVAL);
+        Ok((session, recovered))
+    }
+
+    /// Resumes every session found by `recover` in turn on the current thread and lets `replay`
+    /// issue the operations that were not persisted.
+    ///
+    /// `replay` is called with the resumed session and the serial number it was persisted up
+    /// to, and should issue the operations of that session with higher serial numbers from its
+    /// own source. Each session is completed and stopped before the next one is resumed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use faster_rs::FasterKvBuilder;
+    /// let (store, recovered) = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+    ///     .with_disk("storage")
+    ///     .recover_latest()
+    ///     .unwrap();
+    ///
+    /// // Operations as they were issued before the crash, e.g. read back from a journal
+    /// let journal: Vec<(u64, u64, u64)> = vec![(1, 10, 1), (2, 20, 2)];
+    /// store
+    ///     .replay_sessions(&recovered, |session, recovered| {
+    ///         for (key, value, serial_number) in &journal {
+    ///             if *serial_number > recovered.serial_number {
+    ///                 session.upsert(key, value, *serial_number)?;
+    ///             }
+    ///         }
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn replay_sessions<F>(
+        &self,
+        recover: &Recover,
+        mut replay: F,
+    ) -> Result<Vec<RecoveredSession>, FasterError<'static>>
+    where
+        F: FnMut(&Session<C>, &RecoveredSession) -> Result<(), FasterError<'static>>,
+    {
+        let mut sessions = Vec::with_capacity(recover.session_ids.len());
+        for id in &recover.session_ids {
+            let (session, recovered) = self.resume_session(id)?;
+            replay(&session, &recovered)?;
+            session.complete_pending(true)?;
+            sessions.push(recovered);
+        }
+        Ok(sessions)
+    }
+}
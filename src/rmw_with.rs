@@ -0,0 +1,397 @@
+use crate::codec::Codec;
+use crate::faster_traits::{RmwAction, RMW_DELETE, RMW_KEEP, RMW_UPDATE};
+use crate::instrument::{callback_failed, Operation};
+use crate::keyspace::{encode_key, EncodedKey, ROOT};
+use crate::status::{self, Status};
+use crate::ttl::{ExpiringLogic, PinnedClock};
+use crate::{FasterError, FasterKv};
+
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+
+struct RmwWithContext<C, V, I, U> {
+    initial: Option<I>,
+    // Kept in case FASTER asks for the initial value more than once
+    initial_decision: Option<RmwAction<Vec<u8>>>,
+    update: U,
+    error: Option<FasterError<'static>>,
+    codec: PhantomData<fn() -> (C, V)>,
+}
+
+impl<C, V, I, U> RmwLogic for RmwWithContext<C, V, I, U>
+where
+    C: Codec<V>,
+    I: FnOnce() -> V,
+    U: Fn(&V) -> V,
+{
+    fn decide(&mut self, current: Option<&[u8]>) -> RmwAction<Vec<u8>> {
+        match current {
+            None => {
+                if let Some(initial) = self.initial.take() {
+                    // Unwinding into FASTER is undefined behaviour
+                    let result = panic::catch_unwind(AssertUnwindSafe(initial))
+                        .map_err(|_| FasterError::CallbackPanic)
+                        .and_then(|value| C::encode(&value));
+                    self.initial_decision = Some(match result {
+                        Ok(encoded) => RmwAction::Update(encoded),
+                        Err(err) => {
+                            callback_failed("rmw_with", &err);
+                            self.error = Some(err);
+                            // Leave the key absent
+                            RmwAction::Keep
+                        }
+                    });
+                }
+                self.initial_decision.clone().unwrap_or(RmwAction::Keep)
+            }
+            Some(current) => {
+                let update = &self.update;
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    C::decode(current).and_then(|value| C::encode(&update(&value)))
+                }))
+                .unwrap_or(Err(FasterError::CallbackPanic));
+                match result {
+                    Ok(encoded) => RmwAction::Update(encoded),
+                    Err(err) => {
+                        callback_failed("rmw_with", &err);
+                        self.error = Some(err);
+                        // Leave the record untouched
+                        RmwAction::Keep
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Logic of a Read-Modify-Write that lives in a context borrowed for the duration of the call,
+/// see `rmw_in_context`.
+pub(crate) trait RmwLogic {
+    /// Decides what to do with the encoded `current` value, which is `None` for a missing key.
+    /// Runs inside FASTER, so it must not unwind.
+    fn decide(&mut self, current: Option<&[u8]>) -> RmwAction<Vec<u8>>;
+}
+
+impl<L: RmwLogic> RmwLogic for &mut L {
+    fn decide(&mut self, current: Option<&[u8]>) -> RmwAction<Vec<u8>> {
+        (**self).decide(current)
+    }
+}
+
+/// Logic of a Read-Modify-Write that owns everything it needs, so that the operation may
+/// complete after the call that issued it has returned, see `rmw_detached`.
+pub(crate) trait DetachedLogic: RmwLogic + Sized {
+    /// Called once with the status of the operation, from `complete_pending` if it went
+    /// pending.
+    fn completed(self, status: u8);
+}
+
+// The context handed to FASTER with an operation
+struct RmwCall<L> {
+    logic: L,
+    // Decision made for a size query, written by the copy that follows it
+    staged: Option<RmwAction<Vec<u8>>>,
+    status: Option<u8>,
+}
+
+unsafe extern "C" fn decide_callback<L: RmwLogic>(
+    context: *mut libc::c_void,
+    current: *const u8,
+    length_current: u64,
+    dst: *mut u8,
+    action: *mut u8,
+) -> u64 {
+    let call = &mut *(context as *mut RmwCall<L>);
+    // A missing key is passed as a null pointer
+    let current = match current.is_null() {
+        true => None,
+        false => Some(std::slice::from_raw_parts(current, length_current as usize)),
+    };
+    let decision = match call.staged.take() {
+        Some(staged) if !dst.is_null() => staged,
+        _ => call.logic.decide(current),
+    };
+    let (code, size) = match &decision {
+        RmwAction::Update(encoded) => {
+            if !dst.is_null() {
+                encoded.as_ptr().copy_to(dst, encoded.len());
+            }
+            (RMW_UPDATE, encoded.len())
+        }
+        RmwAction::Keep => (RMW_KEEP, 0),
+        RmwAction::Delete => (RMW_DELETE, 0),
+    };
+    if dst.is_null() {
+        call.staged = Some(decision);
+    }
+    *action = code;
+    size as u64
+}
+
+unsafe extern "C" fn complete_callback<L: RmwLogic>(context: *mut libc::c_void, status: u8) {
+    (*(context as *mut RmwCall<L>)).status = Some(status);
+}
+
+unsafe extern "C" fn detached_complete_callback<L: DetachedLogic>(
+    context: *mut libc::c_void,
+    status: u8,
+) {
+    let call = Box::from_raw(context as *mut RmwCall<L>);
+    call.logic.completed(status);
+}
+
+impl<C> FasterKv<C> {
+    /// Runs a Read-Modify-Write of the encoded key with `logic`. If `existing_only`, a missing
+    /// key is left absent without consulting `logic`. On a store with a TTL, `logic` does not
+    /// see the expiry of values and an expired record counts as a missing key.
+    ///
+    /// `logic` lives on the caller's stack, so an operation that goes pending is completed
+    /// before returning. Only this operation is waited for, the other pending operations of the
+    /// session complete as far as they are ready.
+    pub(crate) fn rmw_in_context<L: RmwLogic>(
+        &self,
+        encoded_key: &EncodedKey,
+        logic: &mut L,
+        existing_only: bool,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        match self.ttl {
+            Some(policy) => {
+                let mut expiring = ExpiringLogic::new(logic, policy);
+                let status = self.rmw_stored(
+                    encoded_key,
+                    &mut expiring,
+                    existing_only,
+                    monotonic_serial_number,
+                )?;
+                Ok(expiring.status(status))
+            }
+            None => self.rmw_stored(encoded_key, logic, existing_only, monotonic_serial_number),
+        }
+    }
+
+    /// Like `rmw_in_context`, for logic that sees values as they are stored, with their expiry
+    /// on a store with a TTL. Keeps the secondary indexes of the store up to date.
+    pub(crate) fn rmw_stored<L: RmwLogic>(
+        &self,
+        encoded_key: &EncodedKey,
+        logic: &mut L,
+        existing_only: bool,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        if !self.maintains_indexes(encoded_key) {
+            return self.issue_in_context(
+                encoded_key,
+                logic,
+                existing_only,
+                monotonic_serial_number,
+            );
+        }
+        let mut indexed = self.indexed(encoded_key, logic, monotonic_serial_number);
+        let status = self.issue_in_context(
+            encoded_key,
+            &mut indexed,
+            existing_only,
+            monotonic_serial_number,
+        )?;
+        indexed.finish();
+        Ok(status)
+    }
+
+    fn issue_in_context<L: RmwLogic>(
+        &self,
+        encoded_key: &EncodedKey,
+        logic: &mut L,
+        existing_only: bool,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        let mut call = RmwCall {
+            logic,
+            staged: None,
+            status: None,
+        };
+        let call: *mut RmwCall<&mut L> = &mut call;
+        let issue = match existing_only {
+            true => ffi::faster_rmw_existing,
+            false => ffi::faster_rmw_with,
+        };
+        unsafe {
+            {
+                let _clock = PinnedClock::pin();
+                issue(
+                    self.faster_t,
+                    encoded_key.as_ptr(),
+                    encoded_key.length(),
+                    monotonic_serial_number,
+                    Some(decide_callback::<&mut L>),
+                    Some(complete_callback::<&mut L>),
+                    call as *mut libc::c_void,
+                );
+            }
+            // Errors of other operations' merges stay parked for the next complete_pending
+            while (*call).status.is_none() {
+                ffi::faster_complete_pending(self.faster_t, false);
+                thread::yield_now();
+            }
+            status::to_result((*call).status.unwrap())
+        }
+    }
+
+    /// Runs a Read-Modify-Write of each of the packed encoded keys with the logic at the same
+    /// position with a single call into FASTER, which sees values as they are stored. Returns
+    /// once every item has completed, handing back each logic with the status of its item.
+    pub(crate) fn rmw_batch_in_context<L: RmwLogic>(
+        &self,
+        keys: &[u8],
+        key_lengths: &[u64],
+        logics: Vec<L>,
+        monotonic_serial_number: u64,
+    ) -> Vec<(L, u8)> {
+        let mut calls: Vec<RmwCall<L>> = logics
+            .into_iter()
+            .map(|logic| RmwCall {
+                logic,
+                staged: None,
+                status: None,
+            })
+            .collect();
+        let contexts: Vec<*mut libc::c_void> = calls
+            .iter_mut()
+            .map(|call| call as *mut RmwCall<L> as *mut libc::c_void)
+            .collect();
+        let mut statuses = vec![0u8; calls.len()];
+        unsafe {
+            let _clock = PinnedClock::pin();
+            ffi::faster_rmw_with_batch(
+                self.faster_t,
+                calls.len() as u64,
+                keys.as_ptr(),
+                key_lengths.as_ptr(),
+                monotonic_serial_number,
+                Some(decide_callback::<L>),
+                Some(complete_callback::<L>),
+                contexts.as_ptr(),
+                statuses.as_mut_ptr(),
+            );
+        }
+        calls
+            .into_iter()
+            .map(|call| call.logic)
+            .zip(statuses)
+            .collect()
+    }
+
+    /// Runs a Read-Modify-Write of the encoded key with `logic`, which sees values as they are
+    /// stored. If `existing_only`, a missing key is left absent without consulting `logic`.
+    ///
+    /// Unlike `rmw_in_context`, this returns `Pending` for an operation that goes pending,
+    /// which `complete_pending` completes like any other.
+    pub(crate) fn rmw_detached<L: DetachedLogic>(
+        &self,
+        encoded_key: &EncodedKey,
+        logic: L,
+        existing_only: bool,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        let call = Box::into_raw(Box::new(RmwCall {
+            logic,
+            staged: None,
+            status: None,
+        }));
+        let issue = match existing_only {
+            true => ffi::faster_rmw_existing,
+            false => ffi::faster_rmw_with,
+        };
+        // The call is freed by detached_complete_callback, which FASTER invokes exactly once
+        let status = unsafe {
+            let _clock = PinnedClock::pin();
+            issue(
+                self.faster_t,
+                encoded_key.as_ptr(),
+                encoded_key.length(),
+                monotonic_serial_number,
+                Some(decide_callback::<L>),
+                Some(detached_complete_callback::<L>),
+                call as *mut libc::c_void,
+            )
+        };
+        status::to_result(status)
+    }
+
+    /// Updates the value stored for `key` with a closure instead of a
+    /// [FasterRmw](trait.FasterRmw.html) implementation.
+    ///
+    /// `update` receives the current value and returns the new one. If the key is missing,
+    /// `initial` provides the value to store instead. Both run inside FASTER, which may call
+    /// `update` more than once if a concurrent write interferes, so it should not have side
+    /// effects. A pending operation is completed before returning, which allows the closures to
+    /// borrow from the caller. Only this operation is waited for, not the other pending
+    /// operations of the session.
+    ///
+    /// If decoding the current value or `update` fails, the record is left unchanged and the
+    /// error is returned. If `initial` panics or its value fails to encode, the key stays
+    /// absent and the error is returned.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    /// let key = 1u64;
+    ///
+    /// // Keep the largest value seen so far
+    /// for sample in &[3u64, 7, 5] {
+    ///     store
+    ///         .rmw_with(&key, || *sample, |current| (*current).max(*sample), 1)
+    ///         .unwrap();
+    /// }
+    /// let (_, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+    /// assert_eq!(recv.recv().unwrap().unwrap(), 7);
+    /// ```
+    pub fn rmw_with<K, V, I, U>(
+        &self,
+        key: &K,
+        initial: I,
+        update: U,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+        I: FnOnce() -> V,
+        U: Fn(&V) -> V,
+    {
+        self.rmw_with_in(ROOT, key, initial, update, monotonic_serial_number)
+    }
+
+    pub(crate) fn rmw_with_in<K, V, I, U>(
+        &self,
+        namespace: &[u8],
+        key: &K,
+        initial: I,
+        update: U,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+        I: FnOnce() -> V,
+        U: Fn(&V) -> V,
+    {
+        self.observed(Operation::RmwWith, || {
+            self.count_operation();
+            let encoded_key = encode_key::<C, K>(namespace, key)?;
+            let mut context = RmwWithContext::<C, V, I, U> {
+                initial: Some(initial),
+                initial_decision: None,
+                update,
+                error: None,
+                codec: PhantomData,
+            };
+            let status =
+                self.rmw_in_context(&encoded_key, &mut context, false, monotonic_serial_number)?;
+            match context.error.take() {
+                Some(err) => Err(err),
+                None => Ok(status),
+            }
+        })
+    }
+}
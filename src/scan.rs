@@ -0,0 +1,275 @@
+use crate::codec::{Bincode, Codec};
+use crate::keyspace;
+use crate::ttl;
+use crate::{FasterError, FasterKey, FasterKv, FasterValue};
+
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::time::SystemTime;
+
+/// A record found in the hybrid log, with its key and value still encoded.
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub address: u64,
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+    pub tombstone: bool,
+    /// When the record expires, for stores built with a TTL. `None` if it never expires.
+    pub expires_at: Option<SystemTime>,
+}
+
+impl LogRecord {
+    /// Decodes the key of a record written by a store using the default codec.
+    pub fn decode_key<K: FasterKey>(&self) -> Result<K, FasterError<'static>> {
+        self.decode_key_with::<Bincode, K>()
+    }
+
+    /// Decodes the value, which is `None` for deleted records.
+    pub fn decode_value<V: FasterValue>(&self) -> Result<Option<V>, FasterError<'static>> {
+        self.decode_value_with::<Bincode, V>()
+    }
+
+    /// Decodes the key of a record written by a store using the codec `C`.
+    pub fn decode_key_with<C: Codec<K>, K>(&self) -> Result<K, FasterError<'static>> {
+        C::decode(&self.key)
+    }
+
+    pub fn decode_value_with<C: Codec<V>, V>(&self) -> Result<Option<V>, FasterError<'static>> {
+        if self.tombstone {
+            return Ok(None);
+        }
+        C::decode(&self.value).map(Some)
+    }
+}
+
+/// Iterator over the records of the hybrid log between two addresses, in address order.
+///
+/// By default every record is returned, including deletes and older versions of a key.
+pub struct LogScan<'a> {
+    iterator: *mut ffi::faster_iterator,
+    // The prefix of the internal keys returned, see keyspace.rs, which is stripped from them.
+    // `None` for the keys of callers outside of tables.
+    namespace: Option<Vec<u8>>,
+    // Values carry an expiry, which is split off into the record
+    expiring: bool,
+    skip_tombstones: bool,
+    latest_only: bool,
+    // Records collected up front when only the latest version of each key is wanted
+    latest: Option<VecDeque<LogRecord>>,
+    // The iterator must not outlive the store it reads from
+    store: PhantomData<&'a FasterKv>,
+}
+
+impl<'a> LogScan<'a> {
+    /// Leaves out records that mark a key as deleted.
+    pub fn skip_tombstones(mut self) -> Self {
+        self.skip_tombstones = true;
+        self
+    }
+
+    /// Only returns the newest record of each key within the scanned range.
+    ///
+    /// The whole range is read before the first record is returned. A key deleted within the
+    /// range is left out entirely when combined with `skip_tombstones`.
+    pub fn latest_versions(mut self) -> Self {
+        self.latest_only = true;
+        self
+    }
+
+    fn next_raw(&mut self) -> Option<LogRecord> {
+        unsafe {
+            let mut record: ffi::faster_record = std::mem::zeroed();
+            // The record points into FASTER's buffers, which are only valid until the next call
+            let key = loop {
+                if !ffi::faster_iterator_get_next(self.iterator, &mut record) {
+                    return None;
+                }
+                let (length, internal) = keyspace::split_length(record.key_length);
+                let key = std::slice::from_raw_parts(record.key, length);
+                match &self.namespace {
+                    None if !internal => break key,
+                    Some(prefix) if internal && key.starts_with(prefix) => {
+                        break &key[prefix.len()..]
+                    }
+                    _ => continue,
+                }
+            };
+            let mut value = match record.tombstone {
+                true => Vec::new(),
+                false => {
+                    std::slice::from_raw_parts(record.value, record.value_length as usize).to_vec()
+                }
+            };
+            let expires_at = match self.expiring && !record.tombstone {
+                true => ttl::strip_expiry(&mut value),
+                false => None,
+            };
+            Some(LogRecord {
+                address: record.address,
+                key: key.to_vec(),
+                value,
+                tombstone: record.tombstone,
+                expires_at,
+            })
+        }
+    }
+
+    fn collect_latest(&mut self) -> VecDeque<LogRecord> {
+        let mut latest: HashMap<Vec<u8>, LogRecord> = HashMap::new();
+        while let Some(record) = self.next_raw() {
+            latest.insert(record.key.clone(), record);
+        }
+        let mut records: Vec<LogRecord> = latest.into_values().collect();
+        records.sort_by_key(|record| record.address);
+        records.into_iter().collect()
+    }
+}
+
+impl<'a> Iterator for LogScan<'a> {
+    type Item = LogRecord;
+
+    fn next(&mut self) -> Option<LogRecord> {
+        if self.latest_only && self.latest.is_none() {
+            self.latest = Some(self.collect_latest());
+        }
+        loop {
+            let record = match &mut self.latest {
+                Some(latest) => latest.pop_front(),
+                None => self.next_raw(),
+            }?;
+            if !(self.skip_tombstones && record.tombstone) {
+                return Some(record);
+            }
+        }
+    }
+}
+
+impl<'a> Drop for LogScan<'a> {
+    fn drop(&mut self) {
+        unsafe { ffi::faster_iterator_destroy(self.iterator) }
+    }
+}
+
+// The types an Iter decodes to, which it does not own
+type Decodes<K, V, C> = fn() -> (K, V, C);
+
+/// Iterator over the live key-value pairs of a store, see [iter](../struct.FasterKv.html#method.iter).
+pub struct Iter<'a, K, V, C = Bincode> {
+    scan: LogScan<'a>,
+    types: PhantomData<Decodes<K, V, C>>,
+}
+
+impl<'a, K, V, C> Iter<'a, K, V, C> {
+    /// Iterates over the live records of `scan`.
+    pub(crate) fn new(scan: LogScan<'a>) -> Self {
+        Iter {
+            scan: scan.skip_tombstones().latest_versions(),
+            types: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V, C> Iterator for Iter<'a, K, V, C>
+where
+    C: Codec<K> + Codec<V>,
+{
+    type Item = Result<(K, V), FasterError<'static>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let now = SystemTime::now();
+        let record = loop {
+            let record = self.scan.next()?;
+            match record.expires_at {
+                Some(expires_at) if expires_at <= now => continue,
+                _ => break record,
+            }
+        };
+        // Tombstones are skipped, so every record carries a value
+        let decoded = record
+            .decode_key_with::<C, K>()
+            .and_then(|key| Ok((key, <C as Codec<V>>::decode(&record.value)?)));
+        Some(decoded)
+    }
+}
+
+impl<C> FasterKv<C> {
+    /// Scans the records of the hybrid log with addresses in `[begin, end)`.
+    ///
+    /// Records that have been flushed to disk are read back from storage. `end` is capped at
+    /// the tail of the log, so `scan(0, u64::MAX)` visits the whole log. Like every other
+    /// operation this must be called from a thread with an active session. The records of
+    /// tables and those faster-rs keeps for itself, such as the entries of secondary indexes,
+    /// are left out.
+    pub fn scan(&self, begin: u64, end: u64) -> Result<LogScan<'_>, FasterError<'static>> {
+        self.scan_keys(begin, end, None, self.expiring())
+    }
+
+    /// Like `scan`, for the internal keys starting with `namespace`, the id of a table. Keys are
+    /// returned without it.
+    pub(crate) fn scan_namespace(
+        &self,
+        namespace: Vec<u8>,
+        begin: u64,
+        end: u64,
+    ) -> Result<LogScan<'_>, FasterError<'static>> {
+        self.scan_keys(begin, end, Some(namespace), self.expiring())
+    }
+
+    /// Like `scan_namespace`, for records faster-rs keeps for itself. Their values are
+    /// returned as they are stored.
+    pub(crate) fn scan_internal(
+        &self,
+        prefix: Vec<u8>,
+        begin: u64,
+        end: u64,
+    ) -> Result<LogScan<'_>, FasterError<'static>> {
+        self.scan_keys(begin, end, Some(prefix), false)
+    }
+
+    fn scan_keys(
+        &self,
+        begin: u64,
+        end: u64,
+        namespace: Option<Vec<u8>>,
+        expiring: bool,
+    ) -> Result<LogScan<'_>, FasterError<'static>> {
+        if begin > end {
+            return Err(FasterError::InvalidAddress(begin));
+        }
+        let iterator = unsafe { ffi::faster_scan(self.faster_t, begin, end) };
+        if iterator.is_null() {
+            return Err(FasterError::ScanError);
+        }
+        Ok(LogScan {
+            iterator,
+            namespace,
+            expiring,
+            skip_tombstones: false,
+            latest_only: false,
+            latest: None,
+            store: PhantomData,
+        })
+    }
+
+    /// Iterates over the current value of every key in the store, leaving out expired records.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1u64, &10u64, 1).unwrap();
+    /// store.upsert(&2u64, &20u64, 2).unwrap();
+    /// store.upsert(&1u64, &11u64, 3).unwrap();
+    ///
+    /// let mut pairs: Vec<(u64, u64)> = store.iter().unwrap().map(Result::unwrap).collect();
+    /// pairs.sort();
+    /// assert_eq!(pairs, vec![(1, 11), (2, 20)]);
+    /// ```
+    pub fn iter<K, V>(&self) -> Result<Iter<'_, K, V, C>, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        Ok(Iter::new(self.scan(0, u64::MAX)?))
+    }
+}
@@ -0,0 +1,672 @@
+use crate::codec::{Bincode, Codec};
+use crate::faster_traits::{RmwAction, RMW_KEEP, RMW_UPDATE};
+use crate::instrument::callback_failed;
+use crate::keyspace::{self, EncodedKey, INDEX};
+use crate::rmw_with::{DetachedLogic, RmwLogic};
+use crate::status::{self, Status};
+use crate::ttl::{self, PinnedClock, NEVER};
+use crate::{FasterError, FasterKv, TypedFasterKv};
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The records of an index are internal keys, see keyspace.rs, starting with the name of the
+// index. BUILT marks that the index covers the records written before it was first registered.
+// ENTRY, the length of an encoded index key as a little endian u32, the index key and an
+// encoded key form the key of the entry recording whether that key maps to the index key.
+const BUILT: u8 = 0;
+const ENTRY: u8 = 1;
+
+// Entry values start with the expiry of the record they stand for, like the values of a store
+// with a TTL, so that compaction drops them along with it. The sequence number of the write
+// follows, then whether the key maps to the index key.
+const ENTRY_LENGTH: usize = 17;
+
+// Records touched by the build of an index between two calls to complete_pending
+const TOUCHES_PER_COMPLETION: usize = 256;
+
+fn entry_prefix(index: &[u8], index_key: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(index.len() + 5 + index_key.len());
+    bytes.extend_from_slice(index);
+    bytes.push(ENTRY);
+    bytes.extend_from_slice(&(index_key.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(index_key);
+    bytes
+}
+
+fn entry_value(expiry: u64, sequence: u64, live: bool) -> [u8; ENTRY_LENGTH] {
+    let mut bytes = [0; ENTRY_LENGTH];
+    bytes[..8].copy_from_slice(&expiry.to_le_bytes());
+    bytes[8..16].copy_from_slice(&sequence.to_le_bytes());
+    bytes[16] = live as u8;
+    bytes
+}
+
+fn entry_sequence(bytes: &[u8]) -> Option<u64> {
+    bytes
+        .get(8..16)
+        .map(|sequence| u64::from_le_bytes(sequence.try_into().unwrap()))
+}
+
+fn live_entry(bytes: &[u8]) -> bool {
+    bytes.len() == ENTRY_LENGTH && bytes[16] == 1 && !ttl::expired(ttl::split_expiry(bytes).0)
+}
+
+// Stores the entry unless the current one was written for a later write of the key, so that
+// entries end up as the last write left them in whatever order the writes update them
+unsafe extern "C" fn entry_callback(
+    current: *const u8,
+    length_current: u64,
+    modification: *const u8,
+    length_modification: u64,
+    dst: *mut u8,
+    action: *mut u8,
+) -> u64 {
+    let modification = std::slice::from_raw_parts(modification, length_modification as usize);
+    let newer = current.is_null()
+        || entry_sequence(std::slice::from_raw_parts(current, length_current as usize))
+            < entry_sequence(modification);
+    if !newer {
+        *action = RMW_KEEP;
+        return 0;
+    }
+    if !dst.is_null() {
+        modification.as_ptr().copy_to(dst, modification.len());
+    }
+    *action = RMW_UPDATE;
+    modification.len() as u64
+}
+
+// Sets the target to whether the key was found
+unsafe extern "C" fn found_callback(
+    target: *mut libc::c_void,
+    value: *const u8,
+    _length: u64,
+    _status: u32,
+) {
+    *(target as *mut Option<bool>) = Some(!value.is_null());
+}
+
+/// A registered index with its types erased, so a store can hold indexes of different types.
+trait Maintained: Send + Sync {
+    /// The prefix of the internal keys of the index.
+    fn prefix(&self) -> &[u8];
+
+    /// The encoded index key of an encoded value, `None` if the value is not one the index
+    /// covers.
+    fn index_key(&self, value: &[u8]) -> Option<Vec<u8>>;
+}
+
+struct IndexState<V, I, C> {
+    name: String,
+    prefix: Vec<u8>,
+    extractor: Box<dyn Fn(&V) -> I + Send + Sync>,
+    codec: PhantomData<fn() -> C>,
+}
+
+impl<V, I, C> Maintained for IndexState<V, I, C>
+where
+    C: Codec<V> + Codec<I>,
+{
+    fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    fn index_key(&self, value: &[u8]) -> Option<Vec<u8>> {
+        let value = <C as Codec<V>>::decode(value).ok()?;
+        <C as Codec<I>>::encode(&(self.extractor)(&value)).ok()
+    }
+}
+
+type Registered = Arc<Vec<Arc<dyn Maintained>>>;
+
+/// The secondary indexes registered with a store.
+pub(crate) struct Indexes {
+    // Set by the first registration, so that the writes of a store without indexes take the
+    // plain path
+    active: AtomicBool,
+    registered: RwLock<Registered>,
+    // Orders the writes of entries. Starts from the clock, so that it keeps growing across
+    // recoveries.
+    sequence: AtomicU64,
+    expiring: bool,
+}
+
+impl Indexes {
+    pub(crate) fn new(expiring: bool) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or(0);
+        Indexes {
+            active: AtomicBool::new(false),
+            registered: RwLock::new(Arc::new(Vec::new())),
+            sequence: AtomicU64::new(now),
+            expiring,
+        }
+    }
+
+    /// Whether writes have to keep indexes up to date.
+    pub(crate) fn active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    fn registered(&self) -> Registered {
+        let registered = self
+            .registered
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        Arc::clone(&registered)
+    }
+
+    // Returns false if an index of the same name is registered
+    fn publish(&self, index: Arc<dyn Maintained>) -> bool {
+        let mut registered = self
+            .registered
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if registered
+            .iter()
+            .any(|other| other.prefix() == index.prefix())
+        {
+            return false;
+        }
+        let mut indexes = Vec::clone(&registered);
+        indexes.push(index);
+        *registered = Arc::new(indexes);
+        self.active.store(true, Ordering::Release);
+        true
+    }
+
+    fn withdraw(&self, prefix: &[u8]) {
+        let mut registered = self
+            .registered
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let indexes = registered
+            .iter()
+            .filter(|index| index.prefix() != prefix)
+            .cloned()
+            .collect();
+        *registered = Arc::new(indexes);
+    }
+
+    fn next_sequence(&self) -> u64 {
+        self.sequence.fetch_add(1, Ordering::SeqCst)
+    }
+
+    // The expiry and encoded value of a value as it is stored
+    fn split<'b>(&self, bytes: &'b [u8]) -> (u64, &'b [u8]) {
+        match self.expiring {
+            true => ttl::split_expiry(bytes),
+            false => (NEVER, bytes),
+        }
+    }
+}
+
+// Writes the entries of one key, once the write of its record has completed
+struct EntryWriter {
+    faster_t: *mut ffi::faster_t,
+    indexes: Arc<Indexes>,
+    key: Vec<u8>,
+    monotonic_serial_number: u64,
+}
+
+impl EntryWriter {
+    fn write(&self, index: &dyn Maintained, index_key: &[u8], value: &[u8; ENTRY_LENGTH]) {
+        let mut entry = entry_prefix(index.prefix(), index_key);
+        entry.extend_from_slice(&self.key);
+        // Entries that go pending complete with the other operations of the session
+        let status = unsafe {
+            ffi::faster_rmw_conditional(
+                self.faster_t,
+                entry.as_ptr(),
+                keyspace::internal_length(&entry),
+                value.as_ptr(),
+                value.len() as u64,
+                self.monotonic_serial_number,
+                Some(entry_callback),
+            )
+        };
+        if let Err(err) = status::to_result(status) {
+            callback_failed("secondary_index", &err);
+        }
+    }
+}
+
+// The values a write replaced and stored, as they are stored
+struct Change {
+    sequence: u64,
+    old: Option<Vec<u8>>,
+    new: Option<Vec<u8>>,
+}
+
+/// Runs the logic of a write on an indexed store, which sees values as they are stored, and
+/// updates the entries of the key once the write has completed.
+///
+/// The entries a write changes get the sequence number drawn when its logic ran. A later write
+/// of the key runs its logic on the record the earlier one stored, so it draws a higher number,
+/// and each entry keeps the value of the highest number written to it. The entries a write moves
+/// the key out of are kept as tombstones, so that an earlier write updating them late does not
+/// bring them back.
+pub(crate) struct IndexedWrite<L> {
+    logic: L,
+    entries: EntryWriter,
+    // Made by the last decision
+    change: Option<Change>,
+}
+
+impl<L: RmwLogic> RmwLogic for IndexedWrite<L> {
+    fn decide(&mut self, current: Option<&[u8]>) -> RmwAction<Vec<u8>> {
+        let action = self.logic.decide(current);
+        self.change = match &action {
+            RmwAction::Keep => None,
+            action => Some(Change {
+                sequence: self.entries.indexes.next_sequence(),
+                old: current.map(<[u8]>::to_vec),
+                new: match action {
+                    RmwAction::Update(new) => Some(new.clone()),
+                    _ => None,
+                },
+            }),
+        };
+        action
+    }
+}
+
+impl<L> IndexedWrite<L> {
+    /// Updates the entries of the key to match the completed write.
+    ///
+    /// The indexes are those registered now rather than when the logic ran, so a write is
+    /// either left to the build of an index registered in the meantime or updates it.
+    pub(crate) fn finish(self) {
+        let change = match self.change {
+            Some(change) => change,
+            None => return,
+        };
+        let indexes = &self.entries.indexes;
+        let old = change.old.as_deref().map(|bytes| indexes.split(bytes));
+        let new = change.new.as_deref().map(|bytes| indexes.split(bytes));
+        for index in indexes.registered().iter() {
+            let old_key = old.and_then(|(_, value)| index.index_key(value));
+            let new_key = new.and_then(|(_, value)| index.index_key(value));
+            let moved = old_key != new_key;
+            if let (true, Some(old_key)) = (moved, &old_key) {
+                let tombstone = entry_value(NEVER, change.sequence, false);
+                self.entries.write(&**index, old_key, &tombstone);
+            }
+            let expiry = new.map(|(expiry, _)| expiry);
+            if let (Some(new_key), Some(expiry)) = (&new_key, expiry) {
+                // An entry that stays has to follow the expiry of its record
+                if moved || old.map(|(expiry, _)| expiry) != Some(expiry) {
+                    let live = entry_value(expiry, change.sequence, true);
+                    self.entries.write(&**index, new_key, &live);
+                }
+            }
+        }
+    }
+}
+
+impl<L: RmwLogic> DetachedLogic for IndexedWrite<L> {
+    fn completed(self, status: u8) {
+        if status::to_result(status).is_ok() {
+            self.finish();
+        }
+    }
+}
+
+/// The writes of `upsert`, `delete` and `rmw` as the logic of a Read-Modify-Write, so that the
+/// entries of an indexed store can be updated from the value they replace.
+pub(crate) enum Write {
+    Replace(Vec<u8>),
+    // The modification and the function merging it into the stored value
+    Merge(Vec<u8>, fn(&[u8], &[u8]) -> Vec<u8>),
+    Delete,
+}
+
+impl RmwLogic for Write {
+    fn decide(&mut self, current: Option<&[u8]>) -> RmwAction<Vec<u8>> {
+        match (self, current) {
+            (Write::Replace(value), _) => RmwAction::Update(value.clone()),
+            (Write::Merge(modification, _), None) => RmwAction::Update(modification.clone()),
+            (Write::Merge(modification, merge), Some(current)) => {
+                RmwAction::Update(merge(current, modification))
+            }
+            (Write::Delete, Some(_)) => RmwAction::Delete,
+            (Write::Delete, None) => RmwAction::Keep,
+        }
+    }
+}
+
+// Adds the entry of a record written before its index was registered, leaving the record as
+// it is
+struct Backfill {
+    index: Arc<dyn Maintained>,
+    entries: EntryWriter,
+    // The current value, with the sequence number drawn when it was seen
+    found: Option<(u64, Vec<u8>)>,
+}
+
+impl RmwLogic for Backfill {
+    fn decide(&mut self, current: Option<&[u8]>) -> RmwAction<Vec<u8>> {
+        self.found =
+            current.map(|current| (self.entries.indexes.next_sequence(), current.to_vec()));
+        RmwAction::Keep
+    }
+}
+
+impl DetachedLogic for Backfill {
+    fn completed(self, _status: u8) {
+        if let Some((sequence, current)) = &self.found {
+            let (expiry, value) = self.entries.indexes.split(current);
+            if let Some(index_key) = self.index.index_key(value) {
+                let live = entry_value(expiry, *sequence, true);
+                self.entries.write(&*self.index, &index_key, &live);
+            }
+        }
+    }
+}
+
+/// Index of the keys of a store by a value derived from their records, see
+/// [register_index](struct.FasterKv.html#method.register_index).
+///
+/// The handle can be cloned and shared between threads. The index stays registered with the
+/// store when its handles are dropped.
+pub struct SecondaryIndex<'a, K, V, I, C = Bincode> {
+    store: &'a FasterKv<C>,
+    state: Arc<IndexState<V, I, C>>,
+    keys: PhantomData<fn() -> K>,
+}
+
+impl<'a, K, V, I, C> Clone for SecondaryIndex<'a, K, V, I, C> {
+    fn clone(&self) -> Self {
+        SecondaryIndex {
+            store: self.store,
+            state: Arc::clone(&self.state),
+            keys: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V, I, C> SecondaryIndex<'a, K, V, I, C>
+where
+    C: Codec<K> + Codec<V> + Codec<I>,
+{
+    pub fn name(&self) -> &str {
+        &self.state.name
+    }
+
+    /// Keys whose current value maps to `index_key`, in no particular order.
+    ///
+    /// Entries are records of the hybrid log, which is scanned for those of `index_key`, so a
+    /// lookup takes time in proportion to the size of the log. Like every other operation this
+    /// must be called from a thread with an active session.
+    pub fn lookup(&self, index_key: &I) -> Result<Vec<K>, FasterError<'static>> {
+        let index_key = <C as Codec<I>>::encode(index_key)?;
+        let prefix = entry_prefix(&self.state.prefix, &index_key);
+        self.store
+            .live_entries(&prefix)?
+            .iter()
+            .map(|key| <C as Codec<K>>::decode(key))
+            .collect()
+    }
+
+    /// Number of keys in the index. Scans the log like `lookup`.
+    pub fn len(&self) -> Result<usize, FasterError<'static>> {
+        let mut prefix = self.state.prefix.clone();
+        prefix.push(ENTRY);
+        Ok(self.store.live_entries(&prefix)?.len())
+    }
+
+    pub fn is_empty(&self) -> Result<bool, FasterError<'static>> {
+        self.len().map(|len| len == 0)
+    }
+}
+
+impl<C> FasterKv<C> {
+    /// Registers an index of the keys of this store by `extractor(value)`.
+    ///
+    /// [lookup](struct.SecondaryIndex.html#method.lookup) finds the keys whose current value
+    /// maps to a given index key. The index is kept in the store itself, as records of a key
+    /// space of their own that never collide with the keys of the store, and every write of
+    /// a key outside of tables through this store updates it, including those of sessions,
+    /// batches and async operations. Values that do not decode as `V` are not indexed. Since the index is part
+    /// of the hybrid log, checkpoints and compaction cover it along with the records it
+    /// indexes, and entries of expired records are left out.
+    ///
+    /// Writes of an indexed store run as Read-Modify-Writes, so upserts and deletes may go
+    /// pending like them, and update the index once they have completed. Entries of writes
+    /// completed by `complete_pending` may only be captured by the checkpoint after the one
+    /// capturing their records. Writes by a store that has not registered the index, such as
+    /// another process, are not indexed, so the index should be registered every time the store
+    /// is opened, after recovering and before writing. Writes that other threads have in
+    /// flight, including pending ones, when the first index of a store is registered may be
+    /// missed.
+    ///
+    /// Registering an index for the first time adds the records already in the store, which
+    /// other threads may keep writing meanwhile. It completes the pending operations of the
+    /// session. Registering it again, also on a store recovered from a checkpoint taken after
+    /// the first registration, finds the index in the store. `name` identifies the records of
+    /// the index and may only contain ASCII letters, digits, `-` and `_`, up to 255 of them.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    ///
+    /// let store = FasterKv::default();
+    /// let by_length = store
+    ///     .register_index::<u64, String, _, _>("length", |value| value.len(), 1)
+    ///     .unwrap();
+    ///
+    /// store.upsert(&1u64, &String::from("one"), 2).unwrap();
+    /// store.upsert(&2u64, &String::from("two"), 3).unwrap();
+    /// store.upsert(&3u64, &String::from("three"), 4).unwrap();
+    ///
+    /// let mut keys = by_length.lookup(&3).unwrap();
+    /// keys.sort();
+    /// assert_eq!(keys, vec![1, 2]);
+    /// ```
+    pub fn register_index<K, V, I, F>(
+        &self,
+        name: &str,
+        extractor: F,
+        monotonic_serial_number: u64,
+    ) -> Result<SecondaryIndex<'_, K, V, I, C>, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V> + Codec<I>,
+        V: 'static,
+        I: 'static,
+        F: Fn(&V) -> I + Send + Sync + 'static,
+    {
+        if !keyspace::valid_name(name) {
+            return Err(FasterError::InvalidIndexName(name.to_string()));
+        }
+        let state = Arc::new(IndexState {
+            name: name.to_string(),
+            prefix: keyspace::named(INDEX, name),
+            extractor: Box::new(extractor),
+            codec: PhantomData,
+        });
+        let index = Arc::clone(&state) as Arc<dyn Maintained>;
+        if !self.indexes.publish(Arc::clone(&index)) {
+            return Err(FasterError::DuplicateIndex(name.to_string()));
+        }
+        if let Err(err) = self.build_index(index, monotonic_serial_number) {
+            self.indexes.withdraw(&state.prefix);
+            return Err(err);
+        }
+        Ok(SecondaryIndex {
+            store: self,
+            state,
+            keys: PhantomData,
+        })
+    }
+
+    /// Whether writes of `encoded_key` update the entries of indexes. Only the keys of callers
+    /// outside of tables are indexed.
+    pub(crate) fn maintains_indexes(&self, encoded_key: &EncodedKey) -> bool {
+        self.indexes.active() && !encoded_key.internal()
+    }
+
+    /// Wraps the logic of a write of `encoded_key`, which sees values as they are stored, to
+    /// keep the indexes up to date. The entries are written by `finish`.
+    pub(crate) fn indexed<L: RmwLogic>(
+        &self,
+        encoded_key: &EncodedKey,
+        logic: L,
+        monotonic_serial_number: u64,
+    ) -> IndexedWrite<L> {
+        IndexedWrite {
+            logic,
+            entries: self.entry_writer(encoded_key, monotonic_serial_number),
+            change: None,
+        }
+    }
+
+    /// Issues `write` of `encoded_key` on an indexed store. The entries are updated once it has
+    /// completed, by `complete_pending` if it goes pending.
+    pub(crate) fn write_indexed(
+        &self,
+        encoded_key: &EncodedKey,
+        write: Write,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        let indexed = self.indexed(encoded_key, write, monotonic_serial_number);
+        self.rmw_detached(encoded_key, indexed, false, monotonic_serial_number)
+    }
+
+    fn entry_writer(&self, encoded_key: &EncodedKey, monotonic_serial_number: u64) -> EntryWriter {
+        EntryWriter {
+            faster_t: self.faster_t,
+            indexes: Arc::clone(&self.indexes),
+            key: encoded_key.bytes().to_vec(),
+            monotonic_serial_number,
+        }
+    }
+
+    // Adds the records written before the index was published, unless the store already holds
+    // the index. Writes that finished without the index completed before the tail is read, so
+    // their records lie below it and are touched here. Writes finishing later update the
+    // entries of the index themselves.
+    fn build_index(
+        &self,
+        index: Arc<dyn Maintained>,
+        monotonic_serial_number: u64,
+    ) -> Result<(), FasterError<'static>> {
+        let until = self.tail_address();
+        let mut built_key = index.prefix().to_vec();
+        built_key.push(BUILT);
+        let mut built: Option<bool> = None;
+        let status = unsafe {
+            ffi::faster_read(
+                self.faster_t,
+                built_key.as_ptr(),
+                keyspace::internal_length(&built_key),
+                monotonic_serial_number,
+                Some(found_callback),
+                &mut built as *mut Option<bool> as *mut libc::c_void,
+            )
+        };
+        if status::to_result(status)? == Status::Pending {
+            let _clock = PinnedClock::pin();
+            unsafe { ffi::faster_complete_pending(self.faster_t, true) };
+        }
+        if built == Some(true) {
+            return Ok(());
+        }
+
+        let records = self.scan(self.begin_address(), until)?.skip_tombstones();
+        for (touched, record) in records.enumerate() {
+            let key = EncodedKey::in_space(Cow::Owned(record.key), false);
+            let backfill = Backfill {
+                index: Arc::clone(&index),
+                entries: self.entry_writer(&key, monotonic_serial_number),
+                found: None,
+            };
+            self.rmw_detached(&key, backfill, true, monotonic_serial_number)?;
+            if touched % TOUCHES_PER_COMPLETION == TOUCHES_PER_COMPLETION - 1 {
+                unsafe { ffi::faster_complete_pending(self.faster_t, false) };
+            }
+        }
+        // Written once every entry is, so that an index whose build was cut short is built
+        // again. Its value is an expiry, which keeps compaction on a store with a TTL from
+        // dropping it.
+        unsafe {
+            let _clock = PinnedClock::pin();
+            ffi::faster_complete_pending(self.faster_t, true);
+        }
+        let marker = NEVER.to_le_bytes();
+        let status = unsafe {
+            ffi::faster_upsert(
+                self.faster_t,
+                built_key.as_ptr(),
+                keyspace::internal_length(&built_key),
+                marker.as_ptr(),
+                marker.len() as u64,
+                monotonic_serial_number,
+            )
+        };
+        status::to_result(status).map(|_| ())
+    }
+
+    // The rest of the keys of the live entries starting with `prefix`. The latest record of an
+    // entry holds its value, see `entry_callback`.
+    fn live_entries(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, FasterError<'static>> {
+        let mut latest: HashMap<Vec<u8>, bool> = HashMap::new();
+        for record in self.scan_internal(prefix.to_vec(), 0, u64::MAX)? {
+            let live = !record.tombstone && live_entry(&record.value);
+            latest.insert(record.key, live);
+        }
+        Ok(latest
+            .into_iter()
+            .filter(|(_, live)| *live)
+            .map(|(key, _)| key)
+            .collect())
+    }
+}
+
+impl<K, V, C> TypedFasterKv<K, V, C>
+where
+    C: Codec<K> + Codec<V>,
+{
+    /// Registers an index of the keys of this store by `extractor(value)`, see
+    /// [FasterKv::register_index](struct.FasterKv.html#method.register_index).
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::TypedFasterKv;
+    ///
+    /// let store: TypedFasterKv<u64, String> = TypedFasterKv::default();
+    /// let by_length = store
+    ///     .register_index("length", |value: &String| value.len(), 1)
+    ///     .unwrap();
+    ///
+    /// store.upsert(&1, &String::from("one"), 2).unwrap();
+    /// store.upsert(&2, &String::from("two"), 3).unwrap();
+    /// store.upsert(&3, &String::from("three"), 4).unwrap();
+    ///
+    /// let mut keys = by_length.lookup(&3).unwrap();
+    /// keys.sort();
+    /// assert_eq!(keys, vec![1, 2]);
+    /// ```
+    pub fn register_index<I, F>(
+        &self,
+        name: &str,
+        extractor: F,
+        monotonic_serial_number: u64,
+    ) -> Result<SecondaryIndex<'_, K, V, I, C>, FasterError<'static>>
+    where
+        C: Codec<I>,
+        V: 'static,
+        I: 'static,
+        F: Fn(&V) -> I + Send + Sync + 'static,
+    {
+        self.store
+            .register_index(name, extractor, monotonic_serial_number)
+    }
+}
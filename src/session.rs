@@ -0,0 +1,302 @@
+use crate::codec::{Bincode, Codec};
+use crate::{FasterConditionalRmw, FasterError, FasterKv, FasterRmw, ReadWith, SessionId, Status};
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+pub(crate) const DEFAULT_REFRESH_INTERVAL: u64 = 1 << 8;
+
+/// A FASTER session registered for the current thread.
+///
+/// Operations issued through the session refresh it every `refresh_interval` operations.
+/// Dropping the session completes all of its pending operations and stops it, so FASTER
+/// never keeps state around for threads that have finished. A session is bound to the
+/// thread that started it and can therefore not be sent to another thread.
+///
+/// # Example
+/// ```
+/// use faster_rs::{FasterKv, Status};
+/// let store = FasterKv::default();
+///
+/// {
+///     let session = store.session().unwrap();
+///     session.upsert(&1, &42, 1).unwrap();
+///     let (res, recv) = session.read::<i32, i32>(&1, 2).unwrap();
+///     assert_eq!(Status::Ok, res);
+///     assert_eq!(42, recv.recv().unwrap().unwrap());
+/// } // Session is stopped here
+/// ```
+///
+/// ```compile_fail
+/// use faster_rs::FasterKv;
+/// fn assert_send<T: Send>(_: T) {}
+///
+/// let store = FasterKv::default();
+/// assert_send(store.session().unwrap());
+/// ```
+pub struct Session<'a, C = Bincode> {
+    store: &'a FasterKv<C>,
+    id: SessionId,
+    refresh_interval: u64,
+    operations: Cell<u64>,
+    // Raw pointer makes the session !Send and !Sync
+    thread_bound: PhantomData<*const ()>,
+}
+
+impl<'a, C> Session<'a, C> {
+    pub(crate) fn new(store: &'a FasterKv<C>, id: SessionId, refresh_interval: u64) -> Self {
+        Session {
+            store,
+            id,
+            refresh_interval,
+            operations: Cell::new(0),
+            thread_bound: PhantomData,
+        }
+    }
+
+    /// The GUID of this session, which is reported again by recovery.
+    pub fn id(&self) -> &SessionId {
+        &self.id
+    }
+
+    pub fn upsert<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.tick();
+        self.store.upsert(key, value, monotonic_serial_number)
+    }
+
+    pub fn upsert_with_ttl<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        ttl: Duration,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.tick();
+        self.store
+            .upsert_with_ttl(key, value, ttl, monotonic_serial_number)
+    }
+
+    pub fn read<K, V>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<(Status, Receiver<Result<V, FasterError<'static>>>), FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.tick();
+        self.store.read(key, monotonic_serial_number)
+    }
+
+    pub fn read_with<K, F, R>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+        reader: F,
+    ) -> Result<ReadWith<R>, FasterError<'static>>
+    where
+        C: Codec<K>,
+        F: FnOnce(&[u8]) -> R + 'static,
+        R: 'static,
+    {
+        self.tick();
+        self.store.read_with(key, monotonic_serial_number, reader)
+    }
+
+    pub fn rmw<K, V>(
+        &self,
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        V: FasterRmw,
+        C: Codec<K> + Codec<V>,
+    {
+        self.tick();
+        self.store.rmw(key, modification, monotonic_serial_number)
+    }
+
+    pub fn upsert_batch<'b, K, V, I>(
+        &self,
+        items: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Status>, FasterError<'static>>
+    where
+        K: 'b,
+        V: 'b,
+        C: Codec<K> + Codec<V>,
+        I: IntoIterator<Item = (&'b K, &'b V)>,
+    {
+        let statuses = self.store.upsert_batch(items, monotonic_serial_number)?;
+        self.tick_by(statuses.len() as u64);
+        Ok(statuses)
+    }
+
+    pub fn read_batch<'b, K, V, I>(
+        &self,
+        keys: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Result<Option<V>, FasterError<'static>>>, FasterError<'static>>
+    where
+        K: 'b,
+        C: Codec<K> + Codec<V>,
+        I: IntoIterator<Item = &'b K>,
+    {
+        let values = self.store.read_batch(keys, monotonic_serial_number)?;
+        self.tick_by(values.len() as u64);
+        Ok(values)
+    }
+
+    pub fn rmw_batch<'b, K, V, I>(
+        &self,
+        items: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Status>, FasterError<'static>>
+    where
+        K: 'b,
+        V: FasterRmw + 'b,
+        C: Codec<K> + Codec<V>,
+        I: IntoIterator<Item = (&'b K, &'b V)>,
+    {
+        let statuses = self.store.rmw_batch(items, monotonic_serial_number)?;
+        self.tick_by(statuses.len() as u64);
+        Ok(statuses)
+    }
+
+    pub fn rmw_conditional<K, V>(
+        &self,
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        V: FasterConditionalRmw,
+        C: Codec<K> + Codec<V>,
+    {
+        self.tick();
+        self.store
+            .rmw_conditional(key, modification, monotonic_serial_number)
+    }
+
+    pub fn rmw_with<K, V, I, U>(
+        &self,
+        key: &K,
+        initial: I,
+        update: U,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+        I: FnOnce() -> V,
+        U: Fn(&V) -> V,
+    {
+        self.tick();
+        self.store
+            .rmw_with(key, initial, update, monotonic_serial_number)
+    }
+
+    pub fn compare_and_swap<K, V>(
+        &self,
+        key: &K,
+        expected: &V,
+        new: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<bool, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.tick();
+        self.store
+            .compare_and_swap(key, expected, new, monotonic_serial_number)
+    }
+
+    pub fn upsert_if_absent<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<bool, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.tick();
+        self.store
+            .upsert_if_absent(key, value, monotonic_serial_number)
+    }
+
+    pub fn delete<K>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        C: Codec<K>,
+    {
+        self.tick();
+        self.store.delete(key, monotonic_serial_number)
+    }
+
+    pub fn complete_pending(&self, b: bool) -> Result<(), FasterError<'static>> {
+        self.store.complete_pending(b)
+    }
+
+    pub fn refresh(&self) {
+        self.store.refresh()
+    }
+
+    fn tick(&self) {
+        self.tick_by(1);
+    }
+
+    fn tick_by(&self, count: u64) {
+        let before = self.operations.get();
+        let operations = before + count;
+        self.operations.set(operations);
+        if operations / self.refresh_interval > before / self.refresh_interval {
+            self.store.refresh();
+        }
+    }
+}
+
+// Errors of pending RMWs cannot be reported from drop; call complete_pending beforehand to see them
+impl<'a, C> Drop for Session<'a, C> {
+    fn drop(&mut self) {
+        let _ = self.store.complete_pending(true);
+        self.store.stop_session();
+    }
+}
+
+impl<C> FasterKv<C> {
+    /// Starts a session for the current thread that refreshes every 256 operations.
+    pub fn session(&self) -> Result<Session<'_, C>, FasterError<'static>> {
+        self.session_with_refresh_interval(DEFAULT_REFRESH_INTERVAL)
+    }
+
+    /// Starts a session for the current thread that refreshes every `refresh_interval`
+    /// operations.
+    pub fn session_with_refresh_interval(
+        &self,
+        refresh_interval: u64,
+    ) -> Result<Session<'_, C>, FasterError<'static>> {
+        assert!(refresh_interval > 0, "Refresh interval must be positive");
+        let id = self
+            .start_session()?
+            .parse()
+            .inspect_err(|_| self.stop_session())?;
+        Ok(Session::new(self, id, refresh_interval))
+    }
+}
@@ -0,0 +1,48 @@
+use crate::FasterKv;
+
+/// Snapshot of a store's hash index size, hybrid log and sessions, see
+/// [stats](struct.FasterKv.html#method.stats).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    /// Number of buckets in the main hash table, as the store was opened with or grown to by
+    /// [grow_index](struct.FasterKv.html#method.grow_index)
+    pub table_size: u64,
+    pub begin_address: u64,
+    pub head_address: u64,
+    pub read_only_address: u64,
+    pub tail_address: u64,
+    /// Bytes of the log between the head and the tail, which are held in memory
+    pub in_memory_bytes: u64,
+    /// Bytes of the log that have been flushed to disk
+    pub on_disk_bytes: u64,
+    pub active_sessions: u64,
+}
+
+impl<C> FasterKv<C> {
+    /// Collects statistics about the store, for exporting to monitoring.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::FasterKv;
+    /// let store = FasterKv::default();
+    ///
+    /// store.upsert(&1u64, &1u64, 1).unwrap();
+    /// let stats = store.stats();
+    /// assert_eq!(stats.table_size, 1 << 15);
+    /// assert!(stats.tail_address > stats.begin_address);
+    /// ```
+    pub fn stats(&self) -> Stats {
+        let mut raw: ffi::faster_stats = unsafe { std::mem::zeroed() };
+        unsafe { ffi::faster_stats(self.faster_t, &mut raw) };
+        Stats {
+            table_size: raw.table_size,
+            begin_address: raw.begin_address,
+            head_address: raw.head_address,
+            read_only_address: raw.read_only_address,
+            tail_address: raw.tail_address,
+            in_memory_bytes: raw.in_memory_bytes,
+            on_disk_bytes: raw.on_disk_bytes,
+            active_sessions: raw.active_sessions,
+        }
+    }
+}
@@ -1,5 +1,8 @@
 // Represents Status values from FASTER
 
+use crate::FasterError;
+use std::io;
+
 pub const OK: u8 = 0;
 pub const PENDING: u8 = 1;
 pub const NOT_FOUND: u8 = 2;
@@ -7,3 +10,32 @@ pub const OUT_OF_MEMORY: u8 = 3;
 pub const IO_ERROR: u8 = 4;
 pub const CORRUPTION: u8 = 5;
 pub const ABORTED: u8 = 6;
+
+/// Outcome of an operation that FASTER accepted.
+///
+/// Failure statuses (`OUT_OF_MEMORY`, `IO_ERROR`, `CORRUPTION` and `ABORTED`)
+/// are reported as a [FasterError](../enum.FasterError.html) instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The operation completed immediately
+    Ok,
+    /// The operation went to disk and completes during `complete_pending`
+    Pending,
+    /// The key does not exist in the store
+    NotFound,
+}
+
+pub(crate) fn to_result(status: u8) -> Result<Status, FasterError<'static>> {
+    match status {
+        OK => Ok(Status::Ok),
+        PENDING => Ok(Status::Pending),
+        NOT_FOUND => Ok(Status::NotFound),
+        OUT_OF_MEMORY => Err(FasterError::OutOfMemory),
+        IO_ERROR => Err(FasterError::IOError(io::Error::other(
+            "FASTER reported an I/O error",
+        ))),
+        CORRUPTION => Err(FasterError::Corruption),
+        ABORTED => Err(FasterError::Aborted),
+        _ => Err(FasterError::UnknownStatus(status)),
+    }
+}
@@ -0,0 +1,329 @@
+use crate::codec::{Bincode, Codec};
+use crate::keyspace::{self, MAX_NAME_LENGTH, TABLE};
+use crate::status::Status;
+use crate::ttl;
+use crate::{FasterConditionalRmw, FasterError, FasterKv, FasterRmw, Iter, LogScan, ReadWith};
+#[cfg(feature = "async")]
+use crate::{OperationFuture, ReadFuture};
+
+use std::marker::PhantomData;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, SystemTime};
+
+/// Records and bytes held by the live records of a table, see
+/// [estimate_size](struct.Table.html#method.estimate_size).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableSize {
+    pub records: u64,
+    /// Encoded keys and values, leaving out the headers FASTER adds to every record
+    pub bytes: u64,
+}
+
+/// A namespace of a `FasterKv` whose keys and values are fixed to `K` and `V`, see
+/// [table](struct.FasterKv.html#method.table).
+///
+/// Keys are stored in the internal key space of the store, prefixed with the id of the table,
+/// so equal keys in different tables refer to different records.
+pub struct Table<'a, K, V, C = Bincode> {
+    store: &'a FasterKv<C>,
+    name: String,
+    id: Vec<u8>,
+    types: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'a, K, V, C> Table<'a, K, V, C>
+where
+    C: Codec<K> + Codec<V>,
+{
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn upsert(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.store
+            .upsert_expiring(&self.id, key, value, ttl::NEVER, monotonic_serial_number)
+    }
+
+    pub fn upsert_with_ttl(
+        &self,
+        key: &K,
+        value: &V,
+        ttl: Duration,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.store
+            .upsert_with_ttl_in(&self.id, key, value, ttl, monotonic_serial_number)
+    }
+
+    pub fn read(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<(Status, Receiver<Result<V, FasterError<'static>>>), FasterError<'static>> {
+        self.store.read_in(&self.id, key, monotonic_serial_number)
+    }
+
+    pub fn read_with<F, R>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+        reader: F,
+    ) -> Result<ReadWith<R>, FasterError<'static>>
+    where
+        F: FnOnce(&[u8]) -> R + 'static,
+        R: 'static,
+    {
+        self.store
+            .read_with_in(&self.id, key, monotonic_serial_number, reader)
+    }
+
+    pub fn upsert_batch<'b, I>(
+        &self,
+        items: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Status>, FasterError<'static>>
+    where
+        K: 'b,
+        V: 'b,
+        I: IntoIterator<Item = (&'b K, &'b V)>,
+    {
+        self.store
+            .upsert_batch_in(&self.id, items, monotonic_serial_number)
+    }
+
+    pub fn read_batch<'b, I>(
+        &self,
+        keys: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Result<Option<V>, FasterError<'static>>>, FasterError<'static>>
+    where
+        K: 'b,
+        I: IntoIterator<Item = &'b K>,
+    {
+        self.store
+            .read_batch_in(&self.id, keys, monotonic_serial_number)
+    }
+
+    pub fn rmw_with<I, U>(
+        &self,
+        key: &K,
+        initial: I,
+        update: U,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        I: FnOnce() -> V,
+        U: Fn(&V) -> V,
+    {
+        self.store
+            .rmw_with_in(&self.id, key, initial, update, monotonic_serial_number)
+    }
+
+    pub fn compare_and_swap(
+        &self,
+        key: &K,
+        expected: &V,
+        new: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<bool, FasterError<'static>> {
+        self.store
+            .compare_and_swap_in(&self.id, key, expected, new, monotonic_serial_number)
+    }
+
+    pub fn upsert_if_absent(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<bool, FasterError<'static>> {
+        self.store
+            .upsert_if_absent_in(&self.id, key, value, monotonic_serial_number)
+    }
+
+    pub fn delete(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.store.delete_in(&self.id, key, monotonic_serial_number)
+    }
+
+    /// Scans the records of this table with addresses in `[begin, end)`, see
+    /// [scan](struct.FasterKv.html#method.scan). Keys are returned without the table id.
+    pub fn scan(&self, begin: u64, end: u64) -> Result<LogScan<'a>, FasterError<'static>> {
+        self.store.scan_namespace(self.id.clone(), begin, end)
+    }
+
+    /// Iterates over the current value of every key in this table.
+    pub fn iter(&self) -> Result<Iter<'a, K, V, C>, FasterError<'static>> {
+        Ok(Iter::new(self.scan(0, u64::MAX)?))
+    }
+
+    /// Deletes every key of this table, returning how many were deleted.
+    ///
+    /// The keys are found by scanning the log and deleted one by one, the `i`th with serial
+    /// number `monotonic_serial_number + i`. Keys written while the table is cleared may
+    /// survive.
+    pub fn clear(&self, monotonic_serial_number: u64) -> Result<u64, FasterError<'static>> {
+        let mut deleted = 0;
+        for record in self.scan(0, u64::MAX)?.skip_tombstones().latest_versions() {
+            let key = record.decode_key_with::<C, K>()?;
+            self.delete(&key, monotonic_serial_number + deleted)?;
+            deleted += 1;
+        }
+        Ok(deleted)
+    }
+
+    /// Counts the live records of this table and the bytes they take up.
+    ///
+    /// FASTER keeps no statistics per table, so this scans the whole log.
+    pub fn estimate_size(&self) -> Result<TableSize, FasterError<'static>> {
+        let now = SystemTime::now();
+        let mut size = TableSize::default();
+        for record in self.scan(0, u64::MAX)?.skip_tombstones().latest_versions() {
+            if matches!(record.expires_at, Some(expires_at) if expires_at <= now) {
+                continue;
+            }
+            size.records += 1;
+            size.bytes += (self.id.len() + record.key.len() + record.value.len()) as u64;
+        }
+        Ok(size)
+    }
+}
+
+impl<'a, K, V, C> Table<'a, K, V, C>
+where
+    V: FasterRmw,
+    C: Codec<K> + Codec<V>,
+{
+    pub fn rmw(
+        &self,
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.store
+            .rmw_in(&self.id, key, modification, monotonic_serial_number)
+    }
+
+    pub fn rmw_batch<'b, I>(
+        &self,
+        items: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Status>, FasterError<'static>>
+    where
+        K: 'b,
+        V: 'b,
+        I: IntoIterator<Item = (&'b K, &'b V)>,
+    {
+        self.store
+            .rmw_batch_in(&self.id, items, monotonic_serial_number)
+    }
+}
+
+impl<'a, K, V, C> Table<'a, K, V, C>
+where
+    V: FasterConditionalRmw,
+    C: Codec<K> + Codec<V>,
+{
+    pub fn rmw_conditional(
+        &self,
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.store
+            .rmw_conditional_in(&self.id, key, modification, monotonic_serial_number)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, K, V, C> Table<'a, K, V, C>
+where
+    C: Codec<K> + Codec<V>,
+{
+    pub fn read_async(&self, key: &K, monotonic_serial_number: u64) -> ReadFuture<'a, V, C> {
+        self.store
+            .read_async_in(&self.id, key, monotonic_serial_number)
+    }
+
+    pub fn upsert_async(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> OperationFuture<'a, C> {
+        OperationFuture::new(self.store, self.upsert(key, value, monotonic_serial_number))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'a, K, V, C> Table<'a, K, V, C>
+where
+    V: FasterRmw,
+    C: Codec<K> + Codec<V>,
+{
+    pub fn rmw_async(
+        &self,
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> OperationFuture<'a, C> {
+        OperationFuture::new(
+            self.store,
+            self.rmw(key, modification, monotonic_serial_number),
+        )
+    }
+}
+
+impl<C> FasterKv<C> {
+    /// Opens the table `name`, a namespace within this store whose keys and values are fixed
+    /// to `K` and `V`.
+    ///
+    /// Tables share the hybrid log, the hash index and checkpoints of the store, so one store
+    /// can hold several kinds of records without a log of its own for each. Every table keeps
+    /// its keys apart by prefixing them with an id derived from `name`, which may be at most
+    /// 255 bytes long. Opening a table again, also after recovery, returns the same records.
+    ///
+    /// The keys of tables live in a key space of their own, which FASTER tells apart from the
+    /// keys written outside of tables. Those are stored exactly as they are encoded and never
+    /// collide with the keys of a table, whatever their bytes. `scan` and `iter` on the store
+    /// leave out the records of tables, and secondary indexes only cover keys outside of
+    /// tables.
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{FasterKv, Status};
+    ///
+    /// let store = FasterKv::default();
+    /// let users = store.table::<u64, String>("users").unwrap();
+    /// let orders = store.table::<u64, u64>("orders").unwrap();
+    ///
+    /// users.upsert(&1, &String::from("Ada"), 1).unwrap();
+    /// orders.upsert(&1, &42, 2).unwrap();
+    ///
+    /// let (res, recv) = users.read(&1, 3).unwrap();
+    /// assert_eq!(res, Status::Ok);
+    /// assert_eq!(recv.recv().unwrap().unwrap(), "Ada");
+    /// assert_eq!(orders.estimate_size().unwrap().records, 1);
+    /// ```
+    pub fn table<K, V>(&self, name: &str) -> Result<Table<'_, K, V, C>, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+            return Err(FasterError::InvalidTableName(name.to_string()));
+        }
+        Ok(Table {
+            store: self,
+            name: name.to_string(),
+            id: keyspace::named(TABLE, name),
+            types: PhantomData,
+        })
+    }
+}
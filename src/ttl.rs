@@ -0,0 +1,352 @@
+use crate::codec::Codec;
+use crate::faster_traits::{merge, rmw_callback, RmwAction};
+use crate::keyspace::ROOT;
+use crate::rmw_with::RmwLogic;
+use crate::status::Status;
+use crate::{FasterError, FasterKv, FasterRmw};
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::convert::TryInto;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How a Read-Modify-Write on a store with a TTL treats the expiry of the record it updates,
+/// see [with_ttl](struct.FasterKvBuilder.html#method.with_ttl). This applies to `rmw` as well
+/// as `rmw_with`, `rmw_conditional`, `compare_and_swap` and `upsert_if_absent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TtlPolicy {
+    /// Keep the expiry of the current record. Records created by a Read-Modify-Write never
+    /// expire.
+    Preserve,
+    /// Expire the updated record the given duration after the Read-Modify-Write.
+    Refresh(Duration),
+}
+
+// Values of a store with a TTL are prefixed with their expiry in milliseconds since the Unix
+// epoch, as a little endian u64
+const EXPIRY_LENGTH: usize = 8;
+
+// Expiry of records that never expire. A modification carrying it keeps the current expiry.
+pub(crate) const NEVER: u64 = u64::MAX;
+
+thread_local! {
+    // Time at which the records FASTER hands to callbacks on this thread are judged. Fixed for
+    // the duration of a call into FASTER, which may call back more than once for one record
+    // and must get the same answer every time.
+    static CLOCK: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+fn system_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn now() -> u64 {
+    CLOCK
+        .with(|clock| clock.get())
+        .unwrap_or_else(system_millis)
+}
+
+/// Fixes the time callbacks on this thread see until the guard is dropped.
+pub(crate) struct PinnedClock {
+    previous: Option<u64>,
+}
+
+impl PinnedClock {
+    pub(crate) fn pin() -> Self {
+        let previous = CLOCK.with(|clock| clock.replace(Some(now())));
+        PinnedClock { previous }
+    }
+}
+
+impl Drop for PinnedClock {
+    fn drop(&mut self) {
+        CLOCK.with(|clock| clock.set(self.previous));
+    }
+}
+
+fn expiry_after(ttl: Duration) -> u64 {
+    now().saturating_add(ttl.as_millis() as u64).min(NEVER - 1)
+}
+
+fn with_expiry(expiry: u64, encoded: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(EXPIRY_LENGTH + encoded.len());
+    bytes.extend_from_slice(&expiry.to_le_bytes());
+    bytes.extend_from_slice(encoded);
+    bytes
+}
+
+pub(crate) fn split_expiry(bytes: &[u8]) -> (u64, &[u8]) {
+    if bytes.len() < EXPIRY_LENGTH {
+        return (NEVER, bytes);
+    }
+    let (expiry, value) = bytes.split_at(EXPIRY_LENGTH);
+    (u64::from_le_bytes(expiry.try_into().unwrap()), value)
+}
+
+/// The encoded value of a record read from FASTER, or `None` if it has expired.
+///
+/// Values of stores without a TTL, for which `EXPIRING` is `false`, are returned as they are.
+#[inline(always)]
+pub(crate) fn unexpired<const EXPIRING: bool>(bytes: &[u8]) -> Option<&[u8]> {
+    if !EXPIRING {
+        return Some(bytes);
+    }
+    match split_expiry(bytes) {
+        (expiry, _) if expiry <= now() => None,
+        (_, value) => Some(value),
+    }
+}
+
+/// Splits the expiry off the value of a record found while scanning a store with a TTL.
+pub(crate) fn strip_expiry(bytes: &mut Vec<u8>) -> Option<SystemTime> {
+    let (expiry, _) = split_expiry(bytes);
+    bytes.drain(..EXPIRY_LENGTH.min(bytes.len()));
+    match expiry {
+        NEVER => None,
+        expiry => Some(UNIX_EPOCH + Duration::from_millis(expiry)),
+    }
+}
+
+pub(crate) unsafe extern "C" fn rmw_expiring_callback<C, T>(
+    current: *const u8,
+    length_current: u64,
+    modification: *const u8,
+    length_modification: u64,
+    dst: *mut u8,
+) -> u64
+where
+    C: Codec<T>,
+    T: FasterRmw,
+{
+    let encoded = merge_expiring::<C, T>(
+        std::slice::from_raw_parts(current, length_current as usize),
+        std::slice::from_raw_parts(modification, length_modification as usize),
+    );
+    let size = encoded.len();
+    if !dst.is_null() {
+        encoded.as_ptr().copy_to(dst, size);
+    }
+    size as u64
+}
+
+/// Like `merge`, for the values and modifications of a store with a TTL.
+pub(crate) fn merge_expiring<C, T>(current: &[u8], modification: &[u8]) -> Vec<u8>
+where
+    C: Codec<T>,
+    T: FasterRmw,
+{
+    let (current_expiry, current) = split_expiry(current);
+    let (expiry, modification) = split_expiry(modification);
+    match current_expiry <= now() {
+        // An expired record is replaced as if the key were missing
+        true => with_expiry(expiry, modification),
+        false => {
+            let expiry = match expiry {
+                NEVER => current_expiry,
+                expiry => expiry,
+            };
+            with_expiry(expiry, &merge::<C, T>(current, modification))
+        }
+    }
+}
+
+/// Whether a record expiring at `expiry` has expired.
+pub(crate) fn expired(expiry: u64) -> bool {
+    expiry <= now()
+}
+
+/// Runs the logic of a Read-Modify-Write on a store with a TTL. `logic` sees values without
+/// their expiry and an expired record as a missing key, while the expiry of the value it
+/// stores follows the [TtlPolicy](enum.TtlPolicy.html) of the store.
+pub(crate) struct ExpiringLogic<'l, L> {
+    logic: &'l mut L,
+    policy: TtlPolicy,
+    // Whether the last decision left an expired record without a value
+    expired_absent: bool,
+}
+
+impl<'l, L: RmwLogic> ExpiringLogic<'l, L> {
+    pub(crate) fn new(logic: &'l mut L, policy: TtlPolicy) -> Self {
+        ExpiringLogic {
+            logic,
+            policy,
+            expired_absent: false,
+        }
+    }
+
+    /// The status of the operation as if the expired record had been missing.
+    pub(crate) fn status(&self, status: Status) -> Status {
+        match status {
+            Status::Ok if self.expired_absent => Status::NotFound,
+            status => status,
+        }
+    }
+}
+
+impl<'l, L: RmwLogic> RmwLogic for ExpiringLogic<'l, L> {
+    fn decide(&mut self, current: Option<&[u8]>) -> RmwAction<Vec<u8>> {
+        let (expired, unexpired) = match current.map(split_expiry) {
+            Some((expiry, _)) if expiry <= now() => (true, None),
+            current => (false, current),
+        };
+        let decision = self.logic.decide(unexpired.map(|(_, value)| value));
+        self.expired_absent = expired && !matches!(decision, RmwAction::Update(_));
+        match decision {
+            RmwAction::Update(encoded) => {
+                let expiry = match (self.policy, unexpired) {
+                    (TtlPolicy::Refresh(ttl), _) => expiry_after(ttl),
+                    (TtlPolicy::Preserve, Some((expiry, _))) => expiry,
+                    (TtlPolicy::Preserve, None) => NEVER,
+                };
+                RmwAction::Update(with_expiry(expiry, &encoded))
+            }
+            // An expired record that is not replaced is deleted rather than copied
+            RmwAction::Keep if expired => RmwAction::Delete,
+            action => action,
+        }
+    }
+}
+
+// Tells compaction whether to keep a record
+unsafe extern "C" fn unexpired_callback(value: *const u8, length: u64) -> bool {
+    let (expiry, _) = split_expiry(std::slice::from_raw_parts(value, length as usize));
+    expiry > now()
+}
+
+impl<C> FasterKv<C> {
+    /// Upserts `value` for `key`, to be treated as missing once `ttl` has passed.
+    ///
+    /// Expired records are reported as [NotFound](status/enum.Status.html) by reads, treated
+    /// as a missing key by Read-Modify-Writes and conditional writes, and dropped by `compact`.
+    /// Expiry is judged against the system clock, so it holds across checkpoints and recovery.
+    /// Requires a store built [with_ttl](struct.FasterKvBuilder.html#method.with_ttl).
+    ///
+    /// # Example
+    /// ```
+    /// use faster_rs::{FasterKvBuilder, Status, TtlPolicy};
+    /// use std::time::Duration;
+    ///
+    /// let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+    ///     .with_ttl(TtlPolicy::Preserve)
+    ///     .build()
+    ///     .unwrap();
+    /// store
+    ///     .upsert_with_ttl(&1u64, &42u64, Duration::from_millis(10), 1)
+    ///     .unwrap();
+    /// std::thread::sleep(Duration::from_millis(20));
+    /// let (res, _) = store.read::<u64, u64>(&1, 2).unwrap();
+    /// assert_eq!(res, Status::NotFound);
+    /// ```
+    pub fn upsert_with_ttl<K, V>(
+        &self,
+        key: &K,
+        value: &V,
+        ttl: Duration,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        self.upsert_with_ttl_in(ROOT, key, value, ttl, monotonic_serial_number)
+    }
+
+    pub(crate) fn upsert_with_ttl_in<K, V>(
+        &self,
+        namespace: &[u8],
+        key: &K,
+        value: &V,
+        ttl: Duration,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        C: Codec<K> + Codec<V>,
+    {
+        if self.ttl.is_none() {
+            return Err(FasterError::TtlNotEnabled);
+        }
+        let expiry = expiry_after(ttl);
+        self.upsert_expiring(namespace, key, value, expiry, monotonic_serial_number)
+    }
+
+    /// Encodes a value to be written to the log, prefixed with `expiry` if the store has a TTL.
+    pub(crate) fn encode_value<'v, V>(
+        &self,
+        value: &'v V,
+        expiry: u64,
+    ) -> Result<Cow<'v, [u8]>, FasterError<'static>>
+    where
+        C: Codec<V>,
+    {
+        Ok(self.expiring_value(C::encode_borrowed(value)?, expiry))
+    }
+
+    /// Prefixes an encoded value with `expiry` if the store has a TTL.
+    pub(crate) fn expiring_value<'v>(&self, encoded: Cow<'v, [u8]>, expiry: u64) -> Cow<'v, [u8]> {
+        match self.ttl {
+            Some(_) => Cow::Owned(with_expiry(expiry, &encoded)),
+            None => encoded,
+        }
+    }
+
+    /// Encodes the modification of a Read-Modify-Write, which carries the expiry the
+    /// [TtlPolicy](enum.TtlPolicy.html) of the store asks for.
+    pub(crate) fn encode_modification<V>(
+        &self,
+        modification: &V,
+    ) -> Result<Vec<u8>, FasterError<'static>>
+    where
+        C: Codec<V>,
+    {
+        let expiry = match self.ttl {
+            Some(TtlPolicy::Refresh(ttl)) => expiry_after(ttl),
+            _ => NEVER,
+        };
+        self.encode_value(modification, expiry).map(Cow::into_owned)
+    }
+
+    /// The Read-Modify-Write callback for values of type `V` in this store.
+    pub(crate) fn rmw_callback<V>(&self) -> ffi::rmw_callback
+    where
+        C: Codec<V>,
+        V: FasterRmw,
+    {
+        match self.ttl {
+            Some(_) => Some(rmw_expiring_callback::<C, V>),
+            None => Some(rmw_callback::<C, V>),
+        }
+    }
+
+    /// Merges an encoded modification into the value stored for a key like the callback of
+    /// `rmw_callback` does.
+    pub(crate) fn merge_fn<V>(&self) -> fn(&[u8], &[u8]) -> Vec<u8>
+    where
+        C: Codec<V>,
+        V: FasterRmw,
+    {
+        match self.ttl {
+            Some(_) => merge_expiring::<C, V>,
+            None => merge::<C, V>,
+        }
+    }
+
+    pub(crate) fn expiring(&self) -> bool {
+        self.ttl.is_some()
+    }
+
+    pub(crate) fn compact_log(&self, until_address: u64) -> bool {
+        match self.ttl {
+            None => unsafe { ffi::faster_compact(self.faster_t, until_address) },
+            // The filter is called by a thread of faster-c, where the clock is never pinned
+            Some(_) => unsafe {
+                ffi::faster_compact_with_filter(
+                    self.faster_t,
+                    until_address,
+                    Some(unexpired_callback),
+                )
+            },
+        }
+    }
+}
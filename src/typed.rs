@@ -0,0 +1,371 @@
+use crate::codec::{Bincode, Codec};
+use crate::util::*;
+use crate::{
+    CheckpointInfo, CheckpointPair, FasterConditionalRmw, FasterError, FasterKey, FasterKv,
+    FasterKvBuilder, FasterRmw, FasterValue, Iter, ReadWith, Stats, Status,
+};
+#[cfg(feature = "async")]
+use crate::{CompletePendingFuture, OperationFuture, ReadFuture};
+
+use std::marker::PhantomData;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+/// A `FasterKv` whose key and value types are fixed when the store is built.
+///
+/// All operations are type-checked against `K` and `V`, so a value can never be
+/// read back as a different type than the one it was written with.
+///
+/// # Example
+/// ```
+/// use faster_rs::{FasterKvBuilder, Status, TypedFasterKv};
+///
+/// let store: TypedFasterKv<u64, String> = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+///     .build_typed()
+///     .unwrap();
+///
+/// let key = 1;
+/// let value = String::from("Hello");
+///
+/// store.upsert(&key, &value, 1).unwrap();
+///
+/// let (res, recv) = store.read(&key, 1).unwrap();
+/// assert_eq!(Status::Ok, res);
+/// assert_eq!(value, recv.recv().unwrap().unwrap());
+/// ```
+pub struct TypedFasterKv<K, V, C = Bincode> {
+    pub(crate) store: FasterKv<C>,
+    // fn() -> (K, V) keeps Send/Sync independent of K and V, which are never stored
+    types: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V, C> TypedFasterKv<K, V, C>
+where
+    C: Codec<K> + Codec<V>,
+{
+    pub(crate) fn new(store: FasterKv<C>) -> Self {
+        TypedFasterKv {
+            store,
+            types: PhantomData,
+        }
+    }
+
+    pub fn upsert(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.store.upsert(key, value, monotonic_serial_number)
+    }
+
+    pub fn upsert_with_ttl(
+        &self,
+        key: &K,
+        value: &V,
+        ttl: Duration,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.store
+            .upsert_with_ttl(key, value, ttl, monotonic_serial_number)
+    }
+
+    pub fn read(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<(Status, Receiver<Result<V, FasterError<'static>>>), FasterError<'static>> {
+        self.store.read(key, monotonic_serial_number)
+    }
+
+    pub fn upsert_batch<'a, I>(
+        &self,
+        items: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Status>, FasterError<'static>>
+    where
+        K: 'a,
+        V: 'a,
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        self.store.upsert_batch(items, monotonic_serial_number)
+    }
+
+    pub fn read_batch<'a, I>(
+        &self,
+        keys: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Result<Option<V>, FasterError<'static>>>, FasterError<'static>>
+    where
+        K: 'a,
+        I: IntoIterator<Item = &'a K>,
+    {
+        self.store.read_batch(keys, monotonic_serial_number)
+    }
+
+    pub fn read_with<F, R>(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+        reader: F,
+    ) -> Result<ReadWith<R>, FasterError<'static>>
+    where
+        F: FnOnce(&[u8]) -> R + 'static,
+        R: 'static,
+    {
+        self.store.read_with(key, monotonic_serial_number, reader)
+    }
+
+    pub fn rmw_with<I, U>(
+        &self,
+        key: &K,
+        initial: I,
+        update: U,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>>
+    where
+        I: FnOnce() -> V,
+        U: Fn(&V) -> V,
+    {
+        self.store
+            .rmw_with(key, initial, update, monotonic_serial_number)
+    }
+
+    pub fn compare_and_swap(
+        &self,
+        key: &K,
+        expected: &V,
+        new: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<bool, FasterError<'static>> {
+        self.store
+            .compare_and_swap(key, expected, new, monotonic_serial_number)
+    }
+
+    pub fn upsert_if_absent(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<bool, FasterError<'static>> {
+        self.store
+            .upsert_if_absent(key, value, monotonic_serial_number)
+    }
+
+    pub fn delete(
+        &self,
+        key: &K,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.store.delete(key, monotonic_serial_number)
+    }
+
+    pub fn size(&self) -> u64 {
+        self.store.size()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.store.stats()
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn render_metrics(&self) -> String {
+        self.store.render_metrics()
+    }
+
+    pub fn iter(&self) -> Result<Iter<'_, K, V, C>, FasterError<'static>> {
+        self.store.iter()
+    }
+
+    pub fn begin_address(&self) -> u64 {
+        self.store.begin_address()
+    }
+
+    pub fn head_address(&self) -> u64 {
+        self.store.head_address()
+    }
+
+    pub fn read_only_address(&self) -> u64 {
+        self.store.read_only_address()
+    }
+
+    pub fn tail_address(&self) -> u64 {
+        self.store.tail_address()
+    }
+
+    pub fn compact(&self, until_address: u64) -> Result<(), FasterError<'static>> {
+        self.store.compact(until_address)
+    }
+
+    pub fn shift_begin_address(&self, address: u64) -> Result<(), FasterError<'static>> {
+        self.store.shift_begin_address(address)
+    }
+
+    pub fn checkpoint(&self) -> Result<CheckPoint, FasterError<'_>> {
+        self.store.checkpoint()
+    }
+
+    pub fn checkpoint_index(&self) -> Result<CheckPoint, FasterError<'_>> {
+        self.store.checkpoint_index()
+    }
+
+    pub fn checkpoint_hybrid_log(&self) -> Result<CheckPoint, FasterError<'_>> {
+        self.store.checkpoint_hybrid_log()
+    }
+
+    pub fn checkpoint_status(&self, token: &str) -> Result<Status, FasterError<'static>> {
+        self.store.checkpoint_status(token)
+    }
+
+    pub fn list_checkpoints(&self) -> Result<Vec<CheckpointInfo>, FasterError<'static>> {
+        self.store.list_checkpoints()
+    }
+
+    pub fn latest_checkpoint(&self) -> Result<Option<CheckpointPair>, FasterError<'static>> {
+        self.store.latest_checkpoint()
+    }
+
+    pub fn prune_checkpoints(
+        &self,
+        retain: usize,
+    ) -> Result<Vec<CheckpointInfo>, FasterError<'static>> {
+        self.store.prune_checkpoints(retain)
+    }
+
+    pub fn recover(
+        &self,
+        index_token: String,
+        hybrid_log_token: String,
+    ) -> Result<Recover, FasterError<'_>> {
+        self.store.recover(index_token, hybrid_log_token)
+    }
+
+    pub fn complete_pending(&self, b: bool) -> Result<(), FasterError<'static>> {
+        self.store.complete_pending(b)
+    }
+
+    pub fn start_session(&self) -> Result<String, FasterError<'static>> {
+        self.store.start_session()
+    }
+
+    pub fn continue_session(&self, token: String) -> Result<u64, FasterError<'static>> {
+        self.store.continue_session(token)
+    }
+
+    pub fn stop_session(&self) {
+        self.store.stop_session()
+    }
+
+    pub fn refresh(&self) {
+        self.store.refresh()
+    }
+
+    pub fn dump_distribution(&self) {
+        self.store.dump_distribution()
+    }
+
+    pub fn grow_index(&self) -> bool {
+        self.store.grow_index()
+    }
+
+    // Warning: Calling this will remove the stored data
+    pub fn clean_storage(&self) -> Result<(), FasterError<'_>> {
+        self.store.clean_storage()
+    }
+}
+
+impl<K, V, C> TypedFasterKv<K, V, C>
+where
+    V: FasterRmw,
+    C: Codec<K> + Codec<V>,
+{
+    pub fn rmw(
+        &self,
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.store.rmw(key, modification, monotonic_serial_number)
+    }
+
+    pub fn rmw_batch<'a, I>(
+        &self,
+        items: I,
+        monotonic_serial_number: u64,
+    ) -> Result<Vec<Status>, FasterError<'static>>
+    where
+        K: 'a,
+        V: 'a,
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        self.store.rmw_batch(items, monotonic_serial_number)
+    }
+}
+
+impl<K, V, C> TypedFasterKv<K, V, C>
+where
+    V: FasterConditionalRmw,
+    C: Codec<K> + Codec<V>,
+{
+    pub fn rmw_conditional(
+        &self,
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> Result<Status, FasterError<'static>> {
+        self.store
+            .rmw_conditional(key, modification, monotonic_serial_number)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<K, V, C> TypedFasterKv<K, V, C>
+where
+    C: Codec<K> + Codec<V>,
+{
+    pub fn read_async(&self, key: &K, monotonic_serial_number: u64) -> ReadFuture<'_, V, C> {
+        self.store.read_async(key, monotonic_serial_number)
+    }
+
+    pub fn upsert_async(
+        &self,
+        key: &K,
+        value: &V,
+        monotonic_serial_number: u64,
+    ) -> OperationFuture<'_, C> {
+        self.store.upsert_async(key, value, monotonic_serial_number)
+    }
+
+    pub fn complete_pending_async(&self) -> CompletePendingFuture<'_, C> {
+        self.store.complete_pending_async()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<K, V, C> TypedFasterKv<K, V, C>
+where
+    V: FasterRmw,
+    C: Codec<K> + Codec<V>,
+{
+    pub fn rmw_async(
+        &self,
+        key: &K,
+        modification: &V,
+        monotonic_serial_number: u64,
+    ) -> OperationFuture<'_, C> {
+        self.store
+            .rmw_async(key, modification, monotonic_serial_number)
+    }
+}
+
+impl<K, V> Default for TypedFasterKv<K, V>
+where
+    K: FasterKey,
+    V: FasterValue,
+{
+    fn default() -> Self {
+        FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+            .build_typed()
+            .unwrap()
+    }
+}
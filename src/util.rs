@@ -1,13 +1,76 @@
 extern crate libc;
 extern crate libfaster_sys as ffi;
 
+use crate::status::{self, Status};
+use crate::{FasterError, SessionId};
+
+use std::ffi::{CStr, CString};
+
+#[derive(Debug, Clone)]
 pub struct CheckPoint {
     pub checked: bool,
     pub token: String,
 }
 
+#[derive(Debug, Clone)]
 pub struct Recover {
-    pub status: u8,
+    pub status: Status,
     pub version: u32,
-    pub session_ids: Vec<String>,
+    pub session_ids: Vec<SessionId>,
+}
+
+/// Converts a checkpoint or session token to pass it to FASTER.
+pub(crate) fn token_to_c(token: String) -> Result<CString, FasterError<'static>> {
+    CString::new(token).map_err(|err| {
+        FasterError::InvalidToken(String::from_utf8_lossy(&err.into_vec()).into_owned())
+    })
+}
+
+/// Copies a token handed out by FASTER.
+pub(crate) unsafe fn token_from_c(
+    token: *const libc::c_char,
+) -> Result<String, FasterError<'static>> {
+    let token = CStr::from_ptr(token);
+    token
+        .to_str()
+        .map(str::to_owned)
+        .map_err(|_| FasterError::InvalidToken(token.to_string_lossy().into_owned()))
+}
+
+pub(crate) unsafe fn checkpoint_from_result(
+    result: *mut ffi::faster_checkpoint_result,
+) -> Result<CheckPoint, FasterError<'static>> {
+    match result.is_null() {
+        true => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("FASTER refused to start the checkpoint");
+            Err(FasterError::CheckpointError)
+        }
+        false => {
+            let token = token_from_c((*result).token);
+            let checked = (*result).checked;
+            ffi::faster_checkpoint_result_destroy(result);
+
+            let checkpoint = CheckPoint {
+                checked,
+                token: token?,
+            };
+            #[cfg(feature = "tracing")]
+            tracing::info!(
+                token = %checkpoint.token,
+                checked = checkpoint.checked,
+                "started checkpoint"
+            );
+            Ok(checkpoint)
+        }
+    }
+}
+
+/// Status of the checkpoint with `token`: Ok once it is durable, Pending while it is being taken.
+pub(crate) fn checkpoint_status(
+    faster_t: *mut ffi::faster_t,
+    token: &str,
+) -> Result<Status, FasterError<'static>> {
+    let token = token_to_c(token.to_owned())?;
+    status::to_result(unsafe { ffi::faster_checkpoint_status(faster_t, token.as_ptr()) })
 }
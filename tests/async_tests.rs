@@ -0,0 +1,122 @@
+#![cfg(feature = "async")]
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::{FasterKv, FasterKvBuilder, Status};
+use futures::executor::block_on;
+use std::thread;
+use tempfile::TempDir;
+
+#[test]
+fn async_read_inserted_value() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    let value: u64 = 1337;
+
+    block_on(async {
+        let upsert = store.upsert_async(&key, &value, 1).await.unwrap();
+        assert_eq!(upsert, Status::Ok);
+
+        let read = store.read_async::<u64, u64>(&key, 2).await.unwrap();
+        assert_eq!(read, Some(value));
+    });
+}
+
+#[test]
+fn async_read_missing_value() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    let read = block_on(store.read_async::<u64, u64>(&key, 1)).unwrap();
+    assert_eq!(read, None);
+}
+
+#[test]
+fn async_rmw_changes_values() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    let value: u64 = 1337;
+    let modification: u64 = 100;
+
+    block_on(async {
+        store.upsert_async(&key, &value, 1).await.unwrap();
+        store.rmw_async(&key, &modification, 2).await.unwrap();
+        store.complete_pending_async().await.unwrap();
+
+        let read = store.read_async::<u64, u64>(&key, 3).await.unwrap();
+        assert_eq!(read, Some(value + modification));
+    });
+}
+
+#[test]
+fn async_reads_complete_after_complete_pending() {
+    let store = FasterKv::default();
+    let ops = 1000;
+
+    block_on(async {
+        for key in 0..ops {
+            store.upsert_async(&key, &(key * 2), key).await.unwrap();
+        }
+        let reads: Vec<_> = (0..ops)
+            .map(|key| store.read_async::<u64, u64>(&key, ops + key))
+            .collect();
+        store.complete_pending_async().await.unwrap();
+        for (key, read) in reads.into_iter().enumerate() {
+            assert_eq!(read.await.unwrap(), Some(key as u64 * 2));
+        }
+    });
+}
+
+// Writes enough records that the first keys are evicted from memory to disk, in a session of
+// the calling thread
+fn evicted_store(dir_path: &str) -> (FasterKv, u64) {
+    let store = FasterKvBuilder::new(1 << 14, 64 * 1024 * 1024)
+        .with_disk(dir_path)
+        .build()
+        .unwrap();
+    let num_keys: u64 = 1 << 19;
+    store.start_session().unwrap();
+    for key in 0..num_keys {
+        store.upsert(&key, &vec![key; 16], key).unwrap();
+    }
+    store.complete_pending(true).unwrap();
+    (store, num_keys)
+}
+
+#[test]
+fn async_reads_from_disk() {
+    let tmp_dir = TempDir::new().unwrap();
+    let (store, num_keys) = evicted_store(&tmp_dir.path().to_string_lossy());
+
+    let (status, _) = store.read::<u64, Vec<u64>>(&0, num_keys).unwrap();
+    assert_eq!(status, Status::Pending);
+    store.complete_pending(true).unwrap();
+
+    block_on(async {
+        for key in (0..num_keys).step_by(4096) {
+            let read = store
+                .read_async::<u64, Vec<u64>>(&key, num_keys + 1 + key)
+                .await
+                .unwrap();
+            assert_eq!(read, Some(vec![key; 16]));
+        }
+    });
+    store.stop_session();
+}
+
+#[test]
+fn async_read_from_disk_awaited_on_another_thread() {
+    let tmp_dir = TempDir::new().unwrap();
+    let (store, num_keys) = evicted_store(&tmp_dir.path().to_string_lossy());
+
+    let read = store.read_async::<u64, Vec<u64>>(&0, num_keys);
+    thread::scope(|scope| {
+        let reader = scope.spawn(|| block_on(read));
+        // The read is completed by the session's thread, which wakes the reader
+        while !reader.is_finished() {
+            store.complete_pending(false).unwrap();
+        }
+        assert_eq!(reader.join().unwrap().unwrap(), Some(vec![0; 16]));
+    });
+    store.stop_session();
+}
@@ -1,8 +1,8 @@
 extern crate faster_rs;
 
-use faster_rs::{status, FasterKv};
+use faster_rs::{FasterError, FasterKv, FasterRmw, Status};
+use serde_derive::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::sync::mpsc::Receiver;
 
 #[test]
 fn faster_check() {
@@ -10,11 +10,11 @@ fn faster_check() {
     let key: u64 = 1;
     let value: u64 = 1337;
 
-    let upsert = store.upsert(&key, &value, 1);
-    assert!((upsert == status::OK || upsert == status::PENDING) == true);
+    let upsert = store.upsert(&key, &value, 1).unwrap();
+    assert!((upsert == Status::Ok || upsert == Status::Pending) == true);
 
-    let rmw = store.rmw(&key, &(5 as u64), 1);
-    assert!(rmw == status::OK);
+    let rmw = store.rmw(&key, &(5 as u64), 1).unwrap();
+    assert!(rmw == Status::Ok);
 
     assert!(store.size() > 0);
 }
@@ -25,12 +25,12 @@ fn faster_read_inserted_value() {
     let key: u64 = 1;
     let value: u64 = 1337;
 
-    let upsert = store.upsert(&key, &value, 1);
-    assert!((upsert == status::OK || upsert == status::PENDING) == true);
+    let upsert = store.upsert(&key, &value, 1).unwrap();
+    assert!((upsert == Status::Ok || upsert == Status::Pending) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
-    assert!(res == status::OK);
-    assert!(recv.recv().unwrap() == value);
+    let (res, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+    assert!(res == Status::Ok);
+    assert!(recv.recv().unwrap().unwrap() == value);
 }
 
 #[test]
@@ -38,11 +38,27 @@ fn faster_read_missing_value_recv_error() {
     let store = FasterKv::default();
     let key: u64 = 1;
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
-    assert!(res == status::NOT_FOUND);
+    let (res, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+    assert!(res == Status::NotFound);
     assert!(recv.recv().is_err());
 }
 
+#[test]
+fn faster_read_wrong_type_returns_error() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    let value: u8 = 42;
+
+    store.upsert(&key, &value, 1).unwrap();
+
+    let (res, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    match recv.recv().unwrap() {
+        Err(FasterError::SerializationError(_)) => {}
+        _ => panic!("Should give SerializationError"),
+    }
+}
+
 #[test]
 fn faster_rmw_changes_values() {
     let store = FasterKv::default();
@@ -50,19 +66,19 @@ fn faster_rmw_changes_values() {
     let value: u64 = 1337;
     let modification: u64 = 100;
 
-    let upsert = store.upsert(&key, &value, 1);
-    assert!((upsert == status::OK || upsert == status::PENDING) == true);
+    let upsert = store.upsert(&key, &value, 1).unwrap();
+    assert!((upsert == Status::Ok || upsert == Status::Pending) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
-    assert!(res == status::OK);
-    assert!(recv.recv().unwrap() == value);
+    let (res, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+    assert!(res == Status::Ok);
+    assert!(recv.recv().unwrap().unwrap() == value);
 
-    let rmw = store.rmw(&key, &modification, 1);
-    assert!((rmw == status::OK || rmw == status::PENDING) == true);
+    let rmw = store.rmw(&key, &modification, 1).unwrap();
+    assert!((rmw == Status::Ok || rmw == Status::Pending) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
-    assert!(res == status::OK);
-    assert!(recv.recv().unwrap() == value + modification);
+    let (res, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+    assert!(res == Status::Ok);
+    assert!(recv.recv().unwrap().unwrap() == value + modification);
 }
 
 #[test]
@@ -71,12 +87,12 @@ fn faster_rmw_without_upsert() {
     let key: u64 = 1;
     let modification: u64 = 100;
 
-    let rmw = store.rmw(&key, &modification, 1);
-    assert!((rmw == status::OK || rmw == status::PENDING) == true);
+    let rmw = store.rmw(&key, &modification, 1).unwrap();
+    assert!((rmw == Status::Ok || rmw == Status::Pending) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
-    assert!(res == status::OK);
-    assert!(recv.recv().unwrap() == modification);
+    let (res, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+    assert!(res == Status::Ok);
+    assert!(recv.recv().unwrap().unwrap() == modification);
 }
 
 #[test]
@@ -86,19 +102,19 @@ fn faster_rmw_string() {
     let value = String::from("Hello, ");
     let modification = String::from("World!");
 
-    let upsert = store.upsert(&key, &value, 1);
-    assert!(upsert == status::OK || upsert == status::PENDING);
+    let upsert = store.upsert(&key, &value, 1).unwrap();
+    assert!(upsert == Status::Ok || upsert == Status::Pending);
 
-    let (res, recv): (u8, Receiver<String>) = store.read(&key, 1);
-    assert_eq!(res, status::OK);
-    assert_eq!(recv.recv().unwrap(), value);
+    let (res, recv) = store.read::<u64, String>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), value);
 
-    let rmw = store.rmw(&key, &modification, 1);
-    assert!(rmw == status::OK || rmw == status::PENDING);
+    let rmw = store.rmw(&key, &modification, 1).unwrap();
+    assert!(rmw == Status::Ok || rmw == Status::Pending);
 
-    let (res, recv): (u8, Receiver<String>) = store.read(&key, 1);
-    assert_eq!(res, status::OK);
-    assert_eq!(recv.recv().unwrap(), String::from("Hello, World!"));
+    let (res, recv) = store.read::<u64, String>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), String::from("Hello, World!"));
 }
 
 #[test]
@@ -109,26 +125,29 @@ fn faster_rmw_vec() {
     let modification = vec![3, 4, 5];
     let modification2 = vec![6, 7, 8, 9, 10];
 
-    let upsert = store.upsert(&key, &value, 1);
-    assert!(upsert == status::OK || upsert == status::PENDING);
+    let upsert = store.upsert(&key, &value, 1).unwrap();
+    assert!(upsert == Status::Ok || upsert == Status::Pending);
 
-    let (res, recv): (u8, Receiver<Vec<i32>>) = store.read(&key, 1);
-    assert_eq!(res, status::OK);
-    assert_eq!(recv.recv().unwrap(), value);
+    let (res, recv) = store.read::<u64, Vec<i32>>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), value);
 
-    let rmw = store.rmw(&key, &modification, 1);
-    assert!(rmw == status::OK || rmw == status::PENDING);
+    let rmw = store.rmw(&key, &modification, 1).unwrap();
+    assert!(rmw == Status::Ok || rmw == Status::Pending);
 
-    let (res, recv): (u8, Receiver<Vec<i32>>) = store.read(&key, 1);
-    assert_eq!(res, status::OK);
-    assert_eq!(recv.recv().unwrap(), vec![0, 1, 2, 3, 4, 5]);
+    let (res, recv) = store.read::<u64, Vec<i32>>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), vec![0, 1, 2, 3, 4, 5]);
 
-    let rmw = store.rmw(&key, &modification2, 1);
-    assert!(rmw == status::OK || rmw == status::PENDING);
+    let rmw = store.rmw(&key, &modification2, 1).unwrap();
+    assert!(rmw == Status::Ok || rmw == Status::Pending);
 
-    let (res, recv): (u8, Receiver<Vec<i32>>) = store.read(&key, 1);
-    assert_eq!(res, status::OK);
-    assert_eq!(recv.recv().unwrap(), vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+    let (res, recv) = store.read::<u64, Vec<i32>>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(
+        recv.recv().unwrap().unwrap(),
+        vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]
+    );
 }
 
 #[test]
@@ -138,12 +157,12 @@ fn faster_rmw_grow_string() {
     let final_string = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
     for i in 0..final_string.len() {
         let letter: String = final_string.get(i..i + 1).unwrap().to_string();
-        store.rmw(&key, &letter, 1);
+        store.rmw(&key, &letter, 1).unwrap();
     }
 
-    let (res, recv): (u8, Receiver<String>) = store.read(&key, 1);
-    assert_eq!(res, status::OK);
-    assert_eq!(recv.recv().unwrap(), final_string);
+    let (res, recv) = store.read::<String, String>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), final_string);
 }
 
 #[test]
@@ -152,15 +171,15 @@ fn faster_rmw_hashset() {
     let key = String::from("set");
     {
         let a: HashSet<i32> = [1, 2, 3].iter().cloned().collect();
-        store.rmw(&key, &a, 1);
+        store.rmw(&key, &a, 1).unwrap();
     }
     {
         let b: HashSet<i32> = [4, 2, 3, 4, 5].iter().cloned().collect();
-        store.rmw(&key, &b, 1);
+        store.rmw(&key, &b, 1).unwrap();
     }
-    let (res, recv): (u8, Receiver<HashSet<i32>>) = store.read(&key, 1);
-    assert_eq!(res, status::OK);
-    let hash_set = recv.recv().unwrap();
+    let (res, recv) = store.read::<String, HashSet<i32>>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    let hash_set = recv.recv().unwrap().unwrap();
     assert_eq!(hash_set.len(), 5);
     assert!(hash_set.contains(&1));
     assert!(hash_set.contains(&2));
@@ -169,23 +188,48 @@ fn faster_rmw_hashset() {
     assert!(hash_set.contains(&5));
 }
 
+#[derive(Serialize, Deserialize)]
+struct PanickingRmw(u64);
+
+impl FasterRmw for PanickingRmw {
+    fn rmw(&self, _modification: Self) -> Self {
+        panic!("RMW logic failed");
+    }
+}
+
+#[test]
+fn faster_rmw_panic_returns_error() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    store.upsert(&key, &PanickingRmw(1), 1).unwrap();
+    match store.rmw(&key, &PanickingRmw(2), 1) {
+        Err(FasterError::CallbackPanic) => {}
+        _ => panic!("Should give CallbackPanic"),
+    }
+
+    let (res, recv) = store.read::<u64, PanickingRmw>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap().0, 1);
+}
+
 #[test]
 fn faster_delete_inserted_value() {
     let store = FasterKv::default();
     let key: u64 = 1;
     let value: u64 = 1337;
 
-    let upsert = store.upsert(&key, &value, 1);
-    assert!((upsert == status::OK || upsert == status::PENDING) == true);
+    let upsert = store.upsert(&key, &value, 1).unwrap();
+    assert!((upsert == Status::Ok || upsert == Status::Pending) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
-    assert!(res == status::OK);
-    assert!(recv.recv().unwrap() == value);
+    let (res, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+    assert!(res == Status::Ok);
+    assert!(recv.recv().unwrap().unwrap() == value);
 
-    let delete = store.delete(&key, 1);
-    assert!((delete == status::OK || delete == status::PENDING) == true);
+    let delete = store.delete(&key, 1).unwrap();
+    assert!((delete == Status::Ok || delete == Status::Pending) == true);
 
-    let (res, recv): (u8, Receiver<u64>) = store.read(&key, 1);
-    assert!(res == status::NOT_FOUND);
+    let (res, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+    assert!(res == Status::NotFound);
     assert!(recv.recv().is_err());
 }
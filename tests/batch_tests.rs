@@ -0,0 +1,164 @@
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::{FasterError, FasterKv, FasterKvBuilder, Status, TtlPolicy, TypedFasterKv};
+use tempfile::TempDir;
+
+#[test]
+fn upsert_batch_then_read_batch() {
+    let store = FasterKv::default();
+    let pairs: Vec<(u64, String)> = (0..1000u64).map(|key| (key, key.to_string())).collect();
+
+    let statuses = store
+        .upsert_batch(pairs.iter().map(|(key, value)| (key, value)), 1)
+        .unwrap();
+    assert_eq!(statuses.len(), pairs.len());
+    assert!(statuses.iter().all(|status| *status == Status::Ok));
+
+    let keys: Vec<u64> = pairs.iter().map(|(key, _)| *key).collect();
+    let values = store.read_batch::<u64, String, _>(&keys, 1001).unwrap();
+    assert_eq!(values.len(), keys.len());
+    for (value, (_, expected)) in values.into_iter().zip(pairs.iter()) {
+        assert_eq!(value.unwrap().as_ref(), Some(expected));
+    }
+}
+
+#[test]
+fn read_batch_reports_missing_keys() {
+    let store = FasterKv::default();
+    store.upsert(&2u64, &20u64, 1).unwrap();
+
+    let values = store.read_batch::<u64, u64, _>(&[1, 2, 3], 2).unwrap();
+    let values: Vec<Option<u64>> = values.into_iter().map(Result::unwrap).collect();
+    assert_eq!(values, vec![None, Some(20), None]);
+}
+
+#[test]
+fn read_batch_reports_decode_errors_per_item() {
+    let store = FasterKv::default();
+    store.upsert(&1u64, &(1u64, 2u64), 1).unwrap();
+    store.upsert(&2u64, &1u64, 2).unwrap();
+
+    // The second value is too short to decode as a pair
+    let values = store.read_batch::<u64, (u64, u64), _>(&[1, 2], 3).unwrap();
+    assert_eq!(values[0].as_ref().unwrap(), &Some((1, 2)));
+    match &values[1] {
+        Err(FasterError::SerializationError(_)) => {}
+        other => panic!("Expected a serialization error, got {:?}", other),
+    }
+}
+
+#[test]
+fn rmw_batch_merges_repeated_keys() {
+    let store = FasterKv::default();
+    let increments: Vec<(u64, u64)> = (0..100u64).map(|i| (i % 10, 1)).collect();
+
+    let statuses = store
+        .rmw_batch(
+            increments
+                .iter()
+                .map(|(key, modification)| (key, modification)),
+            1,
+        )
+        .unwrap();
+    assert_eq!(statuses.len(), increments.len());
+
+    let keys: Vec<u64> = (0..10).collect();
+    for value in store.read_batch::<u64, u64, _>(&keys, 101).unwrap() {
+        assert_eq!(value.unwrap(), Some(10));
+    }
+}
+
+#[test]
+fn empty_batches() {
+    let store = FasterKv::default();
+    let keys: Vec<u64> = Vec::new();
+    let values: Vec<u64> = Vec::new();
+
+    assert!(store
+        .upsert_batch(keys.iter().zip(values.iter()), 1)
+        .unwrap()
+        .is_empty());
+    assert!(store
+        .read_batch::<u64, u64, _>(&keys, 1)
+        .unwrap()
+        .is_empty());
+    assert!(store
+        .rmw_batch(keys.iter().zip(values.iter()), 1)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn batches_complete_pending_items() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let store = FasterKvBuilder::new(1 << 14, 64 * 1024 * 1024)
+        .with_disk(&dir_path)
+        .build()
+        .unwrap();
+    let session = store.session().unwrap();
+    let num_keys: u64 = 1 << 18;
+    let keys: Vec<u64> = (0..num_keys).collect();
+
+    for chunk in keys.chunks(4096) {
+        session
+            .upsert_batch(chunk.iter().zip(chunk.iter()), chunk[0])
+            .unwrap();
+    }
+
+    // Early keys have been evicted to disk by now
+    let sampled: Vec<u64> = keys.iter().step_by(1024).cloned().collect();
+    let ones = vec![1u64; sampled.len()];
+    let statuses = session
+        .rmw_batch(sampled.iter().zip(ones.iter()), num_keys)
+        .unwrap();
+    assert!(statuses.iter().all(|status| *status == Status::Ok));
+
+    let values = session
+        .read_batch::<u64, u64, _>(&sampled, 2 * num_keys)
+        .unwrap();
+    for (value, key) in values.into_iter().zip(sampled.iter()) {
+        assert_eq!(value.unwrap(), Some(key + 1));
+    }
+}
+
+#[test]
+fn typed_batches() {
+    let store: TypedFasterKv<u64, u64> = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .build_typed()
+        .unwrap();
+    let keys: Vec<u64> = (0..10).collect();
+
+    store.upsert_batch(keys.iter().zip(keys.iter()), 1).unwrap();
+    store.rmw_batch(keys.iter().zip(keys.iter()), 11).unwrap();
+
+    let values = store.read_batch(&keys, 21).unwrap();
+    for (value, key) in values.into_iter().zip(keys.iter()) {
+        assert_eq!(value.unwrap(), Some(key * 2));
+    }
+}
+
+#[test]
+fn batches_of_an_indexed_store_with_a_ttl() {
+    let store: TypedFasterKv<u64, u64> = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .with_ttl(TtlPolicy::Preserve)
+        .build_typed()
+        .unwrap();
+    let by_value = store
+        .register_index("value", |value: &u64| *value, 1)
+        .unwrap();
+    let keys: Vec<u64> = (0..10).collect();
+
+    let statuses = store.upsert_batch(keys.iter().zip(keys.iter()), 2).unwrap();
+    assert!(statuses.iter().all(|status| *status == Status::Ok));
+    let statuses = store.rmw_batch(keys.iter().zip(keys.iter()), 12).unwrap();
+    assert!(statuses.iter().all(|status| *status == Status::Ok));
+
+    let values = store.read_batch(&keys, 22).unwrap();
+    for (value, key) in values.into_iter().zip(keys.iter()) {
+        assert_eq!(value.unwrap(), Some(key * 2));
+    }
+    assert_eq!(by_value.lookup(&4).unwrap(), vec![2]);
+    assert_eq!(by_value.lookup(&9).unwrap(), Vec::<u64>::new());
+}
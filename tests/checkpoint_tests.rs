@@ -1,7 +1,8 @@
 extern crate faster_rs;
 extern crate tempfile;
 
-use faster_rs::{FasterError, FasterKv, FasterKvBuilder};
+use faster_rs::{CheckpointKind, CheckpointPolicy, FasterError, FasterKv, FasterKvBuilder, Status};
+use std::time::{Duration, Instant, SystemTime};
 use tempfile::TempDir;
 
 #[test]
@@ -16,7 +17,7 @@ fn single_checkpoint() {
     let value: u64 = 100;
 
     for key in 0..1000 {
-        store.upsert(&(key as u64), &value, key);
+        store.upsert(&(key as u64), &value, key).unwrap();
     }
 
     let checkpoint = store.checkpoint().unwrap();
@@ -36,7 +37,7 @@ fn single_checkpoint_index() {
     let value: u64 = 100;
 
     for key in 0..1000 {
-        store.upsert(&(key as u64), &value, key);
+        store.upsert(&(key as u64), &value, key).unwrap();
     }
 
     let checkpoint = store.checkpoint_index().unwrap();
@@ -56,7 +57,7 @@ fn single_checkpoint_hybrid_log() {
     let value: u64 = 100;
 
     for key in 0..1000 {
-        store.upsert(&(key as u64), &value, key);
+        store.upsert(&(key as u64), &value, key).unwrap();
     }
 
     let checkpoint = store.checkpoint_hybrid_log().unwrap();
@@ -64,6 +65,342 @@ fn single_checkpoint_hybrid_log() {
     assert_eq!(checkpoint.token.len(), 37 - 1); // -1 \0
 }
 
+fn wait_for_policy_checkpoints(policy: CheckpointPolicy) {
+    let table_size: u64 = 1 << 14;
+    let log_size: u64 = 1073741824;
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let mut builder = FasterKvBuilder::new(table_size, log_size);
+    builder.with_disk(&dir_path).with_checkpoint_policy(policy);
+    let store = builder.build().unwrap();
+    let value: u64 = 100;
+
+    // Refreshing on every operation lets each checkpoint finish before the next one is due
+    let session = store.session_with_refresh_interval(1).unwrap();
+    let start = Instant::now();
+    let mut key: u64 = 0;
+    while store.checkpoints().len() < 2 && start.elapsed() < Duration::from_secs(30) {
+        session.upsert(&(key % 1000), &value, key).unwrap();
+        key += 1;
+    }
+
+    let checkpoints = store.checkpoints();
+    assert!(checkpoints.len() >= 2);
+    // Checkpoints are only reported once durable
+    for checkpoint in checkpoints {
+        assert_eq!(checkpoint.checked, true);
+        assert_eq!(checkpoint.token.len(), 37 - 1); // -1 \0
+        assert_eq!(
+            store.checkpoint_status(&checkpoint.token).unwrap(),
+            Status::Ok
+        );
+    }
+}
+
+#[test]
+fn checkpoint_status_reports_durability() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let store = FasterKvBuilder::new(1 << 14, 1073741824)
+        .with_disk(&dir_path)
+        .build()
+        .unwrap();
+    let session = store.session().unwrap();
+    session.upsert(&1u64, &1u64, 1).unwrap();
+
+    let checkpoint = store.checkpoint().unwrap();
+    assert!(checkpoint.checked);
+    // The checkpoint moves through its phases as the session refreshes
+    let start = Instant::now();
+    while store.checkpoint_status(&checkpoint.token).unwrap() == Status::Pending
+        && start.elapsed() < Duration::from_secs(30)
+    {
+        session.complete_pending(false).unwrap();
+        session.refresh();
+    }
+    assert_eq!(
+        store.checkpoint_status(&checkpoint.token).unwrap(),
+        Status::Ok
+    );
+
+    let unknown = "00000000-0000-0000-0000-000000000000";
+    assert_eq!(store.checkpoint_status(unknown).unwrap(), Status::NotFound);
+}
+
+#[test]
+fn checkpoint_policy_interval() {
+    wait_for_policy_checkpoints(CheckpointPolicy::Interval(Duration::from_millis(50)));
+}
+
+#[test]
+fn checkpoint_policy_operations() {
+    wait_for_policy_checkpoints(CheckpointPolicy::Operations(10000));
+}
+
+#[test]
+fn checkpoint_policy_log_growth() {
+    wait_for_policy_checkpoints(CheckpointPolicy::LogGrowth(1024 * 1024));
+}
+
+// Issues operations until the storage directory holds the given number of completed checkpoints
+fn wait_for_catalog(store: &FasterKv, count: usize) {
+    let value: u64 = 100;
+    let session = store.session_with_refresh_interval(1).unwrap();
+    let start = Instant::now();
+    let mut key: u64 = 0;
+    while store.list_checkpoints().unwrap().len() < count
+        && start.elapsed() < Duration::from_secs(30)
+    {
+        session.upsert(&(key % 1000), &value, key).unwrap();
+        key += 1;
+    }
+    session.complete_pending(true).unwrap();
+}
+
+#[test]
+fn list_and_prune_checkpoints() {
+    let table_size: u64 = 1 << 14;
+    let log_size: u64 = 1073741824;
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let mut builder = FasterKvBuilder::new(table_size, log_size);
+    builder.with_disk(&dir_path);
+    let store = builder.build().unwrap();
+
+    assert!(store.list_checkpoints().unwrap().is_empty());
+    assert_eq!(store.latest_checkpoint().unwrap(), None);
+
+    let index = store.checkpoint_index().unwrap();
+    wait_for_catalog(&store, 1);
+    let hybrid_log = store.checkpoint_hybrid_log().unwrap();
+    wait_for_catalog(&store, 2);
+    let full = store.checkpoint().unwrap();
+    wait_for_catalog(&store, 3);
+
+    let checkpoints = store.list_checkpoints().unwrap();
+    let kinds: Vec<(String, CheckpointKind)> = checkpoints
+        .iter()
+        .map(|checkpoint| (checkpoint.token.clone(), checkpoint.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (index.token.clone(), CheckpointKind::Index),
+            (hybrid_log.token.clone(), CheckpointKind::HybridLog),
+            (full.token.clone(), CheckpointKind::Full),
+        ]
+    );
+    assert!(checkpoints.iter().all(|checkpoint| checkpoint.size > 0));
+    let versions: Vec<u32> = checkpoints
+        .iter()
+        .map(|checkpoint| checkpoint.version)
+        .collect();
+    assert_eq!(versions, vec![1, 2, 3]);
+
+    // Checkpoints are ordered and paired by version, not by when their files were written
+    let index_info = std::fs::File::options()
+        .write(true)
+        .open(
+            std::path::Path::new(&dir_path)
+                .join("index-checkpoints")
+                .join(&index.token)
+                .join("info.dat"),
+        )
+        .unwrap();
+    index_info
+        .set_modified(SystemTime::now() + Duration::from_secs(3600))
+        .unwrap();
+    assert_eq!(store.list_checkpoints().unwrap()[0].token, index.token);
+
+    let latest = store.latest_checkpoint().unwrap().unwrap();
+    assert_eq!(latest.index_token, full.token);
+    assert_eq!(latest.hybrid_log_token, full.token);
+
+    // The hybrid log checkpoint pairs with the earlier index checkpoint, so both are kept
+    let removed = store.prune_checkpoints(2).unwrap();
+    assert!(removed.is_empty());
+
+    let removed = store.prune_checkpoints(1).unwrap();
+    let removed: Vec<String> = removed
+        .into_iter()
+        .map(|checkpoint| checkpoint.token)
+        .collect();
+    assert_eq!(removed, vec![index.token, hybrid_log.token]);
+    let remaining = store.list_checkpoints().unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].token, full.token);
+}
+
+#[test]
+fn in_memory_list_checkpoints_errors() {
+    let store = FasterKv::default();
+    match store.list_checkpoints() {
+        Err(FasterError::InvalidType) => assert!(true),
+        _ => assert!(false, "Should give InvalidType Error"),
+    }
+}
+
+#[test]
+fn tokens_with_nul_bytes_error() {
+    let tmp_dir = TempDir::new().unwrap();
+    let store = FasterKvBuilder::new(1 << 14, 1073741824)
+        .with_disk(&tmp_dir.path().to_string_lossy())
+        .build()
+        .unwrap();
+    match store.recover(String::from("index\0"), String::from("log")) {
+        Err(FasterError::InvalidToken(token)) => assert_eq!(token, "index\0"),
+        _ => assert!(false, "Should give InvalidToken Error"),
+    }
+    match store.continue_session(String::from("\0")) {
+        Err(FasterError::InvalidToken(_)) => assert!(true),
+        _ => assert!(false, "Should give InvalidToken Error"),
+    }
+}
+
+#[test]
+fn recover_latest_checkpoint() {
+    let table_size: u64 = 1 << 14;
+    let log_size: u64 = 1073741824;
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let mut builder = FasterKvBuilder::new(table_size, log_size);
+    builder.with_disk(&dir_path);
+
+    let session_id = {
+        let store = builder.build().unwrap();
+        let session_id = store.start_session().unwrap();
+        for key in 0..1000u64 {
+            store.upsert(&key, &(key * 2), key + 1).unwrap();
+        }
+        store.checkpoint().unwrap();
+        let start = Instant::now();
+        while store.latest_checkpoint().unwrap().is_none()
+            && start.elapsed() < Duration::from_secs(30)
+        {
+            store.complete_pending(true).unwrap();
+            store.refresh();
+        }
+        store.stop_session();
+        session_id
+    };
+
+    let (store, recovered) = builder.recover_latest().unwrap();
+    assert_eq!(recovered.session_ids, vec![session_id.parse().unwrap()]);
+    assert_eq!(store.continue_session(session_id).unwrap(), 1000);
+    for key in 0..1000u64 {
+        let (_, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+        assert_eq!(recv.recv().unwrap().unwrap(), key * 2);
+    }
+    store.stop_session();
+}
+
+#[test]
+fn recover_latest_falls_back_to_older_checkpoint() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let mut builder = FasterKvBuilder::new(1 << 14, 1073741824);
+    builder.with_disk(&dir_path);
+
+    let newer = {
+        let store = builder.build().unwrap();
+        store.start_session().unwrap();
+        let mut tokens = Vec::new();
+        for round in 1..=2u64 {
+            for key in 0..1000u64 {
+                store
+                    .upsert(&key, &(key * round), round * 1000 + key)
+                    .unwrap();
+            }
+            let token = store.checkpoint().unwrap().token;
+            while store.checkpoint_status(&token).unwrap() == Status::Pending {
+                store.complete_pending(false).unwrap();
+                store.refresh();
+            }
+            tokens.push(token);
+        }
+        store.stop_session();
+        tokens.pop().unwrap()
+    };
+
+    // Without its hash table the newest checkpoint can no longer be recovered, so the store
+    // holds the values of the first round
+    std::fs::remove_file(
+        std::path::Path::new(&dir_path)
+            .join("index-checkpoints")
+            .join(&newer)
+            .join("ht.dat"),
+    )
+    .unwrap();
+
+    let (store, recovered) = builder.recover_latest().unwrap();
+    assert_eq!(recovered.status, Status::Ok);
+    store.start_session().unwrap();
+    for key in 0..1000u64 {
+        let (_, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+        assert_eq!(recv.recv().unwrap().unwrap(), key);
+    }
+    store.stop_session();
+}
+
+#[test]
+fn replay_sessions_after_recovery() {
+    let table_size: u64 = 1 << 14;
+    let log_size: u64 = 1073741824;
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let mut builder = FasterKvBuilder::new(table_size, log_size);
+    builder.with_disk(&dir_path);
+    // Serial number doubles as key, and key * 2 is the value
+    let journal: Vec<u64> = (1..=2000).collect();
+
+    {
+        let store = builder.build().unwrap();
+        let session = store.session_with_refresh_interval(1).unwrap();
+        for key in &journal[..1000] {
+            session.upsert(key, &(key * 2), *key).unwrap();
+        }
+        store.checkpoint().unwrap();
+        while store.latest_checkpoint().unwrap().is_none() {
+            session.refresh();
+        }
+        // Never persisted, so these are replayed after recovery
+        for key in &journal[1000..] {
+            session.upsert(key, &(key * 2), *key).unwrap();
+        }
+    }
+
+    let (store, recovered) = builder.recover_latest().unwrap();
+    let sessions = store
+        .replay_sessions(&recovered, |session, recovered| {
+            for key in journal.iter().filter(|key| **key > recovered.serial_number) {
+                session.upsert(key, &(key * 2), *key)?;
+            }
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, recovered.session_ids[0]);
+    assert!(sessions[0].serial_number >= 1000);
+
+    let session = store.session().unwrap();
+    for key in &journal {
+        let (_, recv) = session.read::<u64, u64>(key, 1).unwrap();
+        assert_eq!(recv.recv().unwrap().unwrap(), key * 2);
+    }
+}
+
+#[test]
+fn recover_latest_without_checkpoint_errors() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let mut builder = FasterKvBuilder::new(1 << 14, 1073741824);
+    builder.with_disk(&dir_path);
+    match builder.recover_latest() {
+        Err(FasterError::NoCheckpoint(dir)) => assert_eq!(dir, dir_path),
+        _ => assert!(false, "Should give NoCheckpoint Error"),
+    }
+}
+
 #[test]
 fn concurrent_checkpoints() {
     //TODO
@@ -75,7 +412,7 @@ fn in_memory_checkpoint_errors() {
     let value: u64 = 100;
 
     for key in 0..1000 {
-        store.upsert(&(key as u64), &value, key);
+        store.upsert(&(key as u64), &value, key).unwrap();
     }
 
     let checkpoint = store.checkpoint();
@@ -92,7 +429,7 @@ fn in_memory_checkpoint_index_errors() {
     let value: u64 = 100;
 
     for key in 0..1000 {
-        store.upsert(&(key as u64), &value, key);
+        store.upsert(&(key as u64), &value, key).unwrap();
     }
 
     let checkpoint = store.checkpoint_index();
@@ -109,7 +446,7 @@ fn in_memory_checkpoint_hybrid_log_errors() {
     let value: u64 = 100;
 
     for key in 0..1000 {
-        store.upsert(&(key as u64), &value, key);
+        store.upsert(&(key as u64), &value, key).unwrap();
     }
 
     let checkpoint = store.checkpoint_hybrid_log();
@@ -0,0 +1,157 @@
+extern crate faster_rs;
+
+#[cfg(feature = "cbor")]
+use faster_rs::codec::Cbor;
+#[cfg(feature = "msgpack")]
+use faster_rs::codec::MessagePack;
+use faster_rs::codec::{Bincode, Codec, FixedWidth, Raw};
+use faster_rs::{FasterError, FasterKv, FasterKvBuilder, Status};
+use serde_derive::{Deserialize, Serialize};
+
+fn build<C>() -> FasterKv<C> {
+    FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .build_with_codec::<C>()
+        .unwrap()
+}
+
+#[test]
+fn fixed_width_encodes_little_endian() {
+    assert_eq!(
+        <FixedWidth as Codec<u32>>::encode(&0x0102_0304).unwrap(),
+        vec![4, 3, 2, 1]
+    );
+    let decoded: i64 = FixedWidth::decode(&(-7i64).to_le_bytes()).unwrap();
+    assert_eq!(decoded, -7);
+}
+
+#[test]
+fn fixed_width_rejects_wrong_length() {
+    match <FixedWidth as Codec<u64>>::decode(&[1, 2, 3]) {
+        Err(FasterError::CodecError(_)) => assert!(true),
+        _ => assert!(false, "Should give CodecError"),
+    }
+}
+
+#[test]
+fn fixed_width_store_upsert_rmw_read() {
+    let store = build::<FixedWidth>();
+    let key: u64 = 1;
+
+    store.upsert(&key, &40u64, 1).unwrap();
+    store.rmw(&key, &2u64, 2).unwrap();
+
+    let (res, recv) = store.read::<u64, u64>(&key, 3).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), 42);
+}
+
+#[test]
+fn raw_store_keeps_bytes() {
+    let store = build::<Raw>();
+    let key = b"key".to_vec();
+    let value = vec![0xde, 0xad, 0xbe, 0xef];
+
+    store.upsert(&key, &value, 1).unwrap();
+    let (res, recv) = store.read::<Vec<u8>, Vec<u8>>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), value);
+
+    let records: Vec<_> = store.scan(0, u64::max_value()).unwrap().collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].key, key);
+    assert_eq!(records[0].value, value);
+}
+
+#[test]
+fn raw_store_borrows_byte_slices() {
+    let store = build::<Raw>();
+    let frame = b"key=value";
+
+    store.upsert_bytes(&frame[..3], &frame[4..], 1).unwrap();
+    let (res, recv) = store.read::<Vec<u8>, Vec<u8>>(&b"key".to_vec(), 2).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), b"value");
+
+    store.delete_bytes(b"key", 3).unwrap();
+    let (res, _) = store.read_bytes(b"key", 4).unwrap();
+    assert_eq!(res, Status::NotFound);
+}
+
+// Neither Serialize nor Deserialize, only stored through its own codec
+#[derive(Debug, PartialEq)]
+struct Celsius(i16);
+
+struct Tenths;
+
+impl Codec<Celsius> for Tenths {
+    fn encode(value: &Celsius) -> Result<Vec<u8>, FasterError<'static>> {
+        Ok((value.0 * 10).to_le_bytes().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Celsius, FasterError<'static>> {
+        let tenths = <FixedWidth as Codec<i16>>::decode(bytes)?;
+        Ok(Celsius(tenths / 10))
+    }
+}
+
+#[test]
+fn store_with_codec_for_non_serde_types() {
+    let store = build::<Tenths>();
+    store.upsert(&Celsius(1), &Celsius(21), 1).unwrap();
+    let (res, recv) = store.read::<Celsius, Celsius>(&Celsius(1), 2).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), Celsius(21));
+}
+
+#[test]
+fn typed_store_with_codec() {
+    let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .build_typed_with_codec::<u32, f64, FixedWidth>()
+        .unwrap();
+
+    store.upsert(&7, &1.5, 1).unwrap();
+    store.rmw(&7, &2.0, 2).unwrap();
+    let (res, recv) = store.read(&7, 3).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), 3.5);
+
+    let pairs: Vec<(u32, f64)> = store.iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(pairs, vec![(7, 3.5)]);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn point_roundtrip<C>()
+where
+    C: Codec<String> + Codec<Point>,
+{
+    let store = build::<C>();
+    let key = String::from("origin");
+    let value = Point { x: 3, y: -4 };
+
+    store.upsert(&key, &value, 1).unwrap();
+    let (res, recv) = store.read::<String, Point>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), value);
+}
+
+#[test]
+fn bincode_store_roundtrip() {
+    point_roundtrip::<Bincode>();
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn msgpack_store_roundtrip() {
+    point_roundtrip::<MessagePack>();
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_store_roundtrip() {
+    point_roundtrip::<Cbor>();
+}
@@ -0,0 +1,169 @@
+extern crate faster_rs;
+
+use faster_rs::combinators::{
+    BoundedVec, Counter, HyperLogLog, Last, Max, Min, SaturatingAdd, TopK,
+};
+use faster_rs::{FasterError, FasterKv, FasterRmw, FasterValue};
+use std::collections::{BTreeSet, HashMap};
+
+// Applies each modification to the same key and reads back the result
+fn aggregate<V>(modifications: Vec<V>) -> V
+where
+    V: FasterRmw + FasterValue,
+{
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    let num_modifications = modifications.len() as u64;
+    for (serial, modification) in modifications.iter().enumerate() {
+        store.rmw(&key, modification, serial as u64).unwrap();
+    }
+    store.complete_pending(true).unwrap();
+    let (_, recv) = store.read::<u64, V>(&key, num_modifications).unwrap();
+    recv.recv().unwrap().unwrap()
+}
+
+#[test]
+fn max_min_last() {
+    assert_eq!(aggregate(vec![Max(3), Max(7), Max(5)]), Max(7));
+    assert_eq!(aggregate(vec![Min(3.5), Min(-1.0), Min(2.0)]), Min(-1.0));
+    assert_eq!(
+        aggregate(vec![Last("a".to_string()), Last("b".to_string())]),
+        Last("b".to_string())
+    );
+}
+
+#[test]
+fn counter_counts_events() {
+    let events = (0..100).map(|_| Counter::one()).collect();
+    assert_eq!(aggregate(events), Counter(100));
+    assert_eq!(aggregate(vec![Counter(u64::MAX), Counter(2)]), Counter(1));
+}
+
+#[test]
+fn saturating_add_clamps() {
+    assert_eq!(
+        aggregate(vec![SaturatingAdd(250u8), SaturatingAdd(10u8)]),
+        SaturatingAdd(255u8)
+    );
+    assert_eq!(
+        aggregate(vec![SaturatingAdd(i32::MIN), SaturatingAdd(-1)]),
+        SaturatingAdd(i32::MIN)
+    );
+}
+
+#[test]
+fn bounded_vec_keeps_most_recent() {
+    let modifications = (0..10u64).map(|i| BoundedVec::<u64, 3>(vec![i])).collect();
+    assert_eq!(aggregate(modifications), BoundedVec(vec![7, 8, 9]));
+    assert_eq!(
+        aggregate(vec![BoundedVec::<u64, 3>(vec![1, 2, 3, 4, 5])]),
+        BoundedVec(vec![1, 2, 3, 4, 5])
+    );
+    assert_eq!(
+        aggregate(vec![
+            BoundedVec::<u64, 3>(vec![1]),
+            BoundedVec(vec![2, 3, 4, 5])
+        ]),
+        BoundedVec(vec![3, 4, 5])
+    );
+}
+
+#[test]
+fn hash_map_merges_values() {
+    let mut first = HashMap::new();
+    first.insert("a".to_string(), 1u64);
+    first.insert("b".to_string(), 2u64);
+    let mut second = HashMap::new();
+    second.insert("b".to_string(), 3u64);
+    second.insert("c".to_string(), 4u64);
+
+    let merged = aggregate(vec![first, second]);
+    assert_eq!(merged.len(), 3);
+    assert_eq!(merged["a"], 1);
+    assert_eq!(merged["b"], 5);
+    assert_eq!(merged["c"], 4);
+}
+
+#[test]
+fn hash_map_of_combinators() {
+    let mut first = HashMap::new();
+    first.insert(1u64, Max(10u64));
+    let mut second = HashMap::new();
+    second.insert(1u64, Max(5u64));
+
+    assert_eq!(aggregate(vec![first, second])[&1], Max(10));
+}
+
+#[test]
+fn btree_set_union() {
+    let first: BTreeSet<u64> = (0..5).collect();
+    let second: BTreeSet<u64> = (3..8).collect();
+    assert_eq!(aggregate(vec![first, second]), (0..8).collect());
+}
+
+#[test]
+fn hyper_log_log_estimates_distinct_items() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    let num_distinct = 10_000u64;
+    for i in 0..num_distinct * 2 {
+        let item = i % num_distinct;
+        store.rmw(&key, &HyperLogLog::<12>::of(&item), i).unwrap();
+    }
+    store.complete_pending(true).unwrap();
+
+    let (_, recv) = store
+        .read::<u64, HyperLogLog<12>>(&key, num_distinct * 2)
+        .unwrap();
+    let estimate = recv.recv().unwrap().unwrap().estimate();
+    let error = (estimate - num_distinct as f64).abs() / num_distinct as f64;
+    assert!(error < 0.05, "Estimate {} is off by {}", estimate, error);
+}
+
+#[test]
+fn hyper_log_log_rejects_other_precision() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    let stored = HyperLogLog::<4>::of("a");
+    store.upsert(&key, &stored, 1).unwrap();
+
+    let result = store.rmw(&key, &HyperLogLog::<12>::of("b"), 2);
+    assert!(matches!(result, Err(FasterError::MergeError(_))));
+
+    let (_, recv) = store.read::<u64, HyperLogLog<4>>(&key, 3).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), stored);
+}
+
+#[test]
+fn hyper_log_log_empty() {
+    assert_eq!(HyperLogLog::<4>::new().estimate(), 0.0);
+}
+
+#[test]
+fn top_k_tracks_frequent_items() {
+    let mut modifications = Vec::new();
+    for i in 0..1000u64 {
+        // Every other occurrence is a heavy hitter, the rest are distinct
+        let item = match i % 2 {
+            0 => 0,
+            _ => i,
+        };
+        modifications.push(TopK::<u64, 3>::of(item));
+    }
+    let top = aggregate(modifications);
+    assert!(top.top().len() <= 3);
+    let (item, count) = top.top()[0];
+    assert_eq!(item, 0);
+    // Counts are lower bounds, short by at most n / (K + 1)
+    assert!((500 - 1000 / 4..=500).contains(&count));
+}
+
+#[test]
+fn top_k_with_counts() {
+    let top = aggregate(vec![
+        TopK::<char, 2>::with_count('a', 5),
+        TopK::with_count('b', 3),
+        TopK::with_count('a', 1),
+    ]);
+    assert_eq!(top.top(), &[('a', 6), ('b', 3)]);
+}
@@ -0,0 +1,81 @@
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::{FasterError, FasterKv, FasterKvBuilder};
+use tempfile::TempDir;
+
+fn disk_store(dir_path: &str) -> FasterKv {
+    // Small mutable region so that records quickly become eligible for compaction
+    FasterKvBuilder::new(1 << 14, 64 * 1024 * 1024)
+        .with_disk(dir_path)
+        .with_log_mutable_fraction(0.1)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn addresses_are_ordered() {
+    let store = FasterKv::default();
+    for key in 0..1000 {
+        store.upsert(&(key as u64), &key, key).unwrap();
+    }
+    assert!(store.begin_address() <= store.head_address());
+    assert!(store.head_address() <= store.read_only_address());
+    assert!(store.read_only_address() <= store.tail_address());
+}
+
+#[test]
+fn compaction_keeps_live_keys_and_drops_deleted_keys() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let store = disk_store(&dir_path);
+    let session = store.session().unwrap();
+    let num_keys: u64 = 1 << 16;
+
+    for key in 0..num_keys {
+        session.upsert(&key, &key, key).unwrap();
+    }
+    for key in (0..num_keys).filter(|key| key % 2 == 0) {
+        session.delete(&key, num_keys + key).unwrap();
+    }
+    // Push the records above into the read-only region
+    for key in num_keys..(num_keys * 4) {
+        session.upsert(&key, &key, key).unwrap();
+    }
+    session.complete_pending(true).unwrap();
+
+    let until_address = store.read_only_address();
+    store.compact(until_address).unwrap();
+    assert_eq!(store.begin_address(), until_address);
+
+    for key in 0..num_keys {
+        let (_status, recv) = session.read::<u64, u64>(&key, 4 * num_keys + key).unwrap();
+        session.complete_pending(true).unwrap();
+        if key % 2 == 0 {
+            assert!(recv.recv().is_err());
+        } else {
+            assert_eq!(recv.recv().unwrap().unwrap(), key);
+        }
+    }
+
+    let deleted = store
+        .scan(store.begin_address(), store.tail_address())
+        .unwrap()
+        .filter(|record| record.decode_key::<u64>().unwrap() < num_keys)
+        .filter(|record| record.decode_key::<u64>().unwrap() % 2 == 0)
+        .count();
+    assert_eq!(deleted, 0);
+}
+
+#[test]
+fn compaction_beyond_read_only_address_errors() {
+    let store = FasterKv::default();
+    for key in 0..1000 {
+        store.upsert(&(key as u64), &key, key).unwrap();
+    }
+
+    match store.compact(store.tail_address()) {
+        Err(FasterError::InvalidAddress(_)) => {}
+        _ => panic!("Should give InvalidAddress Error"),
+    }
+}
@@ -0,0 +1,114 @@
+extern crate faster_rs;
+
+use faster_rs::{FasterConditionalRmw, FasterKv, RmwAction, Status};
+use serde_derive::{Deserialize, Serialize};
+
+// Stock level that is removed once it runs out
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Stock(i64);
+
+impl FasterConditionalRmw for Stock {
+    fn conditional_rmw(&self, modification: Self) -> RmwAction<Self> {
+        match self.0 + modification.0 {
+            level if level <= 0 => RmwAction::Delete,
+            level => RmwAction::Update(Stock(level)),
+        }
+    }
+
+    fn initial(modification: Self) -> RmwAction<Self> {
+        match modification.0 {
+            level if level <= 0 => RmwAction::Keep,
+            _ => RmwAction::Update(modification),
+        }
+    }
+}
+
+// Largest value seen so far, left untouched by smaller samples
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct HighWaterMark(u64);
+
+impl FasterConditionalRmw for HighWaterMark {
+    fn conditional_rmw(&self, modification: Self) -> RmwAction<Self> {
+        match modification.0 > self.0 {
+            true => RmwAction::Update(modification),
+            false => RmwAction::Keep,
+        }
+    }
+}
+
+#[test]
+fn conditional_rmw_updates_value() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    assert_eq!(
+        store.rmw_conditional(&key, &Stock(5), 1).unwrap(),
+        Status::Ok
+    );
+    assert_eq!(
+        store.rmw_conditional(&key, &Stock(-2), 2).unwrap(),
+        Status::Ok
+    );
+
+    let (res, recv) = store.read::<u64, Stock>(&key, 3).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), Stock(3));
+}
+
+#[test]
+fn conditional_rmw_deletes_value() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    store.rmw_conditional(&key, &Stock(2), 1).unwrap();
+    store.rmw_conditional(&key, &Stock(-2), 2).unwrap();
+
+    let (res, _) = store.read::<u64, Stock>(&key, 3).unwrap();
+    assert_eq!(res, Status::NotFound);
+
+    // A deleted key starts again from its initial action
+    store.rmw_conditional(&key, &Stock(4), 4).unwrap();
+    let (_, recv) = store.read::<u64, Stock>(&key, 5).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), Stock(4));
+}
+
+#[test]
+fn conditional_rmw_keeps_value() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    for (serial, sample) in [3u64, 7, 5].iter().enumerate() {
+        store
+            .rmw_conditional(&key, &HighWaterMark(*sample), serial as u64)
+            .unwrap();
+    }
+
+    let (_, recv) = store.read::<u64, HighWaterMark>(&key, 4).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), HighWaterMark(7));
+}
+
+#[test]
+fn conditional_rmw_missing_key_kept_absent() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    let status = store.rmw_conditional(&key, &Stock(-1), 1).unwrap();
+    assert_eq!(status, Status::NotFound);
+
+    let (res, _) = store.read::<u64, Stock>(&key, 2).unwrap();
+    assert_eq!(res, Status::NotFound);
+}
+
+#[test]
+fn conditional_rmw_in_session() {
+    let store = FasterKv::default();
+    let session = store.session().unwrap();
+    let key: u64 = 1;
+
+    session.rmw_conditional(&key, &Stock(1), 1).unwrap();
+    session.rmw_conditional(&key, &Stock(-1), 2).unwrap();
+    session.complete_pending(true).unwrap();
+
+    let (res, _) = session.read::<u64, Stock>(&key, 3).unwrap();
+    assert_eq!(res, Status::NotFound);
+}
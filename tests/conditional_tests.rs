@@ -0,0 +1,147 @@
+extern crate faster_rs;
+
+use faster_rs::{FasterKv, FasterKvBuilder, Status};
+use std::sync::Arc;
+use std::thread;
+use tempfile::TempDir;
+
+#[test]
+fn compare_and_swap_replaces_matching_value() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    store.upsert(&key, &String::from("old"), 1).unwrap();
+
+    let swapped = store
+        .compare_and_swap(&key, &String::from("old"), &String::from("new"), 2)
+        .unwrap();
+    assert!(swapped);
+
+    let (res, recv) = store.read::<u64, String>(&key, 3).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), "new");
+}
+
+#[test]
+fn compare_and_swap_keeps_mismatching_value() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    store.upsert(&key, &10u64, 1).unwrap();
+
+    assert!(!store.compare_and_swap(&key, &11u64, &12u64, 2).unwrap());
+
+    let (_, recv) = store.read::<u64, u64>(&key, 3).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), 10);
+}
+
+#[test]
+fn compare_and_swap_missing_key() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    assert!(!store.compare_and_swap(&key, &0u64, &1u64, 1).unwrap());
+
+    let (res, recv) = store.read::<u64, u64>(&key, 2).unwrap();
+    assert_eq!(res, Status::NotFound);
+    assert!(recv.recv().is_err());
+}
+
+#[test]
+fn upsert_if_absent_only_inserts_once() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    assert!(store.upsert_if_absent(&key, &10u64, 1).unwrap());
+    assert!(!store.upsert_if_absent(&key, &20u64, 2).unwrap());
+
+    let (_, recv) = store.read::<u64, u64>(&key, 3).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), 10);
+}
+
+#[test]
+fn upsert_if_absent_after_delete() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    store.upsert(&key, &10u64, 1).unwrap();
+    store.delete(&key, 2).unwrap();
+
+    assert!(store.upsert_if_absent(&key, &20u64, 3).unwrap());
+
+    let (_, recv) = store.read::<u64, u64>(&key, 4).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), 20);
+}
+
+#[test]
+fn conditional_writes_on_disk() {
+    let tmp_dir = TempDir::new().unwrap();
+    let store = FasterKvBuilder::new(1 << 14, 64 * 1024 * 1024)
+        .with_disk(&tmp_dir.path().to_string_lossy())
+        .build()
+        .unwrap();
+    let session = store.session().unwrap();
+    let num_keys: u64 = 1 << 19;
+    for key in 0..num_keys {
+        session.upsert(&key, &vec![key; 16], key).unwrap();
+    }
+    session.complete_pending(true).unwrap();
+
+    // Another pending operation of the session does not hold up the conditional writes
+    let (status, recv) = session.read::<u64, Vec<u64>>(&1, num_keys).unwrap();
+    assert_eq!(status, Status::Pending);
+    assert!(session
+        .compare_and_swap(&0, &vec![0u64; 16], &vec![7u64], num_keys + 1)
+        .unwrap());
+    assert!(!session
+        .compare_and_swap(&2, &vec![0u64; 16], &vec![7u64], num_keys + 2)
+        .unwrap());
+    assert!(!session
+        .upsert_if_absent(&3, &vec![7u64], num_keys + 3)
+        .unwrap());
+    session.complete_pending(true).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), vec![1u64; 16]);
+
+    for (key, expected) in &[(0, vec![7u64]), (2, vec![2u64; 16]), (3, vec![3u64; 16])] {
+        let (_, recv) = session.read::<u64, Vec<u64>>(key, num_keys + 4).unwrap();
+        session.complete_pending(true).unwrap();
+        assert_eq!(&recv.recv().unwrap().unwrap(), expected);
+    }
+}
+
+#[test]
+fn concurrent_compare_and_swap_increments() {
+    let store = Arc::new(FasterKv::default());
+    let key: u64 = 1;
+    let num_threads = 4;
+    let increments_per_thread = 1000;
+    store.upsert(&key, &0u64, 1).unwrap();
+
+    let mut threads = vec![];
+    for _ in 0..num_threads {
+        let store = Arc::clone(&store);
+        threads.push(thread::spawn(move || {
+            let session = store.session().unwrap();
+            let mut serial = 1;
+            for _ in 0..increments_per_thread {
+                loop {
+                    let (_, recv) = session.read::<u64, u64>(&key, serial).unwrap();
+                    let current = recv.recv().unwrap().unwrap();
+                    serial += 1;
+                    if session
+                        .compare_and_swap(&key, &current, &(current + 1), serial)
+                        .unwrap()
+                    {
+                        break;
+                    }
+                }
+            }
+        }));
+    }
+    for t in threads {
+        t.join().expect("Something went wrong in a thread");
+    }
+
+    let (_, recv) = store.read::<u64, u64>(&key, 1).unwrap();
+    assert_eq!(
+        recv.recv().unwrap().unwrap(),
+        num_threads * increments_per_thread
+    );
+}
@@ -0,0 +1,147 @@
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::{FasterKv, FasterKvBuilder, ReadWith};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use tempfile::TempDir;
+
+// Counts the bytes allocated through Rust on each thread, so tests running in parallel do not
+// see each other's allocations. FASTER's own allocations bypass the Rust allocator.
+struct CountingAllocator;
+
+thread_local! {
+    static LIVE_BYTES: Cell<isize> = Cell::new(0);
+}
+
+fn track(delta: isize) {
+    let _ = LIVE_BYTES.try_with(|live| live.set(live.get() + delta));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        track(layout.size() as isize);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        track(-(layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn live_bytes() -> isize {
+    LIVE_BYTES.with(|live| live.get())
+}
+
+// Runs `operations` once to warm up lazily initialised state, then asserts that running it
+// again does not leave any memory behind
+fn assert_no_leaks<F: FnMut()>(mut operations: F) {
+    operations();
+    let before = live_bytes();
+    for _ in 0..10 {
+        operations();
+    }
+    assert_eq!(live_bytes() - before, 0, "Operations leaked memory");
+}
+
+#[test]
+fn upsert_does_not_leak() {
+    let store = FasterKv::default();
+    assert_no_leaks(|| {
+        for key in 0..1000u64 {
+            store.upsert(&key, &format!("value {}", key), key).unwrap();
+        }
+    });
+}
+
+#[test]
+fn read_does_not_leak() {
+    let store = FasterKv::default();
+    for key in 0..1000u64 {
+        store.upsert(&key, &key, key).unwrap();
+    }
+    assert_no_leaks(|| {
+        for key in 0..1000u64 {
+            let (_, recv) = store.read::<u64, u64>(&key, key).unwrap();
+            assert_eq!(recv.recv().unwrap().unwrap(), key);
+        }
+    });
+}
+
+#[test]
+fn read_missing_key_does_not_leak() {
+    let store = FasterKv::default();
+    assert_no_leaks(|| {
+        for key in 0..1000u64 {
+            let (_, recv) = store.read::<u64, u64>(&key, key).unwrap();
+            assert!(recv.recv().is_err());
+        }
+    });
+}
+
+#[test]
+fn read_with_does_not_leak() {
+    let store = FasterKv::default();
+    for key in 0..500u64 {
+        store.upsert(&key, &key, key).unwrap();
+    }
+    assert_no_leaks(|| {
+        for key in 0..1000u64 {
+            match store.read_with(&key, key, |bytes| bytes.len()).unwrap() {
+                ReadWith::Found(length) => assert_eq!(length, 8),
+                ReadWith::NotFound => assert!(key >= 500),
+                ReadWith::Pending(_) => panic!("In-memory read should not go pending"),
+            }
+        }
+    });
+}
+
+#[test]
+fn rmw_does_not_leak() {
+    let store = FasterKv::default();
+    assert_no_leaks(|| {
+        for key in 0..1000u64 {
+            store.rmw(&(key % 10), &1u64, key).unwrap();
+        }
+        store.complete_pending(true).unwrap();
+    });
+}
+
+#[test]
+fn delete_does_not_leak() {
+    let store = FasterKv::default();
+    assert_no_leaks(|| {
+        for key in 0..1000u64 {
+            store.upsert(&key, &key, key).unwrap();
+            store.delete(&key, key).unwrap();
+        }
+    });
+}
+
+#[test]
+fn pending_reads_do_not_leak() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let store = FasterKvBuilder::new(1 << 14, 64 * 1024 * 1024)
+        .with_disk(&dir_path)
+        .build()
+        .unwrap();
+    let session = store.session().unwrap();
+    let num_keys: u64 = 1 << 18;
+    for key in 0..num_keys {
+        session.upsert(&key, &key, key).unwrap();
+    }
+    session.complete_pending(true).unwrap();
+
+    assert_no_leaks(|| {
+        for key in (0..num_keys).step_by(1024) {
+            let (_, recv) = session.read::<u64, u64>(&key, num_keys + key).unwrap();
+            session.complete_pending(true).unwrap();
+            assert_eq!(recv.recv().unwrap().unwrap(), key);
+        }
+    });
+}
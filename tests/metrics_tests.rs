@@ -0,0 +1,124 @@
+#![cfg(feature = "metrics")]
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::{FasterKv, FasterKvBuilder, Status};
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+// Parses the samples of a Prometheus text exposition into a map from series to value,
+// checking that every sample belongs to a declared metric family
+fn scrape(rendered: &str) -> HashMap<String, f64> {
+    let mut families = Vec::new();
+    let mut samples = HashMap::new();
+    for line in rendered.lines() {
+        if let Some(declaration) = line.strip_prefix("# TYPE ") {
+            let mut parts = declaration.split(' ');
+            families.push(parts.next().unwrap().to_string());
+            let kind = parts.next().unwrap();
+            assert!(["counter", "gauge", "histogram"].contains(&kind));
+        } else if line.starts_with("# HELP ") {
+            continue;
+        } else {
+            let split = line.rfind(' ').expect("Sample without a value");
+            let (series, value) = line.split_at(split);
+            let name = series.split('{').next().unwrap();
+            assert!(
+                families
+                    .iter()
+                    .any(|family| name.starts_with(family.as_str())),
+                "Sample {} has no TYPE line",
+                name
+            );
+            let value: f64 = value.trim().parse().expect("Sample value is not a number");
+            assert!(samples.insert(series.to_string(), value).is_none());
+        }
+    }
+    samples
+}
+
+#[test]
+fn metrics_count_operations_by_outcome() {
+    let store = FasterKv::default();
+    for key in 0..10u64 {
+        store.upsert(&key, &key, key).unwrap();
+    }
+    for key in 0..15u64 {
+        store.read::<u64, u64>(&key, key).unwrap();
+    }
+    store.rmw(&1u64, &1u64, 1).unwrap();
+    store.delete(&1u64, 1).unwrap();
+    store.complete_pending(true).unwrap();
+
+    let samples = scrape(&store.render_metrics());
+    let count = |operation: &str, outcome: &str| {
+        samples[&format!(
+            "faster_operations_total{{operation=\"{}\",outcome=\"{}\"}}",
+            operation, outcome
+        )]
+    };
+    assert_eq!(count("upsert", "ok"), 10.0);
+    assert_eq!(count("read", "ok"), 10.0);
+    assert_eq!(count("read", "not_found"), 5.0);
+    assert_eq!(count("rmw", "ok"), 1.0);
+    assert_eq!(count("delete", "ok"), 1.0);
+    assert_eq!(count("complete_pending", "ok"), 1.0);
+    assert_eq!(count("checkpoint", "ok"), 0.0);
+}
+
+#[test]
+fn metrics_record_latency_histograms() {
+    let store = FasterKv::default();
+    for key in 0..100u64 {
+        store.upsert(&key, &key, key).unwrap();
+    }
+
+    let samples = scrape(&store.render_metrics());
+    let series = |suffix: &str| samples[&format!("faster_operation_duration_seconds_{}", suffix)];
+    assert_eq!(series("count{operation=\"upsert\"}"), 100.0);
+    assert_eq!(series("bucket{operation=\"upsert\",le=\"+Inf\"}"), 100.0);
+    assert!(series("sum{operation=\"upsert\"}") > 0.0);
+    // Buckets are cumulative
+    assert!(
+        series("bucket{operation=\"upsert\",le=\"0.00001\"}")
+            <= series("bucket{operation=\"upsert\",le=\"0.001\"}")
+    );
+    assert_eq!(series("count{operation=\"read\"}"), 0.0);
+}
+
+#[test]
+fn metrics_track_checkpoints_and_log_growth() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .with_disk(&dir_path)
+        .build()
+        .unwrap();
+    let before = scrape(&store.render_metrics())["faster_log_size_bytes"];
+
+    for key in 0..1000u64 {
+        store.upsert(&key, &key, key).unwrap();
+    }
+    let checkpoint = store.checkpoint().unwrap();
+    assert!(checkpoint.checked);
+    // The checkpoint is recorded once it is durable
+    while store.checkpoint_status(&checkpoint.token).unwrap() == Status::Pending {
+        store.complete_pending(false).unwrap();
+        store.refresh();
+    }
+
+    let samples = scrape(&store.render_metrics());
+    assert!(samples["faster_log_size_bytes"] > before);
+    assert_eq!(
+        samples["faster_log_address{region=\"tail\"}"],
+        store.tail_address() as f64
+    );
+    assert_eq!(
+        samples["faster_operations_total{operation=\"checkpoint\",outcome=\"ok\"}"],
+        1.0
+    );
+    assert_eq!(
+        samples["faster_operation_duration_seconds_count{operation=\"checkpoint\"}"],
+        1.0
+    );
+}
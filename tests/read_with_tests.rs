@@ -0,0 +1,87 @@
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::codec::FixedWidth;
+use faster_rs::{FasterError, FasterKv, FasterKvBuilder, ReadWith};
+use serde_derive::{Deserialize, Serialize};
+use tempfile::TempDir;
+
+#[derive(Serialize, Deserialize)]
+struct Reading {
+    sensor: u32,
+    samples: Vec<f64>,
+}
+
+#[test]
+fn read_with_borrows_value_bytes() {
+    let store = FasterKv::default();
+    let value = Reading {
+        sensor: 7,
+        samples: vec![1.0, 2.0, 3.0],
+    };
+    store.upsert(&1u64, &value, 1).unwrap();
+
+    // The sensor id is the first field, so it can be read without decoding the samples
+    let sensor = store
+        .read_with(&1u64, 1, |bytes| {
+            let mut sensor = [0; 4];
+            sensor.copy_from_slice(&bytes[..4]);
+            u32::from_le_bytes(sensor)
+        })
+        .unwrap();
+    match sensor {
+        ReadWith::Found(sensor) => assert_eq!(sensor, 7),
+        _ => assert!(false, "Value should be found in memory"),
+    }
+}
+
+#[test]
+fn read_with_missing_key() {
+    let store = FasterKv::default();
+    match store.read_with(&1u64, 1, |bytes| bytes.len()).unwrap() {
+        ReadWith::NotFound => assert!(true),
+        _ => assert!(false, "Should not find a value"),
+    }
+}
+
+#[test]
+fn read_with_reader_panic_returns_error() {
+    let store = FasterKv::default();
+    store.upsert(&1u64, &1u64, 1).unwrap();
+    match store.read_with(&1u64, 1, |_| -> u64 { panic!("reader failed") }) {
+        Err(FasterError::CallbackPanic) => assert!(true),
+        _ => assert!(false, "Should give CallbackPanic Error"),
+    }
+}
+
+#[test]
+fn read_with_from_disk() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let store = FasterKvBuilder::new(1 << 14, 64 * 1024 * 1024)
+        .with_disk(&dir_path)
+        .build_with_codec::<FixedWidth>()
+        .unwrap();
+    let session = store.session().unwrap();
+    let num_keys: u64 = 1 << 18;
+
+    for key in 0..num_keys {
+        session.upsert(&key, &(key * 3), key).unwrap();
+    }
+    session.complete_pending(true).unwrap();
+
+    for key in (0..num_keys).step_by(1024) {
+        let read = session
+            .read_with(&key, num_keys + key, |bytes| bytes.to_vec())
+            .unwrap();
+        let bytes = match read {
+            ReadWith::Found(bytes) => bytes,
+            ReadWith::Pending(recv) => {
+                session.complete_pending(true).unwrap();
+                recv.recv().unwrap().unwrap()
+            }
+            ReadWith::NotFound => panic!("Key {} should exist", key),
+        };
+        assert_eq!(bytes, (key * 3).to_le_bytes().to_vec());
+    }
+}
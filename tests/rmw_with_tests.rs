@@ -0,0 +1,100 @@
+extern crate faster_rs;
+
+use faster_rs::{FasterError, FasterKv, Status};
+
+#[test]
+fn rmw_with_uses_initial_for_missing_key() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    let rmw = store
+        .rmw_with(&key, || vec![1u64], |current: &Vec<u64>| current.clone(), 1)
+        .unwrap();
+    assert_eq!(rmw, Status::Ok);
+
+    let (res, recv) = store.read::<u64, Vec<u64>>(&key, 2).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), vec![1]);
+}
+
+#[test]
+fn rmw_with_different_updates_on_same_type() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    store.upsert(&key, &vec![1u64, 2, 3], 1).unwrap();
+
+    // Append at one call site, drop the oldest element at another
+    store
+        .rmw_with(
+            &key,
+            Vec::new,
+            |current: &Vec<u64>| {
+                let mut next = current.clone();
+                next.push(4);
+                next
+            },
+            2,
+        )
+        .unwrap();
+    store
+        .rmw_with(
+            &key,
+            Vec::new,
+            |current: &Vec<u64>| current[1..].to_vec(),
+            3,
+        )
+        .unwrap();
+
+    let (_, recv) = store.read::<u64, Vec<u64>>(&key, 4).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), vec![2, 3, 4]);
+}
+
+#[test]
+fn rmw_with_borrows_from_caller() {
+    let store = FasterKv::default();
+    let key = String::from("total");
+    let amounts = vec![5u64, 10, 20];
+
+    for amount in &amounts {
+        store
+            .rmw_with(&key, || *amount, |current| current + amount, 1)
+            .unwrap();
+    }
+
+    let (_, recv) = store.read::<String, u64>(&key, 2).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), 35);
+}
+
+#[test]
+fn rmw_with_update_panic_returns_error() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    store.upsert(&key, &10u64, 1).unwrap();
+
+    match store.rmw_with(&key, || 0u64, |_| panic!("update failed"), 2) {
+        Err(FasterError::CallbackPanic) => assert!(true),
+        _ => assert!(false, "Should give CallbackPanic Error"),
+    }
+
+    let (_, recv) = store.read::<u64, u64>(&key, 3).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), 10);
+}
+
+#[test]
+fn rmw_with_initial_panic_leaves_key_absent() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+
+    let result = store.rmw_with(&key, || -> u64 { panic!("initial failed") }, |c| *c, 1);
+    assert!(matches!(result, Err(FasterError::CallbackPanic)));
+
+    let (res, _) = store.read::<u64, u64>(&key, 2).unwrap();
+    assert_eq!(res, Status::NotFound);
+
+    // The key can still be created afterwards
+    store
+        .rmw_with(&key, || 5u64, |current| *current, 3)
+        .unwrap();
+    let (_, recv) = store.read::<u64, u64>(&key, 4).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), 5);
+}
@@ -0,0 +1,76 @@
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::{FasterKv, FasterKvBuilder};
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+#[test]
+fn scan_returns_all_records() {
+    let store = FasterKv::default();
+    for key in 0..100 {
+        store.upsert(&(key as u64), &(key * 2), key).unwrap();
+    }
+    store.delete(&(0 as u64), 100).unwrap();
+
+    let records: Vec<_> = store.scan(0, u64::max_value()).unwrap().collect();
+    assert_eq!(records.len(), 101);
+    assert_eq!(records.iter().filter(|record| record.tombstone).count(), 1);
+    assert!(records
+        .windows(2)
+        .all(|pair| pair[0].address < pair[1].address));
+}
+
+#[test]
+fn scan_skips_tombstones() {
+    let store = FasterKv::default();
+    for key in 0..100 {
+        store.upsert(&(key as u64), &(key * 2), key).unwrap();
+    }
+    store.delete(&(0 as u64), 100).unwrap();
+
+    let scan = store.scan(0, u64::max_value()).unwrap().skip_tombstones();
+    assert_eq!(scan.count(), 100);
+}
+
+#[test]
+fn iter_returns_live_values() {
+    let store = FasterKv::default();
+    for key in 0..100 {
+        store.upsert(&(key as u64), &(key * 2), key).unwrap();
+    }
+    store.delete(&(0 as u64), 100).unwrap();
+
+    let pairs: HashMap<u64, u64> = store.iter().unwrap().map(Result::unwrap).collect();
+    assert_eq!(pairs.len(), 99);
+    assert!(!pairs.contains_key(&0));
+    for key in 1..100 {
+        assert_eq!(pairs[&key], key * 2);
+    }
+}
+
+#[test]
+fn iter_reads_records_from_disk() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    // Small log with a low mutable fraction so most records end up on disk
+    let store = FasterKvBuilder::new(1 << 14, 64 * 1024 * 1024)
+        .with_disk(&dir_path)
+        .with_log_mutable_fraction(0.1)
+        .build()
+        .unwrap();
+    let session = store.session().unwrap();
+    let num_keys: u64 = 1 << 20;
+    for key in 0..num_keys {
+        session.upsert(&key, &key, key).unwrap();
+    }
+    session.complete_pending(true).unwrap();
+
+    let mut count = 0;
+    for pair in store.iter::<u64, u64>().unwrap() {
+        let (key, value) = pair.unwrap();
+        assert_eq!(key, value);
+        count += 1;
+    }
+    assert_eq!(count, num_keys);
+}
@@ -0,0 +1,230 @@
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::{FasterError, FasterKv, FasterKvBuilder, TtlPolicy, TypedFasterKv};
+use serde_derive::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct User {
+    name: String,
+    city: String,
+}
+
+fn user(name: &str, city: &str) -> User {
+    User {
+        name: name.to_string(),
+        city: city.to_string(),
+    }
+}
+
+fn sorted<K: Ord>(mut keys: Vec<K>) -> Vec<K> {
+    keys.sort();
+    keys
+}
+
+#[test]
+fn index_follows_upserts_and_deletes() {
+    let store: TypedFasterKv<u64, User> = TypedFasterKv::default();
+    let by_city = store
+        .register_index("city", |user: &User| user.city.clone(), 1)
+        .unwrap();
+    let london = "London".to_string();
+
+    store.upsert(&1, &user("Ada", "London"), 2).unwrap();
+    store.upsert(&2, &user("Alan", "London"), 3).unwrap();
+    store.upsert(&3, &user("Grace", "New York"), 4).unwrap();
+    assert_eq!(sorted(by_city.lookup(&london).unwrap()), vec![1, 2]);
+    assert_eq!(by_city.lookup(&"New York".to_string()).unwrap(), vec![3]);
+
+    store.upsert(&2, &user("Alan", "Manchester"), 7).unwrap();
+    assert_eq!(by_city.lookup(&london).unwrap(), vec![1]);
+    assert_eq!(by_city.lookup(&"Manchester".to_string()).unwrap(), vec![2]);
+
+    store.delete(&1, 10).unwrap();
+    assert!(by_city.lookup(&london).unwrap().is_empty());
+    assert_eq!(by_city.len().unwrap(), 2);
+}
+
+#[test]
+fn index_follows_rmw() {
+    let store: TypedFasterKv<u64, u64> = TypedFasterKv::default();
+    let by_parity = store
+        .register_index("parity", |value: &u64| value % 2, 1)
+        .unwrap();
+
+    store.upsert(&1, &2, 2).unwrap();
+    assert_eq!(by_parity.lookup(&0).unwrap(), vec![1]);
+
+    store.rmw(&1, &3, 4).unwrap();
+    assert!(by_parity.lookup(&0).unwrap().is_empty());
+    assert_eq!(by_parity.lookup(&1).unwrap(), vec![1]);
+
+    // Missing keys are created with the modification
+    store.rmw(&2, &4, 7).unwrap();
+    assert_eq!(by_parity.lookup(&0).unwrap(), vec![2]);
+
+    store.rmw_batch(vec![(&1, &1), (&2, &1)], 9).unwrap();
+    assert_eq!(by_parity.lookup(&0).unwrap(), vec![1]);
+    assert_eq!(by_parity.lookup(&1).unwrap(), vec![2]);
+
+    store
+        .rmw_with(&3, || 1, |value: &u64| value + 1, 13)
+        .unwrap();
+    store
+        .rmw_with(&3, || 1, |value: &u64| value + 1, 14)
+        .unwrap();
+    assert_eq!(sorted(by_parity.lookup(&0).unwrap()), vec![1, 3]);
+}
+
+#[test]
+fn index_follows_batch_and_conditional_writes() {
+    let store: TypedFasterKv<u64, u64> = TypedFasterKv::default();
+    let by_value = store
+        .register_index("value", |value: &u64| *value, 1)
+        .unwrap();
+
+    store
+        .upsert_batch(vec![(&1, &10), (&2, &10), (&3, &30)], 2)
+        .unwrap();
+    assert_eq!(sorted(by_value.lookup(&10).unwrap()), vec![1, 2]);
+
+    assert!(store.compare_and_swap(&2, &10, &20, 6).unwrap());
+    assert!(!store.compare_and_swap(&3, &10, &20, 7).unwrap());
+    assert_eq!(by_value.lookup(&20).unwrap(), vec![2]);
+    assert_eq!(by_value.lookup(&30).unwrap(), vec![3]);
+
+    assert!(store.upsert_if_absent(&4, &40, 10).unwrap());
+    assert!(!store.upsert_if_absent(&1, &40, 11).unwrap());
+    assert_eq!(by_value.lookup(&40).unwrap(), vec![4]);
+    assert_eq!(by_value.lookup(&10).unwrap(), vec![1]);
+}
+
+#[test]
+fn index_follows_writes_through_the_store_and_its_sessions() {
+    let store = FasterKv::default();
+    let by_value = store
+        .register_index::<u64, u64, _, _>("value", |value| *value, 1)
+        .unwrap();
+
+    store.upsert(&1u64, &10u64, 2).unwrap();
+    {
+        let session = store.session().unwrap();
+        session.upsert(&2u64, &10u64, 3).unwrap();
+        session.rmw(&1u64, &5u64, 4).unwrap();
+        session
+            .upsert_batch(vec![(&3u64, &15u64), (&4u64, &10u64)], 5)
+            .unwrap();
+        session.delete(&4u64, 7).unwrap();
+    }
+    assert_eq!(by_value.lookup(&10).unwrap(), vec![2]);
+    assert_eq!(sorted(by_value.lookup(&15).unwrap()), vec![1, 3]);
+
+    // Records of the index are not records of the store
+    assert_eq!(store.iter::<u64, u64>().unwrap().count(), 3);
+}
+
+#[test]
+fn index_leaves_out_expired_records() {
+    let store: TypedFasterKv<u64, u64> = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .with_ttl(TtlPolicy::Preserve)
+        .build_typed()
+        .unwrap();
+    let by_value = store
+        .register_index("value", |value: &u64| *value, 1)
+        .unwrap();
+
+    store.upsert(&1, &10, 2).unwrap();
+    store
+        .upsert_with_ttl(&2, &10, Duration::from_millis(10), 3)
+        .unwrap();
+    assert_eq!(sorted(by_value.lookup(&10).unwrap()), vec![1, 2]);
+
+    std::thread::sleep(Duration::from_millis(20));
+    assert_eq!(by_value.lookup(&10).unwrap(), vec![1]);
+
+    // A Read-Modify-Write treats the expired record as missing
+    store.rmw(&2, &20, 6).unwrap();
+    assert_eq!(by_value.lookup(&20).unwrap(), vec![2]);
+    assert_eq!(by_value.lookup(&10).unwrap(), vec![1]);
+}
+
+#[test]
+fn index_registered_late_covers_existing_records() {
+    let store: TypedFasterKv<u64, User> = TypedFasterKv::default();
+    store.upsert(&1, &user("Ada", "London"), 1).unwrap();
+    store.upsert(&2, &user("Grace", "New York"), 2).unwrap();
+    store.upsert(&1, &user("Ada", "Paris"), 3).unwrap();
+    store.delete(&2, 4).unwrap();
+
+    let by_city = store
+        .register_index("city", |user: &User| user.city.clone(), 5)
+        .unwrap();
+    assert!(by_city.lookup(&"London".to_string()).unwrap().is_empty());
+    assert!(by_city.lookup(&"New York".to_string()).unwrap().is_empty());
+    assert_eq!(by_city.lookup(&"Paris".to_string()).unwrap(), vec![1]);
+}
+
+#[test]
+fn index_names_are_checked() {
+    let store: TypedFasterKv<u64, u64> = TypedFasterKv::default();
+    store
+        .register_index("value", |value: &u64| *value, 1)
+        .unwrap();
+
+    match store.register_index("value", |value: &u64| *value, 2) {
+        Err(FasterError::DuplicateIndex(name)) => assert_eq!(name, "value"),
+        _ => panic!("Should give DuplicateIndex Error"),
+    }
+    match store.register_index("../value", |value: &u64| *value, 3) {
+        Err(FasterError::InvalidIndexName(_)) => {}
+        _ => panic!("Should give InvalidIndexName Error"),
+    }
+}
+
+#[test]
+fn index_is_recovered_as_of_the_checkpoint() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let mut builder = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024);
+    builder.with_disk(&dir_path);
+
+    let session_id = {
+        let store = builder.build_typed::<u64, User>().unwrap();
+        let session_id = store.start_session().unwrap();
+        let by_city = store
+            .register_index("city", |user: &User| user.city.clone(), 1)
+            .unwrap();
+        store.upsert(&1, &user("Ada", "London"), 2).unwrap();
+        store.upsert(&2, &user("Grace", "New York"), 3).unwrap();
+        store.upsert(&3, &user("Alan", "London"), 4).unwrap();
+        assert_eq!(by_city.len().unwrap(), 3);
+
+        store.checkpoint().unwrap();
+        let start = Instant::now();
+        while store.latest_checkpoint().unwrap().is_none()
+            && start.elapsed() < Duration::from_secs(30)
+        {
+            store.complete_pending(true).unwrap();
+            store.refresh();
+        }
+        // Not part of the checkpoint, neither in the store nor in the index
+        store.upsert(&1, &user("Ada", "Paris"), 6).unwrap();
+        store.stop_session();
+        session_id
+    };
+
+    let (store, _) = builder.recover_latest_typed::<u64, User>().unwrap();
+    let serial_number = store.continue_session(session_id).unwrap();
+    let by_city = store
+        .register_index("city", |user: &User| user.city.clone(), serial_number + 1)
+        .unwrap();
+    assert_eq!(
+        sorted(by_city.lookup(&"London".to_string()).unwrap()),
+        vec![1, 3]
+    );
+    assert!(by_city.lookup(&"Paris".to_string()).unwrap().is_empty());
+    assert_eq!(by_city.lookup(&"New York".to_string()).unwrap(), vec![2]);
+    store.stop_session();
+}
@@ -0,0 +1,86 @@
+extern crate faster_rs;
+
+use faster_rs::{FasterError, FasterKv, SessionId, Status};
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn session_id_is_guid() {
+    let store = FasterKv::default();
+    let session = store.session().unwrap();
+    assert_eq!(session.id().as_str().len(), 37 - 1); // -1 \0
+}
+
+#[test]
+fn session_id_parses() {
+    let id: SessionId = "936A1DB2-5c4f-4f4b-a6ef-8e1a6b2f1e6d".parse().unwrap();
+    assert_eq!(id.as_str(), "936a1db2-5c4f-4f4b-a6ef-8e1a6b2f1e6d");
+
+    for invalid in &[
+        "",
+        "936a1db2",
+        "936a1db2_5c4f_4f4b_a6ef_8e1a6b2f1e6d",
+        "936a1db2-5c4f-4f4b-a6ef-8e1a6b2f1e6g",
+    ] {
+        match invalid.parse::<SessionId>() {
+            Err(FasterError::InvalidSessionId(id)) => assert_eq!(&id, invalid),
+            _ => assert!(false, "Should give InvalidSessionId Error"),
+        }
+    }
+}
+
+#[test]
+fn session_can_restart_after_drop() {
+    let store = FasterKv::default();
+    let key: u64 = 1;
+    let value: u64 = 1337;
+
+    {
+        let session = store.session().unwrap();
+        session.upsert(&key, &value, 1).unwrap();
+    }
+
+    let session = store.session_with_refresh_interval(1).unwrap();
+    let (res, recv) = session.read::<u64, u64>(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), value);
+}
+
+#[test]
+fn multi_threaded_sessions() {
+    let store = Arc::new(FasterKv::default());
+    let ops = 1 << 15;
+
+    let initial_value: u64 = 100;
+    let modification: u64 = 30;
+    {
+        let session = store.session().unwrap();
+        for key in 0..ops {
+            session.upsert(&(key as u64), &initial_value, key).unwrap();
+        }
+    }
+
+    let num_threads = 16;
+    let mut threads = vec![];
+    for _ in 0..num_threads {
+        let store = Arc::clone(&store);
+        threads.push(thread::spawn(move || {
+            // Pending operations are completed when the session is dropped
+            let session = store.session().unwrap();
+            for key in 0..ops {
+                session.rmw(&(key as u64), &modification, key).unwrap();
+            }
+        }))
+    }
+
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    let session = store.session().unwrap();
+    for key in 0..ops {
+        let expected_value = initial_value + (modification * num_threads);
+        let (_res, recv) = session.read::<u64, u64>(&key, ops + key).unwrap();
+        assert_eq!(recv.recv().unwrap().unwrap(), expected_value);
+    }
+}
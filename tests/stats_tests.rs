@@ -0,0 +1,44 @@
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::{FasterKv, FasterKvBuilder};
+use tempfile::TempDir;
+
+#[test]
+fn stats_of_empty_store() {
+    let store = FasterKv::default();
+    let stats = store.stats();
+
+    assert_eq!(stats.table_size, 1 << 15);
+    assert_eq!(stats.tail_address, store.tail_address());
+    assert_eq!(stats.on_disk_bytes, 0);
+    assert_eq!(stats.active_sessions, 0);
+}
+
+#[test]
+fn stats_report_log_layout() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let store = FasterKvBuilder::new(1 << 14, 64 * 1024 * 1024)
+        .with_disk(&dir_path)
+        .build()
+        .unwrap();
+    let session = store.session().unwrap();
+    let num_keys: u64 = 1 << 18;
+    for key in 0..num_keys {
+        session.upsert(&key, &key, key).unwrap();
+    }
+    session.complete_pending(true).unwrap();
+
+    let stats = store.stats();
+    assert!(stats.begin_address <= stats.head_address);
+    assert!(stats.head_address <= stats.read_only_address);
+    assert!(stats.read_only_address <= stats.tail_address);
+    assert_eq!(stats.tail_address, store.tail_address());
+    assert_eq!(
+        stats.in_memory_bytes,
+        stats.tail_address - stats.head_address
+    );
+    assert!(stats.on_disk_bytes > 0);
+    assert_eq!(stats.active_sessions, 1);
+}
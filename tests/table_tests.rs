@@ -0,0 +1,253 @@
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::codec::Raw;
+use faster_rs::{FasterError, FasterKv, FasterKvBuilder, ReadWith, Status};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+#[test]
+fn tables_keep_equal_keys_apart() {
+    let store = FasterKv::default();
+    let users = store.table::<u64, String>("users").unwrap();
+    let orders = store.table::<u64, u64>("orders").unwrap();
+
+    users.upsert(&1, &String::from("Ada"), 1).unwrap();
+    orders.upsert(&1, &42, 2).unwrap();
+    orders.rmw(&1, &8, 3).unwrap();
+
+    let (res, recv) = users.read(&1, 4).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), "Ada");
+    let (res, recv) = orders.read(&1, 5).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), 50);
+
+    // Keys outside of tables are kept apart from those of tables
+    let (res, _) = store.read::<u64, u64>(&1, 6).unwrap();
+    assert_eq!(res, Status::NotFound);
+
+    orders.delete(&1, 7).unwrap();
+    let (res, _) = orders.read(&1, 8).unwrap();
+    assert_eq!(res, Status::NotFound);
+    let (res, _) = users.read(&1, 9).unwrap();
+    assert_eq!(res, Status::Ok);
+}
+
+#[test]
+fn table_names_prefixing_each_other_do_not_collide() {
+    let store = FasterKv::default();
+    let short = store.table::<String, u64>("a").unwrap();
+    let long = store.table::<String, u64>("ab").unwrap();
+
+    long.upsert(&String::from("c"), &1, 1).unwrap();
+    short.upsert(&String::from("bc"), &2, 2).unwrap();
+
+    let (_, recv) = long.read(&String::from("c"), 3).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), 1);
+    let (_, recv) = short.read(&String::from("bc"), 4).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), 2);
+}
+
+#[test]
+fn table_iter_and_scan_only_see_their_table() {
+    let store = FasterKv::default();
+    let users = store.table::<u64, String>("users").unwrap();
+    let orders = store.table::<u64, u64>("orders").unwrap();
+
+    for key in 0..10u64 {
+        users.upsert(&key, &format!("user {}", key), key).unwrap();
+        orders.upsert(&key, &(key * 100), 10 + key).unwrap();
+    }
+    users.delete(&3, 20).unwrap();
+    orders.upsert(&4, &1, 21).unwrap();
+
+    let mut pairs: Vec<(u64, u64)> = orders.iter().unwrap().map(Result::unwrap).collect();
+    pairs.sort();
+    assert_eq!(pairs.len(), 10);
+    assert_eq!(pairs[4], (4, 1));
+
+    let mut keys: Vec<u64> = users.iter().unwrap().map(|pair| pair.unwrap().0).collect();
+    keys.sort();
+    assert_eq!(keys, vec![0, 1, 2, 4, 5, 6, 7, 8, 9]);
+
+    // Records of other tables are left out even when they decode as the table's types
+    let records = orders
+        .scan(store.begin_address(), store.tail_address())
+        .unwrap()
+        .count();
+    assert_eq!(records, 11);
+}
+
+#[test]
+fn keys_outside_of_tables_are_stored_unchanged() {
+    let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .build_with_codec::<Raw>()
+        .unwrap();
+    let users = store.table::<Vec<u8>, Vec<u8>>("users").unwrap();
+
+    // The bytes a table key would have if tables prefixed keys in the caller's key space
+    let mut prefixed = vec![1, 5];
+    prefixed.extend_from_slice(b"users");
+    prefixed.extend_from_slice(b"ada");
+    users
+        .upsert(&b"ada".to_vec(), &b"table".to_vec(), 1)
+        .unwrap();
+    store.upsert_bytes(&prefixed, b"root", 2).unwrap();
+
+    let (_, recv) = users.read(&b"ada".to_vec(), 3).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), b"table");
+    let (_, recv) = store.read_bytes(&prefixed, 4).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), b"root");
+
+    let keys: Vec<Vec<u8>> = store.scan(0, u64::MAX).unwrap().map(|r| r.key).collect();
+    assert_eq!(keys, vec![prefixed]);
+    let keys: Vec<Vec<u8>> = users.scan(0, u64::MAX).unwrap().map(|r| r.key).collect();
+    assert_eq!(keys, vec![b"ada".to_vec()]);
+}
+
+#[test]
+fn store_iter_and_scan_leave_out_tables() {
+    let store = FasterKv::default();
+    let orders = store.table::<u64, u64>("orders").unwrap();
+
+    for key in 0..10u64 {
+        store.upsert(&key, &key, key).unwrap();
+        orders.upsert(&key, &(key * 100), 10 + key).unwrap();
+    }
+
+    let mut pairs: Vec<(u64, u64)> = store.iter().unwrap().map(Result::unwrap).collect();
+    pairs.sort();
+    assert_eq!(pairs, (0..10u64).map(|key| (key, key)).collect::<Vec<_>>());
+    let records = store
+        .scan(store.begin_address(), store.tail_address())
+        .unwrap()
+        .count();
+    assert_eq!(records, 10);
+}
+
+#[test]
+fn tables_support_batches_and_conditional_writes() {
+    let store = FasterKv::default();
+    let orders = store.table::<u64, u64>("orders").unwrap();
+    store.upsert(&1u64, &1u64, 1).unwrap();
+
+    let pairs = [(1u64, 10u64), (2, 20)];
+    orders
+        .upsert_batch(pairs.iter().map(|(key, value)| (key, value)), 2)
+        .unwrap();
+    orders
+        .rmw_batch(pairs.iter().map(|(key, value)| (key, value)), 4)
+        .unwrap();
+    let values: Vec<Option<u64>> = orders
+        .read_batch(&[1, 2, 3], 6)
+        .unwrap()
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(values, vec![Some(20), Some(40), None]);
+
+    assert!(!orders.compare_and_swap(&1, &1, &11, 9).unwrap());
+    assert!(orders.compare_and_swap(&1, &20, &21, 10).unwrap());
+    assert!(orders.upsert_if_absent(&3, &30, 11).unwrap());
+    orders.rmw_with(&3, || 0, |value| value + 1, 12).unwrap();
+    match orders.read_with(&3, 13, |bytes| bytes.to_vec()).unwrap() {
+        ReadWith::Found(bytes) => assert_eq!(bytes, 31u64.to_le_bytes()),
+        _ => panic!("Value should be in memory"),
+    }
+
+    // The key outside of the table is untouched
+    let (_, recv) = store.read::<u64, u64>(&1, 14).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), 1);
+    assert!(store.upsert_if_absent(&3u64, &0u64, 15).unwrap());
+}
+
+#[test]
+fn clear_only_deletes_its_table() {
+    let store = FasterKv::default();
+    let users = store.table::<u64, String>("users").unwrap();
+    let orders = store.table::<u64, u64>("orders").unwrap();
+
+    for key in 0..100u64 {
+        users.upsert(&key, &format!("user {}", key), key).unwrap();
+        orders.upsert(&key, &key, 100 + key).unwrap();
+    }
+    users.delete(&0, 200).unwrap();
+
+    assert_eq!(users.clear(201).unwrap(), 99);
+    assert_eq!(users.iter().unwrap().count(), 0);
+    assert_eq!(users.estimate_size().unwrap().records, 0);
+    assert_eq!(orders.iter().unwrap().count(), 100);
+}
+
+#[test]
+fn estimate_size_counts_live_records() {
+    let store = FasterKv::default();
+    let orders = store.table::<u64, u64>("orders").unwrap();
+    let empty = store.table::<u64, u64>("empty").unwrap();
+
+    for key in 0..10u64 {
+        orders.upsert(&key, &key, key).unwrap();
+    }
+    orders.upsert(&0, &1, 10).unwrap();
+    orders.delete(&1, 11).unwrap();
+
+    let size = orders.estimate_size().unwrap();
+    assert_eq!(size.records, 9);
+    // Table id of "orders", a u64 key and a u64 value
+    assert_eq!(size.bytes, 9 * (8 + 8 + 8));
+    assert_eq!(empty.estimate_size().unwrap().records, 0);
+}
+
+#[test]
+fn table_names_are_checked() {
+    let store = FasterKv::default();
+    match store.table::<u64, u64>("") {
+        Err(FasterError::InvalidTableName(_)) => {}
+        _ => panic!("Should give InvalidTableName Error"),
+    }
+    match store.table::<u64, u64>(&"t".repeat(256)) {
+        Err(FasterError::InvalidTableName(_)) => {}
+        _ => panic!("Should give InvalidTableName Error"),
+    }
+    assert!(store.table::<u64, u64>(&"t".repeat(255)).is_ok());
+}
+
+#[test]
+fn tables_share_one_checkpoint() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let mut builder = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024);
+    builder.with_disk(&dir_path);
+
+    {
+        let store = builder.build().unwrap();
+        store.start_session().unwrap();
+        let users = store.table::<u64, String>("users").unwrap();
+        let orders = store.table::<u64, u64>("orders").unwrap();
+        users.upsert(&1, &String::from("Ada"), 1).unwrap();
+        orders.upsert(&1, &42, 2).unwrap();
+
+        store.checkpoint().unwrap();
+        let start = Instant::now();
+        while store.latest_checkpoint().unwrap().is_none()
+            && start.elapsed() < Duration::from_secs(30)
+        {
+            store.complete_pending(true).unwrap();
+            store.refresh();
+        }
+        store.stop_session();
+    }
+
+    let (store, _) = builder.recover_latest().unwrap();
+    store.start_session().unwrap();
+    let users = store.table::<u64, String>("users").unwrap();
+    let orders = store.table::<u64, u64>("orders").unwrap();
+    let (_, recv) = users.read(&1, 3).unwrap();
+    store.complete_pending(true).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), "Ada");
+    let (_, recv) = orders.read(&1, 4).unwrap();
+    store.complete_pending(true).unwrap();
+    assert_eq!(recv.recv().unwrap().unwrap(), 42);
+    store.stop_session();
+}
@@ -1,7 +1,6 @@
 extern crate faster_rs;
 
 use faster_rs::FasterKv;
-use std::sync::mpsc::Receiver;
 use std::sync::Arc;
 use std::thread;
 
@@ -12,10 +11,10 @@ fn multi_threaded_test() {
 
     let initial_value: u64 = 100;
     let modification: u64 = 30;
-    store.start_session();
+    store.start_session().unwrap();
 
     for key in 0..ops {
-        store.upsert(&(key as u64), &initial_value, key);
+        store.upsert(&(key as u64), &initial_value, key).unwrap();
     }
 
     let num_threads = 16;
@@ -24,14 +23,14 @@ fn multi_threaded_test() {
         let store = Arc::clone(&store);
         threads.push(thread::spawn(move || {
             // Register FASTER thread
-            let _session = store.start_session();
+            let _session = store.start_session().unwrap();
 
             for key in 0..ops {
-                store.rmw(&(key as u64), &modification, key);
+                store.rmw(&(key as u64), &modification, key).unwrap();
             }
 
             // Make sure everything is completed
-            store.complete_pending(true);
+            store.complete_pending(true).unwrap();
 
             // Unregister Thread
             store.stop_session();
@@ -44,9 +43,9 @@ fn multi_threaded_test() {
 
     for key in 0..ops {
         let expected_value = initial_value + (modification * num_threads);
-        let (_res, recv): (u8, Receiver<u64>) = store.read(&key, ops + key);
-        assert_eq!(recv.recv().unwrap(), expected_value);
+        let (_res, recv) = store.read::<u64, u64>(&key, ops + key).unwrap();
+        assert_eq!(recv.recv().unwrap().unwrap(), expected_value);
     }
-    store.complete_pending(true);
+    store.complete_pending(true).unwrap();
     store.stop_session();
 }
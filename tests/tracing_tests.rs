@@ -0,0 +1,269 @@
+#![cfg(feature = "tracing")]
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::{FasterKv, FasterKvBuilder, FasterRmw, Status};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+#[derive(Debug, Clone)]
+struct Recorded {
+    name: String,
+    level: Level,
+    fields: HashMap<String, String>,
+}
+
+impl Recorded {
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+}
+
+struct Fields<'a>(&'a mut HashMap<String, String>);
+
+impl<'a> Visit for Fields<'a> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+// Keeps every span and event emitted while it is the default subscriber
+#[derive(Clone, Default)]
+struct Recorder {
+    next_id: Arc<AtomicU64>,
+    spans: Arc<Mutex<HashMap<u64, Recorded>>>,
+    events: Arc<Mutex<Vec<Recorded>>>,
+}
+
+impl Recorder {
+    fn record<F: FnOnce()>(&self, f: F) {
+        tracing::subscriber::with_default(self.clone(), f)
+    }
+
+    fn spans(&self, name: &str) -> Vec<Recorded> {
+        let spans = self.spans.lock().unwrap();
+        let mut ids: Vec<&u64> = spans.keys().collect();
+        ids.sort();
+        ids.into_iter()
+            .map(|id| &spans[id])
+            .filter(|span| span.name == name)
+            .cloned()
+            .collect()
+    }
+
+    fn events(&self, message: &str) -> Vec<Recorded> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.field("message") == Some(message))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut recorded = Recorded {
+            name: attributes.metadata().name().to_string(),
+            level: *attributes.metadata().level(),
+            fields: HashMap::new(),
+        };
+        attributes.record(&mut Fields(&mut recorded.fields));
+        self.spans.lock().unwrap().insert(id, recorded);
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record) {
+        if let Some(recorded) = self.spans.lock().unwrap().get_mut(&span.into_u64()) {
+            values.record(&mut Fields(&mut recorded.fields));
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event) {
+        let mut recorded = Recorded {
+            name: event.metadata().name().to_string(),
+            level: *event.metadata().level(),
+            fields: HashMap::new(),
+        };
+        event.record(&mut Fields(&mut recorded.fields));
+        self.events.lock().unwrap().push(recorded);
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[derive(Serialize, Deserialize)]
+struct Explosive(u64);
+
+impl FasterRmw for Explosive {
+    fn rmw(&self, _modification: Self) -> Self {
+        panic!("Merge logic failed");
+    }
+}
+
+#[test]
+fn operations_emit_spans_with_outcome() {
+    let store = FasterKv::default();
+    let recorder = Recorder::default();
+
+    recorder.record(|| {
+        store.upsert(&1u64, &10u64, 1).unwrap();
+        store.rmw(&1u64, &5u64, 2).unwrap();
+        let (status, _) = store.read::<u64, u64>(&2u64, 3).unwrap();
+        assert_eq!(status, Status::NotFound);
+    });
+
+    let spans = recorder.spans("faster_operation");
+    let operations: Vec<(&str, &str)> = spans
+        .iter()
+        .map(|span| {
+            (
+                span.field("operation").unwrap(),
+                span.field("outcome").unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        operations,
+        vec![("upsert", "ok"), ("rmw", "ok"), ("read", "not_found")]
+    );
+    assert!(spans.iter().all(|span| span.level == Level::DEBUG));
+}
+
+#[test]
+fn failed_operations_are_recorded_as_errors() {
+    let store = FasterKv::default();
+    let recorder = Recorder::default();
+
+    recorder.record(|| {
+        assert!(store.checkpoint().is_err());
+    });
+
+    let spans = recorder.spans("faster_operation");
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].field("operation"), Some("checkpoint"));
+    assert_eq!(spans[0].field("outcome"), Some("error"));
+}
+
+#[test]
+fn session_lifecycle_emits_spans() {
+    let store = FasterKv::default();
+    let recorder = Recorder::default();
+
+    let mut id = String::new();
+    recorder.record(|| {
+        let session = store.session().unwrap();
+        id = session.id().to_string();
+    });
+
+    let lifecycle: Vec<String> = recorder
+        .spans("faster_session")
+        .iter()
+        .map(|span| span.field("event").unwrap().to_string())
+        .collect();
+    assert_eq!(lifecycle, vec!["start", "stop"]);
+    let started = recorder.events("started session");
+    assert_eq!(started.len(), 1);
+    assert_eq!(started[0].field("session"), Some(id.as_str()));
+}
+
+#[test]
+fn checkpoint_and_recovery_emit_events() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let mut builder = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024);
+    builder.with_disk(&dir_path);
+    let recorder = Recorder::default();
+
+    let token = {
+        let store = builder.build().unwrap();
+        store.start_session().unwrap();
+        store.upsert(&1u64, &1u64, 1).unwrap();
+        let mut token = String::new();
+        recorder.record(|| token = store.checkpoint().unwrap().token);
+        let start = Instant::now();
+        while store.latest_checkpoint().unwrap().is_none()
+            && start.elapsed() < Duration::from_secs(30)
+        {
+            store.complete_pending(true).unwrap();
+            store.refresh();
+        }
+        store.stop_session();
+        token
+    };
+
+    let started = recorder.events("started checkpoint");
+    assert_eq!(started.len(), 1);
+    assert_eq!(started[0].level, Level::INFO);
+    assert_eq!(started[0].field("token"), Some(token.as_str()));
+
+    recorder.record(|| {
+        builder.recover_latest().unwrap();
+    });
+
+    let recovered = recorder.events("recovered checkpoint");
+    assert_eq!(recovered.len(), 1);
+    assert_eq!(recovered[0].field("sessions"), Some("1"));
+    let recover = recorder
+        .spans("faster_operation")
+        .into_iter()
+        .find(|span| span.field("operation") == Some("recover"))
+        .unwrap();
+    assert_eq!(recover.field("outcome"), Some("ok"));
+}
+
+#[test]
+fn deserialization_errors_emit_events() {
+    let store = FasterKv::default();
+    store.upsert(&1u64, &u64::MAX, 1).unwrap();
+    let recorder = Recorder::default();
+
+    recorder.record(|| {
+        let (_, recv) = store.read::<u64, String>(&1u64, 2).unwrap();
+        assert!(recv.recv().unwrap().is_err());
+    });
+
+    let failures = recorder.events("failed to deserialize a value");
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].level, Level::WARN);
+    assert_eq!(failures[0].field("callback"), Some("read"));
+}
+
+#[test]
+fn callback_panics_emit_events() {
+    let store = FasterKv::default();
+    store.upsert(&1u64, &Explosive(1), 1).unwrap();
+    let recorder = Recorder::default();
+
+    recorder.record(|| {
+        assert!(store.rmw(&1u64, &Explosive(2), 2).is_err());
+    });
+
+    let panics = recorder.events("callback panicked");
+    assert_eq!(panics.len(), 1);
+    assert_eq!(panics[0].level, Level::ERROR);
+    assert_eq!(panics[0].field("callback"), Some("rmw"));
+}
@@ -0,0 +1,310 @@
+extern crate faster_rs;
+extern crate tempfile;
+
+use faster_rs::{
+    FasterConditionalRmw, FasterError, FasterKv, FasterKvBuilder, ReadWith, RmwAction, Status,
+    TtlPolicy,
+};
+use serde_derive::{Deserialize, Serialize};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+const SHORT: Duration = Duration::from_millis(20);
+const LONG: Duration = Duration::from_secs(3600);
+
+// Largest value seen so far, encoded like the u64 it wraps
+#[derive(Serialize, Deserialize)]
+struct Max(u64);
+
+impl FasterConditionalRmw for Max {
+    fn conditional_rmw(&self, modification: Self) -> RmwAction<Self> {
+        match modification.0 > self.0 {
+            true => RmwAction::Update(modification),
+            false => RmwAction::Keep,
+        }
+    }
+
+    fn initial(modification: Self) -> RmwAction<Self> {
+        match modification.0 {
+            0 => RmwAction::Keep,
+            _ => RmwAction::Update(modification),
+        }
+    }
+}
+
+fn ttl_store(policy: TtlPolicy) -> FasterKv {
+    FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .with_ttl(policy)
+        .build()
+        .unwrap()
+}
+
+fn expire() {
+    thread::sleep(SHORT * 3);
+}
+
+fn read_u64(store: &FasterKv, key: u64, serial: u64) -> Option<u64> {
+    let (res, recv) = store.read::<u64, u64>(&key, serial).unwrap();
+    match res {
+        Status::Ok => Some(recv.recv().unwrap().unwrap()),
+        Status::NotFound => None,
+        _ => panic!("Value should be in memory"),
+    }
+}
+
+#[test]
+fn upsert_with_ttl_requires_ttl() {
+    let store = FasterKv::default();
+    match store.upsert_with_ttl(&1u64, &1u64, LONG, 1) {
+        Err(FasterError::TtlNotEnabled) => {}
+        _ => panic!("Should give TtlNotEnabled Error"),
+    }
+}
+
+#[test]
+fn expired_records_are_not_found() {
+    let store = ttl_store(TtlPolicy::Preserve);
+    store.upsert_with_ttl(&1u64, &10u64, SHORT, 1).unwrap();
+    store.upsert_with_ttl(&2u64, &20u64, LONG, 2).unwrap();
+    store.upsert(&3u64, &30u64, 3).unwrap();
+    assert_eq!(read_u64(&store, 1, 4), Some(10));
+
+    expire();
+    assert_eq!(read_u64(&store, 1, 5), None);
+    assert_eq!(read_u64(&store, 2, 6), Some(20));
+    assert_eq!(read_u64(&store, 3, 7), Some(30));
+}
+
+#[test]
+fn rmw_preserves_expiry() {
+    let store = ttl_store(TtlPolicy::Preserve);
+    store.upsert_with_ttl(&1u64, &10u64, SHORT, 1).unwrap();
+    store.rmw(&1u64, &5u64, 2).unwrap();
+    assert_eq!(read_u64(&store, 1, 3), Some(15));
+
+    expire();
+    assert_eq!(read_u64(&store, 1, 4), None);
+}
+
+#[test]
+fn rmw_refreshes_expiry() {
+    let store = ttl_store(TtlPolicy::Refresh(LONG));
+    store.upsert_with_ttl(&1u64, &10u64, SHORT, 1).unwrap();
+    store.rmw(&1u64, &5u64, 2).unwrap();
+
+    expire();
+    assert_eq!(read_u64(&store, 1, 3), Some(15));
+}
+
+#[test]
+fn rmw_replaces_expired_records() {
+    let store = ttl_store(TtlPolicy::Preserve);
+    store.upsert_with_ttl(&1u64, &10u64, SHORT, 1).unwrap();
+
+    expire();
+    store.rmw(&1u64, &5u64, 2).unwrap();
+    assert_eq!(read_u64(&store, 1, 3), Some(5));
+}
+
+#[test]
+fn read_with_and_read_batch_skip_expired_records() {
+    let store = ttl_store(TtlPolicy::Preserve);
+    store.upsert_with_ttl(&1u64, &10u64, SHORT, 1).unwrap();
+    store.upsert_with_ttl(&2u64, &20u64, LONG, 2).unwrap();
+
+    expire();
+    match store.read_with(&1u64, 3, |bytes| bytes.len()).unwrap() {
+        ReadWith::NotFound => {}
+        _ => panic!("Expired record should not be found"),
+    }
+    match store.read_with(&2u64, 4, |bytes| bytes.len()).unwrap() {
+        ReadWith::Found(length) => assert_eq!(length, 8),
+        _ => panic!("Value should be found in memory"),
+    }
+
+    let values: Vec<Option<u64>> = store
+        .read_batch(&[1u64, 2u64], 5)
+        .unwrap()
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(values, vec![None, Some(20)]);
+}
+
+#[test]
+fn scans_report_expiry_and_iter_skips_expired_records() {
+    let store = ttl_store(TtlPolicy::Preserve);
+    store.upsert_with_ttl(&1u64, &10u64, SHORT, 1).unwrap();
+    store.upsert_with_ttl(&2u64, &20u64, LONG, 2).unwrap();
+    store.upsert(&3u64, &30u64, 3).unwrap();
+
+    let later = SystemTime::now() + LONG / 2;
+    for record in store
+        .scan(store.begin_address(), store.tail_address())
+        .unwrap()
+    {
+        match record.decode_key::<u64>().unwrap() {
+            1 => assert!(record.expires_at.unwrap() < later),
+            2 => assert!(record.expires_at.unwrap() > later),
+            _ => assert_eq!(record.expires_at, None),
+        }
+        assert_eq!(
+            record.decode_value::<u64>().unwrap(),
+            Some(record.decode_key::<u64>().unwrap() * 10)
+        );
+    }
+
+    expire();
+    let mut pairs: Vec<(u64, u64)> = store.iter().unwrap().map(Result::unwrap).collect();
+    pairs.sort();
+    assert_eq!(pairs, vec![(2, 20), (3, 30)]);
+}
+
+#[test]
+fn conditional_writes_treat_expired_records_as_missing() {
+    let store = ttl_store(TtlPolicy::Preserve);
+    store.upsert_with_ttl(&1u64, &10u64, SHORT, 1).unwrap();
+    store.upsert_with_ttl(&2u64, &20u64, SHORT, 2).unwrap();
+    store.upsert_with_ttl(&3u64, &30u64, SHORT, 3).unwrap();
+    assert!(!store.upsert_if_absent(&1u64, &11u64, 4).unwrap());
+
+    expire();
+    assert!(!store.compare_and_swap(&1u64, &10u64, &12u64, 5).unwrap());
+    assert_eq!(read_u64(&store, 1, 6), None);
+    assert!(store.upsert_if_absent(&2u64, &21u64, 7).unwrap());
+    assert_eq!(read_u64(&store, 2, 8), Some(21));
+    store.rmw_with(&3u64, || 1u64, |v| v + 1, 9).unwrap();
+    assert_eq!(read_u64(&store, 3, 10), Some(1));
+}
+
+#[test]
+fn conditional_writes_preserve_expiry() {
+    let store = ttl_store(TtlPolicy::Preserve);
+    store.upsert_with_ttl(&1u64, &10u64, SHORT, 1).unwrap();
+    store.upsert_with_ttl(&2u64, &20u64, SHORT, 2).unwrap();
+    store.upsert_with_ttl(&3u64, &30u64, SHORT, 3).unwrap();
+
+    assert!(store.compare_and_swap(&1u64, &10u64, &11u64, 4).unwrap());
+    store.rmw_with(&2u64, || 0u64, |v| v + 1, 5).unwrap();
+    assert_eq!(
+        store.rmw_conditional(&3u64, &Max(31), 6).unwrap(),
+        Status::Ok
+    );
+    assert_eq!(read_u64(&store, 1, 7), Some(11));
+    assert_eq!(read_u64(&store, 2, 8), Some(21));
+    assert_eq!(read_u64(&store, 3, 9), Some(31));
+
+    expire();
+    assert_eq!(read_u64(&store, 1, 10), None);
+    assert_eq!(read_u64(&store, 2, 11), None);
+    assert_eq!(read_u64(&store, 3, 12), None);
+}
+
+#[test]
+fn conditional_writes_refresh_expiry() {
+    let store = ttl_store(TtlPolicy::Refresh(LONG));
+    store.upsert_with_ttl(&1u64, &10u64, SHORT, 1).unwrap();
+    store.upsert_with_ttl(&2u64, &20u64, SHORT, 2).unwrap();
+    store.upsert_with_ttl(&3u64, &30u64, SHORT, 3).unwrap();
+
+    assert!(store.compare_and_swap(&1u64, &10u64, &11u64, 4).unwrap());
+    store.rmw_with(&2u64, || 0u64, |v| v + 1, 5).unwrap();
+    assert_eq!(
+        store.rmw_conditional(&3u64, &Max(31), 6).unwrap(),
+        Status::Ok
+    );
+
+    expire();
+    assert_eq!(read_u64(&store, 1, 7), Some(11));
+    assert_eq!(read_u64(&store, 2, 8), Some(21));
+    assert_eq!(read_u64(&store, 3, 9), Some(31));
+}
+
+#[test]
+fn rmw_conditional_keeping_an_expired_record_reports_not_found() {
+    let store = ttl_store(TtlPolicy::Preserve);
+    store.upsert_with_ttl(&1u64, &10u64, SHORT, 1).unwrap();
+
+    expire();
+    // A missing key is only stored by a positive maximum
+    assert_eq!(
+        store.rmw_conditional(&1u64, &Max(0), 2).unwrap(),
+        Status::NotFound
+    );
+    assert_eq!(read_u64(&store, 1, 3), None);
+}
+
+#[test]
+fn recover_rejects_checkpoints_with_another_ttl_setting() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let token = {
+        let store = FasterKvBuilder::new(1 << 14, 1024 * 1024 * 1024)
+            .with_disk(&dir_path)
+            .with_ttl(TtlPolicy::Preserve)
+            .build()
+            .unwrap();
+        let session = store.session().unwrap();
+        session.upsert_with_ttl(&1u64, &10u64, LONG, 1).unwrap();
+        let checkpoint = store.checkpoint().unwrap();
+        assert!(checkpoint.checked);
+        while store.checkpoint_status(&checkpoint.token).unwrap() == Status::Pending {
+            session.complete_pending(false).unwrap();
+            session.refresh();
+        }
+        checkpoint.token
+    };
+
+    let store = FasterKvBuilder::new(1 << 14, 1024 * 1024 * 1024)
+        .with_disk(&dir_path)
+        .build()
+        .unwrap();
+    match store.recover(token.clone(), token) {
+        Err(FasterError::TtlMismatch(true)) => {}
+        _ => panic!("Should give TtlMismatch Error"),
+    }
+}
+
+#[test]
+fn compaction_drops_expired_records() {
+    let tmp_dir = TempDir::new().unwrap();
+    let dir_path = tmp_dir.path().to_string_lossy().into_owned();
+    let store = FasterKvBuilder::new(1 << 14, 64 * 1024 * 1024)
+        .with_disk(&dir_path)
+        .with_log_mutable_fraction(0.1)
+        .with_ttl(TtlPolicy::Preserve)
+        .build()
+        .unwrap();
+    let session = store.session().unwrap();
+    let num_keys: u64 = 1 << 16;
+
+    for key in 0..num_keys {
+        let ttl = if key % 2 == 0 { SHORT } else { LONG };
+        session.upsert_with_ttl(&key, &key, ttl, key).unwrap();
+    }
+    expire();
+    // Push the records above into the read-only region
+    for key in num_keys..(num_keys * 4) {
+        session.upsert(&key, &key, key).unwrap();
+    }
+    session.complete_pending(true).unwrap();
+
+    let until_address = store.read_only_address();
+    store.compact(until_address).unwrap();
+    assert_eq!(store.begin_address(), until_address);
+
+    for key in (0..num_keys).filter(|key| key % 2 == 1) {
+        let (_status, recv) = session.read::<u64, u64>(&key, 4 * num_keys + key).unwrap();
+        session.complete_pending(true).unwrap();
+        assert_eq!(recv.recv().unwrap().unwrap(), key);
+    }
+
+    let expired = store
+        .scan(store.begin_address(), store.tail_address())
+        .unwrap()
+        .filter(|record| record.decode_key::<u64>().unwrap() < num_keys)
+        .filter(|record| record.decode_key::<u64>().unwrap() % 2 == 0)
+        .count();
+    assert_eq!(expired, 0);
+}
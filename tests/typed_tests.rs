@@ -0,0 +1,47 @@
+extern crate faster_rs;
+
+use faster_rs::{FasterKvBuilder, Status, TypedFasterKv};
+
+#[test]
+fn typed_read_inserted_value() {
+    let store: TypedFasterKv<u64, u64> = TypedFasterKv::default();
+    let key: u64 = 1;
+    let value: u64 = 1337;
+
+    let upsert = store.upsert(&key, &value, 1).unwrap();
+    assert!(upsert == Status::Ok || upsert == Status::Pending);
+
+    let (res, recv) = store.read(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), value);
+}
+
+#[test]
+fn typed_rmw_string() {
+    let store = FasterKvBuilder::new(1 << 15, 1024 * 1024 * 1024)
+        .build_typed::<String, String>()
+        .unwrap();
+    let key = String::from("greeting");
+
+    store.upsert(&key, &String::from("Hello, "), 1).unwrap();
+    let rmw = store.rmw(&key, &String::from("World!"), 1).unwrap();
+    assert!(rmw == Status::Ok || rmw == Status::Pending);
+
+    let (res, recv) = store.read(&key, 1).unwrap();
+    assert_eq!(res, Status::Ok);
+    assert_eq!(recv.recv().unwrap().unwrap(), "Hello, World!");
+}
+
+#[test]
+fn typed_delete_inserted_value() {
+    let store: TypedFasterKv<u64, String> = TypedFasterKv::default();
+    let key: u64 = 1;
+
+    store.upsert(&key, &String::from("value"), 1).unwrap();
+    let delete = store.delete(&key, 1).unwrap();
+    assert!(delete == Status::Ok || delete == Status::Pending);
+
+    let (res, recv) = store.read(&key, 1).unwrap();
+    assert_eq!(res, Status::NotFound);
+    assert!(recv.recv().is_err());
+}